@@ -1,25 +1,39 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use analyzer::{checker::RecursiveTypeChecker, project::Project};
+use analyzer::checker::{RecursiveTypeChecker, UnusedFunctionChecker, UnusedVariableChecker};
+use analyzer::config::AnalyzeConfig;
+use analyzer::module::Module;
+use analyzer::project::Project;
 use vfs::Vfs;
 
 use crate::error::{CompilerError, Result};
 
 /// 分析项目中的所有文件
-pub fn analyze_project(input_paths: &[PathBuf], vfs: &Vfs) -> Result<Project> {
+pub fn analyze_project(
+    input_paths: &[PathBuf],
+    vfs: &Vfs,
+    config: AnalyzeConfig,
+) -> Result<Project> {
     for input_path in input_paths {
-        let text = std::fs::read_to_string(input_path).map_err(CompilerError::Io)?;
+        let bytes = std::fs::read(input_path).map_err(CompilerError::Io)?;
         let absolute_path = input_path
             .canonicalize()
             .unwrap_or_else(|_| input_path.clone());
-        vfs.new_file(absolute_path, text);
+        vfs.new_file_from_bytes(absolute_path, bytes)?;
     }
 
     // 初始化并分析项目
-    let mut project = Project::new().with_checker::<RecursiveTypeChecker>();
+    let mut project = Project::new()
+        .with_config(config)
+        .with_checker::<RecursiveTypeChecker>()
+        .with_checker::<UnusedFunctionChecker>()
+        .with_checker::<UnusedVariableChecker>();
     project.full_initialize(vfs);
 
+    // 只有警告时照常编译，警告直接输出；`deny_warnings` 时警告也算错误
+    let has_error = project.modules.values().any(Module::has_errors);
+
     // 按文件收集错误
     let mut errors_by_file = HashMap::new();
     for module in project.modules.values_mut() {
@@ -29,9 +43,12 @@ pub fn analyze_project(input_paths: &[PathBuf], vfs: &Vfs) -> Result<Project> {
         }
     }
 
-    if !errors_by_file.is_empty() {
+    if has_error {
         return Err(CompilerError::Analyze(errors_by_file));
     }
+    if !errors_by_file.is_empty() {
+        CompilerError::Analyze(errors_by_file).report(vfs);
+    }
 
     Ok(project)
 }
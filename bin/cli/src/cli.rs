@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// airyc 编译器命令行参数
 #[derive(Parser, Debug)]
@@ -10,6 +10,9 @@ use clap::{Parser, ValueEnum};
     about = "https://github.com/widsnoy/airyc/"
 )]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// source file(s) (.airy) path - can specify multiple files
     pub input_path: Vec<PathBuf>,
 
@@ -24,6 +27,28 @@ pub struct Args {
     /// optimization level
     #[arg(short = 'O', default_value = "default")]
     pub opt_level: OptLevel,
+
+    /// trap on out-of-range runtime array indices
+    #[arg(long)]
+    pub bounds_check: bool,
+
+    /// enable a feature for `cfg("name")`, can be repeated
+    #[arg(long = "cfg", value_name = "FEATURE")]
+    pub features: Vec<String>,
+
+    /// treat warnings as errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+}
+
+/// 子命令
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// print the parse tree of a source file
+    Ast {
+        /// source file (.airy) path
+        file: PathBuf,
+    },
 }
 
 /// 编译输出目标
@@ -31,6 +56,8 @@ pub struct Args {
 pub enum EmitTarget {
     /// 输出 LLVM IR (.ll 文件)
     Ir,
+    /// 输出汇编 (.s 文件)
+    Asm,
     /// 输出可执行文件
     Exe,
     /// 输出 AST
@@ -5,11 +5,10 @@ use analyzer::module::Module;
 use analyzer::project::Project;
 use codegen::error::{CodegenError, Result};
 use codegen::llvm_ir::Program;
+use inkwell::builder::Builder;
 use inkwell::context::Context as LlvmContext;
 use inkwell::passes::PassBuilderOptions;
-use inkwell::targets::{
-    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
-};
+use inkwell::targets::{FileType, TargetMachine};
 use rayon::prelude::*;
 use rowan::GreenNode;
 use syntax::SyntaxNode;
@@ -25,10 +24,21 @@ pub fn compile_to_ir_file(
     green_node: GreenNode,
     analyzer: &Module,
     opt_level: OptLevel,
+    bounds_check: bool,
     output_path: &Path,
 ) -> Result<()> {
     let context = LlvmContext::create();
-    let module = generate_and_optimize(&context, module_name, green_node, analyzer, opt_level)?;
+    let module = context.create_module(module_name);
+    let builder = context.create_builder();
+    generate_and_optimize(
+        &context,
+        &builder,
+        &module,
+        green_node,
+        analyzer,
+        opt_level,
+        bounds_check,
+    )?;
     module
         .print_to_file(output_path)
         .map_err(|e| CodegenError::LlvmWrite(e.to_string()))?;
@@ -39,6 +49,35 @@ pub fn compile_to_ir_file(
     Ok(())
 }
 
+/// 编译到汇编文件
+/// 将语义分析后的 AST 转换为目标平台的汇编并写入 .s 文件
+pub fn compile_to_asm_file(
+    module_name: &str,
+    green_node: GreenNode,
+    analyzer: &Module,
+    opt_level: OptLevel,
+    bounds_check: bool,
+    output_path: &Path,
+) -> Result<()> {
+    let context = LlvmContext::create();
+    let module = context.create_module(module_name);
+    let builder = context.create_builder();
+    let program = generate_and_optimize(
+        &context,
+        &builder,
+        &module,
+        green_node,
+        analyzer,
+        opt_level,
+        bounds_check,
+    )?;
+    module
+        .verify()
+        .map_err(|e| CodegenError::LlvmVerification(e.to_string_lossy().to_string()))?;
+
+    program.emit_asm(output_path, opt_level.into())
+}
+
 /// 编译到目标文件字节数据
 /// 将语义分析后的 AST 转换为目标文件（.o）的字节数据
 pub fn compile_to_object_bytes(
@@ -46,9 +85,20 @@ pub fn compile_to_object_bytes(
     green_node: GreenNode,
     analyzer: &Module,
     opt_level: OptLevel,
+    bounds_check: bool,
 ) -> Result<Vec<u8>> {
     let context = LlvmContext::create();
-    let module = generate_and_optimize(&context, module_name, green_node, analyzer, opt_level)?;
+    let module = context.create_module(module_name);
+    let builder = context.create_builder();
+    generate_and_optimize(
+        &context,
+        &builder,
+        &module,
+        green_node,
+        analyzer,
+        opt_level,
+        bounds_check,
+    )?;
     module
         .verify()
         .map_err(|e| CodegenError::LlvmVerification(e.to_string_lossy().to_string()))?;
@@ -65,23 +115,23 @@ pub fn compile_to_object_bytes(
 }
 
 /// 生成并优化 LLVM IR
-fn generate_and_optimize<'ctx>(
+fn generate_and_optimize<'a, 'ctx>(
     context: &'ctx LlvmContext,
-    module_name: &str,
+    builder: &'a Builder<'ctx>,
+    module: &'a inkwell::module::Module<'ctx>,
     green_node: GreenNode,
-    analyzer: &Module,
+    analyzer: &'a Module,
     opt_level: OptLevel,
-) -> Result<inkwell::module::Module<'ctx>> {
-    let module = context.create_module(module_name);
-    let builder = context.create_builder();
-
+    bounds_check: bool,
+) -> Result<Program<'a, 'ctx>> {
     let mut program = Program {
         context,
-        builder: &builder,
-        module: &module,
+        builder,
+        module,
         analyzer,
         symbols: Default::default(),
         string_constants: HashMap::new(),
+        bounds_check,
     };
 
     let root = SyntaxNode::new_root(green_node);
@@ -95,9 +145,9 @@ fn generate_and_optimize<'ctx>(
     module.set_data_layout(&machine.get_target_data().get_data_layout());
 
     // 运行 LLVM IR 优化 pass
-    run_optimization_passes(&module, &machine, opt_level)?;
+    run_optimization_passes(module, &machine, opt_level)?;
 
-    Ok(module)
+    Ok(program)
 }
 
 /// 将 Project 中的所有模块分别编译为目标文件
@@ -106,6 +156,7 @@ pub fn compile_project_to_object_bytes(
     project: &Project,
     vfs: &Vfs,
     opt_level: OptLevel,
+    bounds_check: bool,
 ) -> Result<Vec<(String, Vec<u8>)>> {
     project
         .modules
@@ -126,6 +177,7 @@ pub fn compile_project_to_object_bytes(
                 module.green_tree.clone(),
                 module,
                 opt_level,
+                bounds_check,
             )?;
 
             Ok((module_name, object_bytes))
@@ -133,24 +185,9 @@ pub fn compile_project_to_object_bytes(
         .collect::<Result<Vec<_>>>()
 }
 
-/// 创建目标机器
-/// 初始化 LLVM 目标并创建目标机器实例
+/// 创建目标机器，与 `Program::emit_object`/`emit_asm` 使用同一套配置
 fn create_target_machine(opt_level: OptLevel) -> Result<TargetMachine> {
-    Target::initialize_all(&InitializationConfig::default());
-    let triple = inkwell::targets::TargetMachine::get_default_triple();
-    let target =
-        Target::from_triple(&triple).map_err(|e| CodegenError::TargetMachine(e.to_string()))?;
-
-    target
-        .create_target_machine(
-            &triple,
-            "generic",
-            "",
-            opt_level.into(),
-            RelocMode::PIC,
-            CodeModel::Default,
-        )
-        .ok_or_else(|| CodegenError::TargetMachine("failed to create target machine".to_string()))
+    Program::target_machine(opt_level.into())
 }
 
 /// 运行 LLVM IR 优化 pass
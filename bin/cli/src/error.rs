@@ -14,6 +14,9 @@ pub enum CompilerError {
     #[error("failed to read input file: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("failed to decode input file: {0}")]
+    Decode(#[from] vfs::DecodeError),
+
     #[error("analyze errors occurred")]
     Analyze(HashMap<FileID, Vec<AnalyzeError>>),
 
@@ -26,7 +29,7 @@ pub enum CompilerError {
 
 impl CompilerError {
     /// 报告编译错误
-    pub fn report(self, vfs: Vfs) {
+    pub fn report(self, vfs: &Vfs) {
         match self {
             Self::Analyze(semantic_errors) => {
                 for (file_id, errors) in semantic_errors {
@@ -34,6 +37,18 @@ impl CompilerError {
                         let source =
                             NamedSource::new(file.path.to_string_lossy(), file.text.clone());
                         for error in errors {
+                            // 语法错误输出 `file:line:col`，方便编辑器跳转
+                            if let AnalyzeError::ParserError(e) = &error {
+                                let (line, col) = e.location(&file.line_index);
+                                println!(
+                                    "{}:{}:{}: parse error: {}",
+                                    file.path.display(),
+                                    line,
+                                    col,
+                                    e
+                                );
+                                continue;
+                            }
                             let report =
                                 miette::Report::new(error).with_source_code(source.clone());
                             println!("{:?}", report);
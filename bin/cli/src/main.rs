@@ -3,21 +3,27 @@ mod cli;
 mod compiling;
 mod error;
 mod linking;
+mod parsing;
 
-use std::fs;
+use std::path::Path;
 
+use analyzer::config::AnalyzeConfig;
 use clap::Parser;
-use cli::{Args, EmitTarget};
+use cli::{Args, Command, EmitTarget};
 use rayon::prelude::*;
-use syntax::SyntaxNode;
 use vfs::Vfs;
 
-use crate::compiling::{compile_project_to_object_bytes, compile_to_ir_file};
+use crate::compiling::{compile_project_to_object_bytes, compile_to_asm_file, compile_to_ir_file};
 
 fn main() {
     let args = Args::parse();
     let vfs = Vfs::default();
 
+    if let Some(Command::Ast { file }) = &args.command {
+        dump_ast(file, &vfs);
+        return;
+    }
+
     // 检查是否有输入文件
     if args.input_path.is_empty() {
         eprintln!("Error: no input files specified");
@@ -31,27 +37,21 @@ fn main() {
             std::process::exit(1);
         }
 
-        let input_path = &args.input_path[0];
-        let input = match fs::read_to_string(input_path) {
-            Ok(v) => v,
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
-        };
-
-        let parser = parser::parse::Parser::new(&input);
-        let (green_node, _) = parser.parse();
-
-        println!("{:#?}", SyntaxNode::new_root(green_node));
+        dump_ast(&args.input_path[0], &vfs);
         return;
     }
 
     // 语义分析
-    let project = match analyzing::analyze_project(&args.input_path, &vfs) {
+    let config = AnalyzeConfig {
+        features: args.features.iter().cloned().collect(),
+        deny_warnings: args.deny_warnings,
+        executable: args.emit == EmitTarget::Exe,
+        ..Default::default()
+    };
+    let project = match analyzing::analyze_project(&args.input_path, &vfs, config) {
         Ok(project) => project,
         Err(e) => {
-            e.report(vfs);
+            e.report(&vfs);
             std::process::exit(1);
         }
     };
@@ -66,11 +66,12 @@ fn main() {
     }
 
     let opt_level = args.opt_level;
+    let bounds_check = args.bounds_check;
 
     // 代码生成
     match args.emit {
-        EmitTarget::Ir => {
-            // 为每个模块生成 IR 文件（并行）
+        EmitTarget::Ir | EmitTarget::Asm => {
+            // 为每个模块生成 IR 或汇编文件（并行）
             if let Err(e) = project
                 .modules
                 .par_iter()
@@ -85,14 +86,28 @@ fn main() {
                         })
                         .unwrap_or_else(|| "unknown".to_string());
 
-                    let output_path = args.output_dir.join(format!("{}.ll", module_name));
-                    compile_to_ir_file(
-                        &module_name,
-                        module.green_tree.clone(),
-                        module,
-                        opt_level,
-                        &output_path,
-                    )
+                    let green_tree = module.green_tree.clone();
+                    if args.emit == EmitTarget::Asm {
+                        let output_path = args.output_dir.join(format!("{}.s", module_name));
+                        compile_to_asm_file(
+                            &module_name,
+                            green_tree,
+                            module,
+                            opt_level,
+                            bounds_check,
+                            &output_path,
+                        )
+                    } else {
+                        let output_path = args.output_dir.join(format!("{}.ll", module_name));
+                        compile_to_ir_file(
+                            &module_name,
+                            green_tree,
+                            module,
+                            opt_level,
+                            bounds_check,
+                            &output_path,
+                        )
+                    }
                 })
                 .collect::<codegen::error::Result<Vec<_>>>()
             {
@@ -102,13 +117,14 @@ fn main() {
         }
         EmitTarget::Exe => {
             // 生成所有模块的目标文件
-            let object_files = match compile_project_to_object_bytes(&project, &vfs, opt_level) {
-                Ok(v) => v,
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-            };
+            let object_files =
+                match compile_project_to_object_bytes(&project, &vfs, opt_level, bounds_check) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                };
 
             // 确定输出文件名（使用第一个文件的名称）
             let output_name = args.input_path[0]
@@ -127,3 +143,15 @@ fn main() {
         EmitTarget::Ast | EmitTarget::Check => {}
     };
 }
+
+/// 输出语法树，有语法错误时以非零状态退出
+fn dump_ast(input_path: &Path, vfs: &Vfs) {
+    match parsing::dump_ast(input_path, vfs) {
+        Ok(0) => {}
+        Ok(_) => std::process::exit(1),
+        Err(e) => {
+            e.report(vfs);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use syntax::SyntaxNode;
+use vfs::Vfs;
+
+use crate::error::{CompilerError, Result};
+
+/// 解析单个文件，输出带文本的语法树和 `file:line:col` 格式的语法错误
+///
+/// 返回语法错误的数量
+pub fn dump_ast(input_path: &Path, vfs: &Vfs) -> Result<usize> {
+    let bytes = std::fs::read(input_path).map_err(CompilerError::Io)?;
+    let absolute_path = input_path
+        .canonicalize()
+        .unwrap_or_else(|_| input_path.to_path_buf());
+    let file_id = vfs.new_file_from_bytes(absolute_path, bytes)?;
+
+    let error_count = vfs
+        .with_file(&file_id, |file| {
+            let (green_node, errors) = parser::parse::Parser::new(&file.text).parse();
+            println!("{:#?}", SyntaxNode::new_root(green_node));
+            for e in &errors {
+                let (line, col) = e.location(&file.line_index);
+                println!(
+                    "{}:{}:{}: parse error: {}",
+                    file.path.display(),
+                    line,
+                    col,
+                    e
+                );
+            }
+            errors.len()
+        })
+        .unwrap_or_default();
+    Ok(error_count)
+}
@@ -0,0 +1,28 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_ast_subcommand() {
+    let dir = std::env::temp_dir().join(format!("airyc_cli_ast_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("sample.airy");
+    fs::write(
+        &path,
+        "fn main() -> i32 {\n    return 0;\n}\nlet x: i32 = ;\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_airyc-cli"))
+        .arg("ast")
+        .arg(&path)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    fs::remove_dir_all(&dir).unwrap();
+
+    for kind in ["COMP_UNIT", "FUNC_DEF", "RETURN_STMT", "VAR_DEF"] {
+        assert!(stdout.contains(kind), "missing {kind}:\n{stdout}");
+    }
+    assert!(stdout.contains("sample.airy:4:14: parse error"), "{stdout}");
+    assert!(!output.status.success());
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use analyzer::checker::RecursiveTypeChecker;
+use analyzer::checker::{RecursiveTypeChecker, UnusedFunctionChecker, UnusedVariableChecker};
 use analyzer::project::Project;
 use dashmap::DashMap;
 use parking_lot::RwLock;
@@ -32,7 +32,10 @@ pub(crate) struct Backend {
 
 impl Backend {
     pub fn new(client: Client) -> Self {
-        let project = Project::new().with_checker::<RecursiveTypeChecker>();
+        let project = Project::new()
+            .with_checker::<RecursiveTypeChecker>()
+            .with_checker::<UnusedFunctionChecker>()
+            .with_checker::<UnusedVariableChecker>();
         Self {
             client,
             project: RwLock::new(project),
@@ -87,11 +90,11 @@ impl Backend {
                 let uri = entry.key().clone();
                 let file_id = *entry.value();
 
-                if let Some(module) = project.modules.get(&file_id)
+                if project.modules.contains_key(&file_id)
                     && let Some(file) = self.vfs.get_file_by_file_id(&file_id)
                 {
                     let diagnostics = lsp_features::diagnostics::compute_diagnostics(
-                        &module.semantic_errors,
+                        &project.diagnostics(file_id),
                         &file.line_index,
                     );
 
@@ -189,9 +192,20 @@ impl LanguageServer for Backend {
                 // ),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![
+                        lsp_features::dump_types::DUMP_TYPES_COMMAND.to_string(),
+                        lsp_features::eval::EVAL_COMMAND.to_string(),
+                    ],
+                    work_done_progress_options: Default::default(),
+                }),
                 // completion_provider: Some(CompletionOptions {
                 //     trigger_characters: Some(vec![".".to_string(), "->".to_string()]),
                 //     all_commit_characters: None,
@@ -263,12 +277,12 @@ impl LanguageServer for Backend {
         // 提取诊断数据
         let Some(diagnostics) = (|| {
             let project = self.project.read();
-            let module = project.modules.get(&file_id)?;
+            project.modules.get(&file_id)?;
             let file = self.vfs.get_file_by_file_id(&file_id)?;
 
             // 计算诊断信息
             Some(lsp_features::diagnostics::compute_diagnostics(
-                &module.semantic_errors,
+                &project.diagnostics(file_id),
                 &file.line_index,
             ))
         })() else {
@@ -384,6 +398,66 @@ impl LanguageServer for Backend {
         ))
     }
 
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = params.text_document.uri;
+        let position = params.position;
+
+        let Some(file_id) = self.get_file_id(&uri) else {
+            return Ok(None);
+        };
+        let project = self.project.read();
+        let (Some(module), Some(file)) = (
+            project.modules.get(&file_id),
+            self.vfs.get_file_by_file_id(&file_id),
+        ) else {
+            return Ok(None);
+        };
+        let range =
+            lsp_features::rename::prepare_rename(position, &file.line_index, module, &project);
+
+        match range {
+            Some(range) => Ok(Some(PrepareRenameResponse::Range(range))),
+            None => Err(tower_lsp_server::jsonrpc::Error::invalid_params(
+                "cannot rename this element",
+            )),
+        }
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        let file_id = match self.get_file_id(&uri) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let project = self.project.read();
+        let module = match project.modules.get(&file_id) {
+            Some(m) => m,
+            None => return Ok(None),
+        };
+
+        match lsp_features::rename::rename(
+            uri,
+            position,
+            &params.new_name,
+            module,
+            &project,
+            &self.vfs,
+            |file_id| self.get_uri_by_file_id(file_id),
+        ) {
+            Some(edit) => Ok(Some(edit)),
+            None => Err(tower_lsp_server::jsonrpc::Error::invalid_params(format!(
+                "cannot rename this element to '{}'",
+                params.new_name
+            ))),
+        }
+    }
+
     async fn completion(&self, _params: CompletionParams) -> Result<Option<CompletionResponse>> {
         Ok(None)
         // let uri = params.text_document_position.text_document.uri;
@@ -439,4 +513,50 @@ impl LanguageServer for Backend {
             |file_id| self.get_uri_by_file_id(file_id),
         ))
     }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<LSPAny>> {
+        let command = params.command.as_str();
+        if command != lsp_features::dump_types::DUMP_TYPES_COMMAND
+            && command != lsp_features::eval::EVAL_COMMAND
+        {
+            return Err(tower_lsp_server::jsonrpc::Error::method_not_found());
+        }
+
+        // 第一个参数为目标文件的 URI
+        let Some(uri) = params
+            .arguments
+            .first()
+            .and_then(|arg| arg.as_str())
+            .and_then(|s| s.parse::<Uri>().ok())
+        else {
+            return Err(tower_lsp_server::jsonrpc::Error::invalid_params(
+                "expected a document uri",
+            ));
+        };
+
+        if command == lsp_features::dump_types::DUMP_TYPES_COMMAND {
+            return Ok(self.with_module_and_line_index(&uri, |module, line_index| {
+                lsp_features::dump_types::dump_types(module, line_index)
+            }));
+        }
+
+        // nanoc/eval 的第二个参数为表达式所在的范围
+        let Some(range) = params
+            .arguments
+            .get(1)
+            .and_then(|arg| serde_json::from_value::<Range>(arg.clone()).ok())
+        else {
+            return Err(tower_lsp_server::jsonrpc::Error::invalid_params(
+                "expected a source range",
+            ));
+        };
+        let Some(file_id) = self.get_file_id(&uri) else {
+            return Ok(None);
+        };
+        let project = self.project.read();
+        let Some(module) = project.modules.get(&file_id) else {
+            return Ok(None);
+        };
+        Ok(lsp_features::eval::eval(module, &self.vfs, file_id, range))
+    }
 }
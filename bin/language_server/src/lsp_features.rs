@@ -1,8 +1,11 @@
 pub(crate) mod completion;
 pub(crate) mod diagnostics;
 pub(crate) mod document_symbols;
+pub(crate) mod dump_types;
+pub(crate) mod eval;
 pub(crate) mod goto_definition;
 pub(crate) mod hover;
 pub(crate) mod references;
+pub(crate) mod rename;
 pub(crate) mod workspace_symbols;
 // pub(crate) mod semantic_tokens;
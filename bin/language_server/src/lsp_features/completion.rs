@@ -1,4 +1,4 @@
-// pub(crate) mod keyword;
+pub(crate) mod keyword;
 //
 // use analyzer::module::Module;
 // use tools::LineIndex;
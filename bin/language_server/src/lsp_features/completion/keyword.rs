@@ -2,33 +2,60 @@ use tower_lsp_server::ls_types::{CompletionItem, CompletionItemKind};
 
 /// Airyc 语言关键字列表
 ///
-/// 包含所有语言关键字及其分类
+/// 包含所有语言关键字及其说明，补全和 hover 共用
 const KEYWORDS: &[(&str, &str)] = &[
     // 类型关键字
-    ("i32", "32bit integer type"),
-    ("f32", "32bit float type"),
-    ("void", "void type"),
+    ("i32", "32 位有符号整数"),
+    ("i8", "8 位有符号整数"),
+    ("u8", "8 位无符号整数"),
+    ("u32", "32 位无符号整数"),
+    ("i64", "64 位有符号整数"),
+    ("u64", "64 位无符号整数"),
+    ("bool", "布尔类型"),
+    ("void", "空类型，用于没有返回值的函数"),
     // 控制流关键字
-    ("if", "condition statement"),
+    ("if", "条件语句"),
     ("else", "条件分支"),
     ("while", "循环语句"),
-    ("break", "跳出循环"),
+    ("for", "带初始化、条件和步进的循环"),
+    ("break", "跳出循环或 switch"),
     ("continue", "继续下一次循环"),
     ("return", "返回语句"),
+    ("switch", "多分支语句"),
+    ("case", "switch 的分支"),
+    ("default", "switch 的默认分支"),
+    ("goto", "保留关键字，不支持 goto"),
     // 声明关键字
     ("let", "变量声明"),
     ("fn", "函数声明"),
     ("struct", "结构体声明"),
+    ("enum", "枚举声明"),
+    ("type", "类型别名声明"),
     ("const", "常量修饰符"),
     ("mut", "可变修饰符"),
+    ("restrict", "指针不别名修饰符"),
     // 其他关键字
+    ("import", "导入其他文件的符号"),
     ("attach", "函数附加"),
+    ("extern", "外部函数声明"),
+    ("null", "空指针"),
+    ("true", "布尔值真"),
+    ("false", "布尔值假"),
 ];
 
+/// 关键字的说明
+pub(crate) fn keyword_doc(keyword: &str) -> Option<&'static str> {
+    KEYWORDS
+        .iter()
+        .find(|(k, _)| *k == keyword)
+        .map(|(_, description)| *description)
+}
+
 /// 生成所有关键字的补全项
 ///
 /// # 返回值
 /// 返回包含所有 Airyc 关键字的 CompletionItem 列表
+#[allow(dead_code)] // 补全暂未启用
 pub(crate) fn complete_keywords() -> Vec<CompletionItem> {
     KEYWORDS
         .iter()
@@ -1,41 +1,33 @@
 use crate::utils::position_trans::text_range_to_ls_range;
-use analyzer::error::AnalyzeError;
-use miette::Diagnostic as _;
+use analyzer::diagnostic::Diagnostic as AnalyzeDiagnostic;
 use tools::LineIndex;
 use tower_lsp_server::ls_types::*;
 
-/// 将所有错误转换为 LSP Diagnostic
-pub fn compute_diagnostics(errors: &[AnalyzeError], line_index: &LineIndex) -> Vec<Diagnostic> {
-    errors
+/// 将所有诊断转换为 LSP Diagnostic
+pub fn compute_diagnostics(
+    diagnostics: &[AnalyzeDiagnostic],
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    diagnostics
         .iter()
-        .filter_map(|error| error_to_diagnostic(error, line_index))
+        .map(|d| to_lsp_diagnostic(d, line_index))
         .collect()
 }
 
-/// 将单个错误转换为 LSP Diagnostic
-fn error_to_diagnostic(error: &AnalyzeError, line_index: &LineIndex) -> Option<Diagnostic> {
-    let mut message = error.to_string();
-
-    if let Some(help) = error.help() {
-        message = format!("{}\nhelp: {}", message, help)
-    }
-
-    let code = error.code().map(|c| c.to_string());
-    let range = text_range_to_ls_range(line_index, *error.range());
-    let severity = match error.severity() {
-        Some(e) => match e {
-            miette::Severity::Advice => DiagnosticSeverity::HINT,
-            miette::Severity::Warning => DiagnosticSeverity::WARNING,
-            miette::Severity::Error => DiagnosticSeverity::ERROR,
-        },
-        None => DiagnosticSeverity::ERROR,
+/// 将单个诊断转换为 LSP Diagnostic
+fn to_lsp_diagnostic(diagnostic: &AnalyzeDiagnostic, line_index: &LineIndex) -> Diagnostic {
+    let severity = match diagnostic.severity {
+        miette::Severity::Advice => DiagnosticSeverity::HINT,
+        miette::Severity::Warning => DiagnosticSeverity::WARNING,
+        miette::Severity::Error => DiagnosticSeverity::ERROR,
     };
-    Some(Diagnostic {
-        range,
+    Diagnostic {
+        range: text_range_to_ls_range(line_index, diagnostic.range),
         severity: Some(severity),
-        code: code.map(NumberOrString::String),
-        message,
+        // 使用稳定错误码，而不是 miette 的 `semantic::*` 代码
+        code: Some(NumberOrString::String(diagnostic.code.to_string())),
+        message: diagnostic.message.clone(),
         source: None,
         ..Default::default()
-    })
+    }
 }
@@ -0,0 +1,34 @@
+use analyzer::module::Module;
+use serde::Serialize;
+use tools::LineIndex;
+use tower_lsp_server::ls_types::{LSPAny, Range};
+
+use crate::utils::position_trans::text_range_to_ls_range;
+
+/// `workspace/executeCommand` 的命令名，参数为文件 URI
+pub(crate) const DUMP_TYPES_COMMAND: &str = "nanoc/dumpTypes";
+
+/// 编辑器类型浮层中的一项
+#[derive(Debug, Serialize)]
+struct TypeEntry {
+    range: Range,
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+/// 列出文件中每个表达式的类型和折叠出的常量
+pub(crate) fn dump_types(module: &Module, line_index: &LineIndex) -> LSPAny {
+    let entries: Vec<_> = module
+        .dump_types()
+        .into_iter()
+        .map(|entry| TypeEntry {
+            range: text_range_to_ls_range(line_index, entry.range),
+            ty: entry.ty.to_string(),
+            value: entry.value.map(|v| v.to_string()),
+        })
+        .collect();
+
+    serde_json::to_value(entries).unwrap_or_default()
+}
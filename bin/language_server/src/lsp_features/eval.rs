@@ -0,0 +1,56 @@
+use analyzer::module::Module;
+use serde::Serialize;
+use tools::TextRange;
+use tower_lsp_server::ls_types::{LSPAny, Range};
+use vfs::{FileID, Vfs};
+
+use crate::utils::position_trans::ls_position_to_offset;
+
+/// `workspace/executeCommand` 的命令名，参数为文件 URI 和表达式所在的范围
+pub(crate) const EVAL_COMMAND: &str = "nanoc/eval";
+
+/// 求值结果：常量的值，或者说明不能求值的原因
+#[derive(Debug, Serialize)]
+struct EvalResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+/// 在选区所在的作用域中分析选中的表达式，返回折叠出的常量
+pub(crate) fn eval(module: &Module, vfs: &Vfs, file_id: FileID, range: Range) -> Option<LSPAny> {
+    let (start, end) = vfs.with_file(&file_id, |file| {
+        (
+            ls_position_to_offset(&file.line_index, &range.start),
+            ls_position_to_offset(&file.line_index, &range.end),
+        )
+    })?;
+    if start > end {
+        return None;
+    }
+    let source = vfs.text_slice(&file_id, TextRange::new(start, end))?;
+
+    let result = match module.eval_expr_at(&source, start) {
+        Ok(Some(value)) => EvalResult {
+            value: Some(value.to_string()),
+            message: None,
+        },
+        Ok(None) => EvalResult {
+            value: None,
+            message: Some("expression is not a compile-time constant".to_string()),
+        },
+        Err(errors) => EvalResult {
+            value: None,
+            message: Some(
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+        },
+    };
+
+    Some(serde_json::to_value(result).unwrap_or_default())
+}
@@ -1,12 +1,14 @@
 use analyzer::module::Module;
+use syntax::{SyntaxKind, SyntaxNode};
 use tools::LineIndex;
 use tower_lsp_server::ls_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
 
+use crate::lsp_features::completion::keyword::keyword_doc;
 use crate::utils::get_at_position::{
     get_function_id_at_position, get_reference_id_at_position, get_struct_id_at_position,
-    get_variable_id_at_position,
+    get_token_at_offset, get_variable_id_at_position,
 };
-use crate::utils::position_trans::text_range_to_ls_range;
+use crate::utils::position_trans::{ls_position_to_offset, text_range_to_ls_range};
 
 pub(crate) fn hover(pos: Position, line_index: &LineIndex, module: &Module) -> Option<Hover> {
     // 先检查是否为引用，如果是引用则显示被引用元素的定义
@@ -47,7 +49,61 @@ pub(crate) fn hover(pos: Position, line_index: &LineIndex, module: &Module) -> O
         return build_hover_for_struct(module, struct_id, line_index, struct_def.range);
     }
 
-    None
+    // 最后退回到关键字和运算符的说明
+    build_hover_for_token(module, line_index, &pos)
+}
+
+/// 为关键字或运算符构建 hover 信息
+fn build_hover_for_token(module: &Module, line_index: &LineIndex, pos: &Position) -> Option<Hover> {
+    let root = SyntaxNode::new_root(module.green_tree.clone());
+    let offset = ls_position_to_offset(line_index, pos);
+    let token = get_token_at_offset(&root, offset)?;
+    let doc = if token.kind().is_keyword() {
+        keyword_doc(token.text())?
+    } else {
+        operator_doc(token.kind())?
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover_markdown(token.text(), Some(doc)),
+        }),
+        range: Some(text_range_to_ls_range(
+            line_index,
+            token.text_range().into(),
+        )),
+    })
+}
+
+/// 运算符的简短说明，关键字的说明见 `completion::keyword::KEYWORDS`
+fn operator_doc(kind: SyntaxKind) -> Option<&'static str> {
+    let doc = match kind {
+        SyntaxKind::PLUS => "加法，或一元正号",
+        SyntaxKind::MINUS => "减法，或取负",
+        SyntaxKind::STAR => "乘法，或指针解引用",
+        SyntaxKind::SLASH => "除法",
+        SyntaxKind::PERCENT => "取余",
+        SyntaxKind::EQ => "赋值",
+        SyntaxKind::EQEQ => "等于",
+        SyntaxKind::NEQ => "不等于",
+        SyntaxKind::LT => "小于",
+        SyntaxKind::GT => "大于",
+        SyntaxKind::LTEQ => "小于等于",
+        SyntaxKind::GTEQ => "大于等于",
+        SyntaxKind::LTLT => "左移",
+        SyntaxKind::GTGT => "右移，有符号数为算术右移，无符号数为逻辑右移",
+        SyntaxKind::AMP => "取地址",
+        SyntaxKind::AMPAMP => "逻辑与，短路求值",
+        SyntaxKind::PIPEPIPE => "逻辑或，短路求值",
+        SyntaxKind::BANG => "逻辑非",
+        SyntaxKind::DOT => "访问结构体字段",
+        SyntaxKind::ARROW => "通过结构体指针访问字段（`p->x` 即 `(*p).x`），或引出返回类型",
+        SyntaxKind::DOTDOTDOT => "可变参数",
+        SyntaxKind::COLONCOLON => "引用枚举成员：`Enum::Variant`",
+        _ => return None,
+    };
+    Some(doc)
 }
 
 /// 为变量构建 hover 信息
@@ -64,7 +120,7 @@ fn build_hover_for_variable(
     Some(Hover {
         contents: HoverContents::Markup(MarkupContent {
             kind: MarkupKind::Markdown,
-            value: format!("```rust\n{}\n```", signature),
+            value: hover_markdown(&signature, variable.doc.as_deref()),
         }),
         range: Some(text_range_to_ls_range(line_index, range)),
     })
@@ -83,7 +139,7 @@ fn build_hover_for_function(
     Some(Hover {
         contents: HoverContents::Markup(MarkupContent {
             kind: MarkupKind::Markdown,
-            value: format!("```rust\n{}\n```", signature),
+            value: hover_markdown(&signature, function.doc.as_deref()),
         }),
         range: Some(text_range_to_ls_range(line_index, range)),
     })
@@ -102,7 +158,7 @@ fn build_hover_for_struct(
     Some(Hover {
         contents: HoverContents::Markup(MarkupContent {
             kind: MarkupKind::Markdown,
-            value: format!("```rust\n{}\n```", definition),
+            value: hover_markdown(&definition, struct_def.doc.as_deref()),
         }),
         range: Some(text_range_to_ls_range(line_index, range)),
     })
@@ -127,6 +183,14 @@ fn build_hover_for_field(
     })
 }
 
+/// 代码块形式的签名，有文档注释时接在后面
+fn hover_markdown(code: &str, doc: Option<&str>) -> String {
+    match doc {
+        Some(doc) => format!("```rust\n{}\n```\n\n{}", code, doc),
+        None => format!("```rust\n{}\n```", code),
+    }
+}
+
 /// 格式化变量签名
 fn format_variable_signature(
     variable: &analyzer::module::Variable,
@@ -150,7 +214,15 @@ fn format_function_signature(function: &analyzer::module::Function) -> String {
         .collect::<Vec<_>>()
         .join(", ");
 
-    format!("fn {}({}) -> {}", function.name, params, function.ret_type)
+    let keyword = match (function.is_extern, function.is_const) {
+        (true, _) => "extern fn",
+        (false, true) => "const fn",
+        (false, false) => "fn",
+    };
+    format!(
+        "{} {}({}) -> {}",
+        keyword, function.name, params, function.ret_type
+    )
 }
 
 /// 格式化结构体定义（包含所有字段）
@@ -1,17 +1,178 @@
-use analyzer::{module::Module, project::Project};
+use analyzer::{
+    module::{Module, ReferenceTag, StructID},
+    project::Project,
+};
+use syntax::{
+    AstNode, SyntaxNode,
+    ast::{PrimitType, StructDef},
+};
+use tools::{LineIndex, TextRange};
 use tower_lsp_server::ls_types::{Location, Position, Uri};
 use vfs::{FileID, Vfs};
 
 use crate::utils::{
-    get_at_position::{get_function_id_at_position, get_variable_id_at_position},
-    position_trans::text_range_to_ls_range,
+    get_at_position::{
+        get_function_id_at_position, get_reference_id_at_position, get_struct_id_at_position,
+        get_token_at_offset, get_variable_id_at_position,
+    },
+    position_trans::{ls_position_to_offset, text_range_to_ls_range},
 };
 
+/// 光标处符号的定义和所有引用
+pub(crate) struct SymbolOccurrences {
+    /// 定义所在的文件
+    pub(crate) def_file_id: FileID,
+    /// 定义处名字的范围
+    pub(crate) def_range: TextRange,
+    /// 引用所在的文件和范围，不含定义
+    pub(crate) citers: Vec<(FileID, TextRange)>,
+}
+
+/// 解析光标处的变量、函数、字段或结构体，光标可以在定义处也可以在引用处
+pub(crate) fn symbol_occurrences(
+    pos: &Position,
+    module: &Module,
+    project: &Project,
+    line_index: &LineIndex,
+) -> Option<SymbolOccurrences> {
+    let (def_file_id, def_range, citers) = if let Some(ref_id) =
+        get_reference_id_at_position(module, line_index, pos)
+        && let Some(refer) = module.get_reference_by_id(*ref_id)
+    {
+        match refer.tag {
+            // 变量是局部的，使用当前文件
+            ReferenceTag::VarRead(var_id) => (
+                module.file_id,
+                module.get_varaible_by_id(var_id)?.range,
+                module.index.variable_reference.get(&var_id),
+            ),
+            // 函数和字段可能在其他文件，引用索引记录在定义所在的模块上
+            ReferenceTag::FuncCall(func_id) => {
+                let target = project.modules.get(&func_id.module)?;
+                (
+                    func_id.module,
+                    module.get_function_by_id(func_id)?.range,
+                    target.index.function_reference.get(&func_id),
+                )
+            }
+            ReferenceTag::FieldRead(field_id) => {
+                let target = project.modules.get(&field_id.module)?;
+                (
+                    field_id.module,
+                    module.get_field_by_id(field_id)?.range,
+                    target.index.field_reference.get(&field_id),
+                )
+            }
+        }
+    } else if let Some(var_id) = get_variable_id_at_position(module, line_index, pos) {
+        (
+            module.file_id,
+            module.get_varaible_by_id(*var_id)?.range,
+            module.index.variable_reference.get(var_id),
+        )
+    } else if let Some(func_id) = get_function_id_at_position(module, line_index, pos) {
+        (
+            func_id.module,
+            module.get_function_by_id(func_id)?.range,
+            module.index.function_reference.get(&func_id),
+        )
+    } else if let Some(struct_id) = get_struct_name_id_at_position(module, line_index, pos) {
+        // 结构体没有引用索引，直接在语法树中查找
+        let def_range = module.get_struct_by_id(struct_id)?.range;
+        let citers = struct_occurrences(struct_id, project)
+            .into_iter()
+            .filter(|&(file_id, range)| (file_id, range) != (struct_id.module, def_range))
+            .collect();
+        return Some(SymbolOccurrences {
+            def_file_id: struct_id.module,
+            def_range,
+            citers,
+        });
+    } else {
+        return None;
+    };
+
+    Some(SymbolOccurrences {
+        def_file_id,
+        def_range,
+        citers: citers
+            .into_iter()
+            .flatten()
+            .map(|citer_info| (citer_info.file_id, citer_info.range))
+            .collect(),
+    })
+}
+
+/// 光标处的结构体名：类型中的 `struct Name`，或者定义、前向声明中的名字
+fn get_struct_name_id_at_position(
+    module: &Module,
+    line_index: &LineIndex,
+    pos: &Position,
+) -> Option<StructID> {
+    get_struct_id_at_position(module, line_index, pos).or_else(|| {
+        let root = SyntaxNode::new_root(module.green_tree.clone());
+        let token = get_token_at_offset(&root, ls_position_to_offset(line_index, pos))?;
+        let struct_def = token.parent()?.parent().and_then(StructDef::cast)?;
+        module.get_struct_id_by_name(&struct_def.name()?.var_name()?)
+    })
+}
+
+/// 结构体名在整个项目中出现的位置，包括定义和前向声明
+fn struct_occurrences(struct_id: StructID, project: &Project) -> Vec<(FileID, TextRange)> {
+    let mut found = Vec::new();
+    for (file_id, module) in &project.modules {
+        let root = SyntaxNode::new_root(module.green_tree.clone());
+        for node in root.descendants() {
+            let name = if let Some(struct_def) = StructDef::cast(node.clone()) {
+                struct_def.name()
+            } else if let Some(ty) = PrimitType::cast(node)
+                && ty.struct_token().is_some()
+            {
+                ty.name()
+            } else {
+                continue;
+            };
+            if let Some(name) = name
+                && let (Some(text), Some(range)) = (name.var_name(), name.var_range())
+                && module.get_struct_id_by_name(&text) == Some(struct_id)
+            {
+                found.push((*file_id, range));
+            }
+        }
+    }
+    found
+}
+
+/// 把文件中的范围转换成 LSP 的 Location
+pub(crate) fn to_location<F>(
+    file_id: FileID,
+    range: TextRange,
+    source_uri: &Uri,
+    source_file_id: FileID,
+    vfs: &Vfs,
+    get_uri_by_file_id: &F,
+) -> Option<Location>
+where
+    F: Fn(FileID) -> Option<Uri>,
+{
+    let target_uri = if file_id == source_file_id {
+        source_uri.clone()
+    } else {
+        get_uri_by_file_id(file_id)?
+    };
+    let target_line_index = &vfs.get_file_by_file_id(&file_id)?.line_index;
+
+    Some(Location::new(
+        target_uri,
+        text_range_to_ls_range(target_line_index, range),
+    ))
+}
+
 pub(crate) fn get_references<F>(
     source_uri: Uri,
     pos: Position,
     module: &Module,
-    _project: &Project,
+    project: &Project,
     vfs: &Vfs,
     get_uri_by_file_id: F,
 ) -> Option<Vec<Location>>
@@ -20,57 +181,22 @@ where
 {
     // 获取当前文件的 line_index
     let line_index = &vfs.get_file_by_file_id(&module.file_id)?.line_index;
-    if let Some(var_id) = get_variable_id_at_position(module, line_index, &pos) {
-        let refer_list = module.index.variable_reference.get(var_id)?;
-        return Some(
-            refer_list
-                .iter()
-                .filter_map(|citer_info| {
-                    // 获取引用所在文件的 URI
-                    let target_uri = if citer_info.file_id == module.file_id {
-                        source_uri.clone()
-                    } else {
-                        get_uri_by_file_id(citer_info.file_id)?
-                    };
-
-                    // 获取引用所在文件的 LineIndex
-                    let target_line_index =
-                        &vfs.get_file_by_file_id(&citer_info.file_id)?.line_index;
-
-                    Some(Location::new(
-                        target_uri,
-                        text_range_to_ls_range(target_line_index, citer_info.range),
-                    ))
-                })
-                .collect::<Vec<_>>(),
-        );
-    }
-
-    if let Some(func_id) = get_function_id_at_position(module, line_index, &pos) {
-        let refer_list = module.index.function_reference.get(&func_id)?;
-        return Some(
-            refer_list
-                .iter()
-                .filter_map(|citer_info| {
-                    // 获取引用所在文件的 URI
-                    let target_uri = if citer_info.file_id == module.file_id {
-                        source_uri.clone()
-                    } else {
-                        get_uri_by_file_id(citer_info.file_id)?
-                    };
-
-                    // 获取引用所在文件的 LineIndex
-                    let target_line_index =
-                        &vfs.get_file_by_file_id(&citer_info.file_id)?.line_index;
-
-                    Some(Location::new(
-                        target_uri,
-                        text_range_to_ls_range(target_line_index, citer_info.range),
-                    ))
-                })
-                .collect::<Vec<_>>(),
-        );
-    }
+    let occurrences = symbol_occurrences(&pos, module, project, line_index)?;
 
-    None
+    Some(
+        occurrences
+            .citers
+            .into_iter()
+            .filter_map(|(file_id, range)| {
+                to_location(
+                    file_id,
+                    range,
+                    &source_uri,
+                    module.file_id,
+                    vfs,
+                    &get_uri_by_file_id,
+                )
+            })
+            .collect(),
+    )
 }
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use analyzer::{module::Module, project::Project};
+use lexer::Lexer;
+use rowan::TextSize;
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use tools::LineIndex;
+use tower_lsp_server::ls_types::{Position, Range, TextEdit, Uri, WorkspaceEdit};
+use vfs::{FileID, Vfs};
+
+use crate::lsp_features::references::{symbol_occurrences, to_location};
+use crate::utils::position_trans::{ls_position_to_offset, text_range_to_ls_range};
+
+/// 检查光标是否位于可重命名的符号上，返回标识符的范围
+///
+/// 只接受 `rename` 能找到全部引用的变量、函数、字段和结构体；
+/// 关键字、字面量、运算符、属性名以及枚举、类型别名、标签等返回 `None`
+pub(crate) fn prepare_rename(
+    pos: Position,
+    line_index: &LineIndex,
+    module: &Module,
+    project: &Project,
+) -> Option<Range> {
+    let root = SyntaxNode::new_root(module.green_tree.clone());
+    let offset = ls_position_to_offset(line_index, &pos);
+    let token = ident_at_offset(&root, offset)?;
+
+    token.parent().filter(|n| n.kind() == SyntaxKind::NAME)?;
+    symbol_occurrences(&pos, module, project, line_index)?;
+
+    Some(text_range_to_ls_range(
+        line_index,
+        token.text_range().into(),
+    ))
+}
+
+/// 光标落在两个 token 之间时（如 `x|+`），优先取标识符
+fn ident_at_offset(root: &SyntaxNode, offset: u32) -> Option<SyntaxToken> {
+    root.token_at_offset(TextSize::new(offset))
+        .find(|t| t.kind() == SyntaxKind::IDENT)
+}
+
+/// 把光标处的符号连同所有引用一起重命名
+///
+/// 新名字不是合法标识符（包括关键字）时返回 `None`
+pub(crate) fn rename<F>(
+    source_uri: Uri,
+    pos: Position,
+    new_name: &str,
+    module: &Module,
+    project: &Project,
+    vfs: &Vfs,
+    get_uri_by_file_id: F,
+) -> Option<WorkspaceEdit>
+where
+    F: Fn(FileID) -> Option<Uri>,
+{
+    if !is_identifier(new_name) {
+        return None;
+    }
+    let line_index = &vfs.get_file_by_file_id(&module.file_id)?.line_index;
+    let occurrences = symbol_occurrences(&pos, module, project, line_index)?;
+
+    let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+    let ranges =
+        std::iter::once((occurrences.def_file_id, occurrences.def_range)).chain(occurrences.citers);
+    for (file_id, range) in ranges {
+        let location = to_location(
+            file_id,
+            range,
+            &source_uri,
+            module.file_id,
+            vfs,
+            &get_uri_by_file_id,
+        )?;
+        changes
+            .entry(location.uri)
+            .or_default()
+            .push(TextEdit::new(location.range, new_name.to_string()));
+    }
+
+    Some(WorkspaceEdit::new(changes))
+}
+
+fn is_identifier(name: &str) -> bool {
+    matches!(
+        Lexer::new(name).get_tokens(),
+        [(SyntaxKind::IDENT, text, _)] if *text == name
+    )
+}
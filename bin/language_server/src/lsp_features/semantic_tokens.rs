@@ -165,6 +165,8 @@ fn classify_token(
         | SyntaxKind::GT
         | SyntaxKind::LTEQ
         | SyntaxKind::GTEQ
+        | SyntaxKind::LTLT
+        | SyntaxKind::GTGT
         | SyntaxKind::AMP
         | SyntaxKind::AMPAMP
         | SyntaxKind::PIPEPIPE
@@ -2,6 +2,9 @@ mod airyc_ls;
 mod lsp_features;
 mod utils;
 
+#[cfg(test)]
+mod test;
+
 use tower_lsp_server::{LspService, Server};
 
 use crate::airyc_ls::Backend;
@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+
+use analyzer::project::Project;
+use tower_lsp_server::ls_types::*;
+use tower_lsp_server::{LanguageServer, LspService};
+use vfs::Vfs;
+
+use crate::airyc_ls::Backend;
+use crate::lsp_features::dump_types::DUMP_TYPES_COMMAND;
+use crate::lsp_features::eval::EVAL_COMMAND;
+use crate::lsp_features::rename::{prepare_rename, rename};
+
+/// 分析单个文件，对 `pos` 处执行 prepareRename
+fn prepare_rename_at(code: &str, pos: Position) -> Option<Range> {
+    let vfs = Vfs::default();
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), code.to_string());
+
+    let mut project = Project::new();
+    project.full_initialize(&vfs);
+
+    let module = project.modules.get(&file_id).unwrap();
+    let line_index = &vfs.get_file_by_file_id(&file_id).unwrap().line_index;
+    prepare_rename(pos, line_index, module, &project)
+}
+
+#[test]
+fn test_prepare_rename_on_variable() {
+    let code = "fn main() -> i32 {\n    let count: i32 = 1;\n    return count + 2;\n}\n";
+    let range = prepare_rename_at(code, Position::new(2, 12));
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(2, 11), Position::new(2, 16)))
+    );
+
+    // 光标紧贴在标识符末尾也算
+    let range = prepare_rename_at(code, Position::new(2, 16));
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(2, 11), Position::new(2, 16)))
+    );
+}
+
+#[test]
+fn test_prepare_rename_rejects_non_symbols() {
+    let code = "@inline\nfn main() -> i32 {\n    let count: i32 = 1;\n    return count + 2;\n}\n";
+    // `+` 运算符
+    assert_eq!(prepare_rename_at(code, Position::new(3, 17)), None);
+    // `return` 关键字
+    assert_eq!(prepare_rename_at(code, Position::new(3, 6)), None);
+    // 字面量
+    assert_eq!(prepare_rename_at(code, Position::new(3, 19)), None);
+    // 属性名不是符号
+    assert_eq!(prepare_rename_at(code, Position::new(0, 3)), None);
+}
+
+/// 分析单个文件，把 `pos` 处的符号重命名为 `new_name`，返回按位置排序的编辑
+fn rename_at(code: &str, pos: Position, new_name: &str) -> Option<Vec<TextEdit>> {
+    let vfs = Vfs::default();
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), code.to_string());
+
+    let mut project = Project::new();
+    project.full_initialize(&vfs);
+
+    let uri: Uri = "file:///tmp/rename.airy".parse().unwrap();
+    let module = project.modules.get(&file_id).unwrap();
+    let edit = rename(uri.clone(), pos, new_name, module, &project, &vfs, |_| None)?;
+    let mut changes = edit.changes.unwrap();
+    assert_eq!(changes.len(), 1);
+    let mut edits = changes.remove(&uri).unwrap();
+    edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+    Some(edits)
+}
+
+#[test]
+fn test_rename_variable() {
+    let code = "fn main() -> i32 {\n    let count: i32 = 1;\n    count = count + 2;\n    return count;\n}\n";
+    let expected = vec![
+        TextEdit::new(
+            Range::new(Position::new(1, 8), Position::new(1, 13)),
+            "total".to_string(),
+        ),
+        TextEdit::new(
+            Range::new(Position::new(2, 4), Position::new(2, 9)),
+            "total".to_string(),
+        ),
+        TextEdit::new(
+            Range::new(Position::new(2, 12), Position::new(2, 17)),
+            "total".to_string(),
+        ),
+        TextEdit::new(
+            Range::new(Position::new(3, 11), Position::new(3, 16)),
+            "total".to_string(),
+        ),
+    ];
+    // 在定义处和引用处重命名结果相同
+    assert_eq!(
+        rename_at(code, Position::new(1, 10), "total"),
+        Some(expected.clone())
+    );
+    assert_eq!(
+        rename_at(code, Position::new(3, 12), "total"),
+        Some(expected)
+    );
+}
+
+#[test]
+fn test_rename_struct() {
+    let code = "struct Point;\nstruct Point { x: i32 }\nfn get(p: struct Point) -> i32 {\n    let q: struct Point = p;\n    return q.x;\n}\n";
+    let edit = |line, start| {
+        TextEdit::new(
+            Range::new(Position::new(line, start), Position::new(line, start + 5)),
+            "Vec2".to_string(),
+        )
+    };
+    let expected = vec![edit(0, 7), edit(1, 7), edit(2, 17), edit(3, 18)];
+    // 在定义处和类型中的 `struct Point` 处重命名结果相同
+    assert_eq!(
+        rename_at(code, Position::new(1, 9), "Vec2"),
+        Some(expected.clone())
+    );
+    assert_eq!(
+        rename_at(code, Position::new(3, 20), "Vec2"),
+        Some(expected)
+    );
+    assert!(prepare_rename_at(code, Position::new(2, 19)).is_some());
+}
+
+#[test]
+fn test_prepare_rename_rejects_unresolved_names() {
+    let code = "type Word = u64;\nfn main() -> i32 {\n    let w: Word = 1;\n    return 0;\n}\n";
+    // 类型别名没有引用索引，无法完整重命名
+    assert_eq!(prepare_rename_at(code, Position::new(0, 6)), None);
+    assert_eq!(rename_at(code, Position::new(0, 6), "Size"), None);
+}
+
+#[test]
+fn test_rename_rejects_invalid_name() {
+    let code = "fn main() -> i32 {\n    let count: i32 = 1;\n    return count;\n}\n";
+    assert_eq!(rename_at(code, Position::new(1, 10), "return"), None);
+    assert_eq!(rename_at(code, Position::new(1, 10), "a b"), None);
+    assert_eq!(rename_at(code, Position::new(1, 10), "1x"), None);
+}
+
+#[test]
+fn test_hover_keyword() {
+    let code = "struct Point { x: i32 }\nfn main() -> i32 {\n    let p: struct Point = { 1 };\n    return p.x;\n}\n";
+    let vfs = Vfs::default();
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), code.to_string());
+    let mut project = Project::new();
+    project.full_initialize(&vfs);
+    let module = project.modules.get(&file_id).unwrap();
+    let line_index = &vfs.get_file_by_file_id(&file_id).unwrap().line_index;
+    let hover_at = |pos| crate::lsp_features::hover::hover(pos, line_index, module);
+
+    let hover = hover_at(Position::new(0, 2)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("{:?}", hover.contents);
+    };
+    assert!(content.value.contains("结构体声明"), "{}", content.value);
+    assert_eq!(
+        hover.range,
+        Some(Range::new(Position::new(0, 0), Position::new(0, 6)))
+    );
+
+    // 类型中的 `struct` 关键字同样有说明，而结构体名仍显示定义
+    let hover = hover_at(Position::new(2, 12)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("{:?}", hover.contents);
+    };
+    assert!(content.value.contains("结构体声明"), "{}", content.value);
+    let hover = hover_at(Position::new(2, 19)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("{:?}", hover.contents);
+    };
+    assert!(content.value.contains("x: i32"), "{}", content.value);
+
+    // 运算符的说明
+    let hover = hover_at(Position::new(1, 11)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("{:?}", hover.contents);
+    };
+    assert!(content.value.contains("返回类型"), "{}", content.value);
+
+    // 标识符和字面量没有说明
+    assert!(hover_at(Position::new(2, 27)).is_none());
+}
+
+#[tokio::test]
+async fn test_dump_types_command() {
+    let (service, _socket) = LspService::new(Backend::new);
+    let backend = service.inner();
+
+    let uri: Uri = "file:///tmp/dump_types.airy".parse().unwrap();
+    let code = "fn main() -> i32 {\n    let x: i32 = 1 + 2;\n    return x;\n}\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "airy".into(), 0, code.into()),
+        })
+        .await;
+
+    let response = backend
+        .execute_command(ExecuteCommandParams {
+            command: DUMP_TYPES_COMMAND.to_string(),
+            arguments: vec![serde_json::Value::String(uri.to_string())],
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let entries = response.as_array().unwrap();
+    let find = |line: u32, start: u32, end: u32| {
+        entries
+            .iter()
+            .find(|e| {
+                e["range"]
+                    == serde_json::json!({
+                        "start": { "line": line, "character": start },
+                        "end": { "line": line, "character": end },
+                    })
+            })
+            .unwrap_or_else(|| panic!("no entry at {line}:{start}..{end} in {response}"))
+    };
+
+    // `1 + 2` 折叠为常量 3
+    let sum = find(1, 17, 22);
+    assert_eq!(sum["type"], "i32");
+    assert_eq!(sum["value"], "3");
+
+    // 对变量 `x` 的读取不是常量
+    let read = find(2, 11, 12);
+    assert_eq!(read["type"], "i32");
+    assert!(read.get("value").is_none());
+
+    // 未知命令被拒绝
+    let err = backend
+        .execute_command(ExecuteCommandParams {
+            command: "nanoc/unknown".to_string(),
+            arguments: vec![],
+            work_done_progress_params: Default::default(),
+        })
+        .await;
+    assert!(err.is_err());
+}
+
+#[tokio::test]
+async fn test_eval_command() {
+    let (service, _socket) = LspService::new(Backend::new);
+    let backend = service.inner();
+
+    let uri: Uri = "file:///tmp/eval.airy".parse().unwrap();
+    let code = "fn main() -> i32 {\n    let x: i32 = 2 + 3 * 4;\n    return x;\n}\n";
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "airy".into(), 0, code.into()),
+        })
+        .await;
+
+    let eval = |range: Range| {
+        backend.execute_command(ExecuteCommandParams {
+            command: EVAL_COMMAND.to_string(),
+            arguments: vec![
+                serde_json::Value::String(uri.to_string()),
+                serde_json::to_value(range).unwrap(),
+            ],
+            work_done_progress_params: Default::default(),
+        })
+    };
+
+    // 选中 `2 + 3 * 4`
+    let response = eval(Range::new(Position::new(1, 17), Position::new(1, 26)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(response["value"], "14");
+
+    // 选中 `x`：普通变量不是常量
+    let response = eval(Range::new(Position::new(2, 11), Position::new(2, 12)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(response.get("value").is_none());
+    assert!(response["message"].is_string());
+}
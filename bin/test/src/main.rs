@@ -8,6 +8,9 @@ use std::time::{Duration, Instant};
 use anyhow::{Context, Result};
 use clap::Parser;
 
+#[cfg(test)]
+mod test;
+
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(short, long, default_value = "testcases")]
@@ -27,6 +30,10 @@ struct Args {
 
     #[arg(short, long)]
     verbose: bool,
+
+    /// 比较输出使用的 diff 程序，找不到时退回内置的逐行 diff
+    #[arg(long, default_value = "diff")]
+    diff_tool: String,
 }
 
 #[derive(Clone)]
@@ -102,13 +109,17 @@ fn run_with_timeout(
     cmd: &mut Command,
     input: &str,
     timeout: Duration,
-) -> io::Result<(Vec<u8>, i32, bool)> {
+) -> io::Result<(Vec<u8>, Vec<u8>, i32, bool)> {
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
     let mut child = cmd.spawn()?;
 
+    // stdout 和 stderr 分别在独立线程中读取，避免管道写满后子进程阻塞
+    let std_out = drain_pipe(child.stdout.take());
+    let std_err = drain_pipe(child.stderr.take());
+
     if let Some(mut stdin) = child.stdin.take() {
         stdin.write_all(input.as_bytes())?;
     }
@@ -119,15 +130,15 @@ fn run_with_timeout(
         match child.try_wait() {
             Ok(Some(status)) => {
                 let return_code = status.code().unwrap_or(-1);
-                let mut std_out = child.stdout.take().unwrap();
-                let mut buf = vec![];
-                let _ = std::io::Read::read_to_end(&mut std_out, &mut buf);
-                return Ok((buf, return_code, false));
+                let buf = std_out.join().unwrap_or_default();
+                let err_buf = std_err.join().unwrap_or_default();
+                return Ok((buf, err_buf, return_code, false));
             }
             Ok(None) => {
                 if start.elapsed() >= timeout {
                     child.kill()?;
-                    return Ok((vec![], -1, true));
+                    let _ = child.wait();
+                    return Ok((vec![], vec![], -1, true));
                 }
                 thread::sleep(Duration::from_millis(100));
             }
@@ -136,6 +147,16 @@ fn run_with_timeout(
     }
 }
 
+fn drain_pipe(pipe: Option<impl io::Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
 fn run_test(case: &TestCase, args: &Args, tmp_dir: &Path) -> Result<TestResult> {
     let start = Instant::now();
 
@@ -191,7 +212,7 @@ fn run_test(case: &TestCase, args: &Args, tmp_dir: &Path) -> Result<TestResult>
         });
     }
 
-    let (my_output, my_return, timed_out) =
+    let (my_output, my_stderr, my_return, timed_out) =
         run_with_timeout(&mut Command::new(&my_exec_path), &input, timeout)?;
 
     if timed_out {
@@ -207,17 +228,28 @@ fn run_test(case: &TestCase, args: &Args, tmp_dir: &Path) -> Result<TestResult>
 
     fs::write(&my_out, &my_content)?;
 
-    let status = if std_content == my_content {
+    let mut diffs = Vec::new();
+    if std_content != my_content {
+        diffs.push(diff_files(
+            &args.diff_tool,
+            &std_out,
+            &my_out,
+            &std_content,
+            &my_content,
+        )?);
+    }
+    let my_stderr = String::from_utf8_lossy(&my_stderr).to_string();
+    if let Some(diff) = check_stderr(&case.path, tmp_dir, &my_stderr, &args.diff_tool)? {
+        diffs.push(diff);
+    } else if !diffs.is_empty() && !my_stderr.is_empty() {
+        // 没有 .err 文件时把 stderr 附在失败信息里，方便定位运行时错误
+        diffs.push(format!("stderr:\n{my_stderr}"));
+    }
+
+    let status = if diffs.is_empty() {
         TestStatus::Passed
     } else {
-        let diff_output = Command::new("diff")
-            .arg(&std_out)
-            .arg(&my_out)
-            .output()
-            .context("diff failed")?;
-
-        let diff = String::from_utf8_lossy(&diff_output.stdout).to_string();
-        TestStatus::Failed(diff)
+        TestStatus::Failed(diffs.join("\n"))
     };
 
     Ok(TestResult {
@@ -227,6 +259,81 @@ fn run_test(case: &TestCase, args: &Args, tmp_dir: &Path) -> Result<TestResult>
     })
 }
 
+/// 存在 `.err` 文件时比较程序的 stderr，不一致时返回带标签的 diff
+fn check_stderr(
+    case_path: &Path,
+    tmp_dir: &Path,
+    my_stderr: &str,
+    diff_tool: &str,
+) -> Result<Option<String>> {
+    let std_err = case_path.with_extension("err");
+    let Ok(std_err_content) = fs::read_to_string(&std_err) else {
+        return Ok(None);
+    };
+    if std_err_content == my_stderr {
+        return Ok(None);
+    }
+
+    let my_err = tmp_dir.join("my_err.txt");
+    fs::write(&my_err, my_stderr)?;
+    let diff = diff_files(diff_tool, &std_err, &my_err, &std_err_content, my_stderr)?;
+    Ok(Some(format!("stderr mismatch:\n{diff}")))
+}
+
+/// 用外部 diff 程序比较两个文件，程序不存在时使用内置 diff
+fn diff_files(
+    tool: &str,
+    expected_path: &Path,
+    actual_path: &Path,
+    expected: &str,
+    actual: &str,
+) -> Result<String> {
+    match Command::new(tool)
+        .arg(expected_path)
+        .arg(actual_path)
+        .output()
+    {
+        Ok(output) => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(builtin_diff(expected, actual)),
+        Err(e) => Err(e).with_context(|| format!("{tool} failed")),
+    }
+}
+
+/// 基于最长公共子序列的逐行 diff，输出格式近似 unified diff
+fn builtin_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j]: old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::from("--- expected\n+++ actual\n");
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!(" {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
 fn print_result(result: &TestResult, verbose: bool) {
     let status = match &result.status {
         TestStatus::Passed => {
@@ -0,0 +1,88 @@
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::{builtin_diff, check_stderr, run_with_timeout};
+
+/// 不存在的 diff 程序，强制走内置 diff
+const NO_DIFF_TOOL: &str = "airyc-test-no-such-diff";
+
+#[test]
+fn test_builtin_diff_one_line() {
+    let expected = "1\n2\nreturn: 0\n";
+    let actual = "1\n3\nreturn: 0\n";
+    let diff = builtin_diff(expected, actual);
+    assert_eq!(diff, "--- expected\n+++ actual\n 1\n-2\n+3\n return: 0\n");
+}
+
+#[test]
+fn test_builtin_diff_identical() {
+    let diff = builtin_diff("a\nb\n", "a\nb\n");
+    assert!(
+        !diff
+            .lines()
+            .any(|l| l.starts_with('-') && l != "--- expected")
+    );
+    assert!(
+        !diff
+            .lines()
+            .any(|l| l.starts_with('+') && l != "+++ actual")
+    );
+}
+
+#[test]
+fn test_check_stderr_fixture() {
+    let dir = std::env::temp_dir().join("airyc-test-stderr-fixture");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let case = dir.join("01_err.airy");
+    fs::write(&case, "fn main() -> i32 { return 0; }\n").unwrap();
+
+    // 没有 .err 文件时不比较 stderr
+    assert_eq!(
+        check_stderr(&case, &dir, "anything\n", NO_DIFF_TOOL).unwrap(),
+        None
+    );
+
+    fs::write(case.with_extension("err"), "error: boom\n").unwrap();
+    assert_eq!(
+        check_stderr(&case, &dir, "error: boom\n", NO_DIFF_TOOL).unwrap(),
+        None
+    );
+
+    let diff = check_stderr(&case, &dir, "error: bang\n", NO_DIFF_TOOL)
+        .unwrap()
+        .unwrap();
+    assert!(diff.starts_with("stderr mismatch:\n"), "{diff}");
+    assert!(diff.contains("-error: boom\n+error: bang\n"), "{diff}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_run_with_timeout_captures_stderr() {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "echo out; echo err >&2; exit 3"]);
+    let (stdout, stderr, code, timed_out) =
+        run_with_timeout(&mut cmd, "", Duration::from_secs(5)).unwrap();
+    assert_eq!(stdout, b"out\n");
+    assert_eq!(stderr, b"err\n");
+    assert_eq!(code, 3);
+    assert!(!timed_out);
+}
+
+#[test]
+fn test_run_with_timeout_large_output() {
+    // 两个流都远超管道缓冲区，同步读取时子进程会阻塞直到超时
+    let mut cmd = Command::new("sh");
+    cmd.args([
+        "-c",
+        "head -c 1000000 /dev/zero; head -c 1000000 /dev/zero >&2",
+    ]);
+    let (stdout, stderr, code, timed_out) =
+        run_with_timeout(&mut cmd, "", Duration::from_secs(5)).unwrap();
+    assert!(!timed_out);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.len(), 1_000_000);
+    assert_eq!(stderr.len(), 1_000_000);
+}
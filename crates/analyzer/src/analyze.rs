@@ -1,12 +1,46 @@
 //! 主要进行类型推导和常量计算, 以及基本的检查
 
+mod const_fn;
 mod decl;
+mod definite_init;
+mod eval;
 mod expr;
 mod func;
+mod missing_return;
 mod stmt;
+mod unconditional_recursion;
 
-use syntax::Visitor;
+use syntax::{SyntaxNode, Visitor};
 
 use crate::module::Module;
+use crate::profile::ProfilePhase;
 
-impl Visitor for Module {}
+/// 编译期断言内建函数，参数必须是常量表达式，不生成代码
+pub const STATIC_ASSERT: &str = "static_assert";
+
+/// 条件编译内建函数 `cfg("name")`：按 `AnalyzeConfig::features` 折叠为 bool 常量
+pub const CFG: &str = "cfg";
+
+/// 类型大小内建函数 `sizeof(T)`：T 占用的字节数，折叠为 u64 常量
+pub const SIZEOF: &str = "sizeof";
+
+/// 类型化分配内建函数 `alloc<T>(n)`：分配 n 个 T，结果类型为 `*mut T`，由 codegen 降级为 malloc 调用
+pub const ALLOC: &str = "alloc";
+
+/// 字符串比较内建函数 `streq(a, b)`：两个参数都是字符串常量时按转义后的内容折叠为 bool，
+/// 否则由 codegen 降级为 `strcmp(a, b) == 0`
+pub const STREQ: &str = "streq";
+
+impl Visitor for Module {
+    fn skip_subtree(&mut self, node: &SyntaxNode) -> bool {
+        self.prepare_const_global(node)
+    }
+
+    fn before_dispatch(&mut self, node: &SyntaxNode) {
+        self.profile_start(ProfilePhase::of_node(node.kind()));
+    }
+
+    fn after_dispatch(&mut self, _node: &SyntaxNode) {
+        self.profile_stop();
+    }
+}
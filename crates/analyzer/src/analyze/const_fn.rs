@@ -0,0 +1,422 @@
+//! `const fn`：检查函数体只使用可折叠的操作，并在分析阶段对常量参数的调用求值
+//!
+//! 求值时直接解释函数体的语法树，局部变量按 `VariableID` 保存在环境中。
+//! 只能求值本模块中定义的 `const fn`，且被调用的函数需要先于调用处完成分析；
+//! 求值失败（除零、溢出、超出步数限制等）时调用不视为常量，回退到运行期调用。
+
+use std::collections::HashMap;
+
+use rowan::WalkEvent;
+use syntax::ast::*;
+use syntax::{SyntaxKind, SyntaxNode};
+
+use crate::error::AnalyzeError;
+use crate::module::{FunctionID, Module, ReferenceTag, VariableID};
+use crate::r#type::Ty;
+use crate::value::Value;
+
+/// 单次求值最多执行的语句和表达式数
+const MAX_STEPS: usize = 100_000;
+/// 最大调用深度
+const MAX_DEPTH: usize = 64;
+
+/// `const fn` 中允许的值类型：整数和 bool
+fn is_const_fn_ty(ty: &Ty) -> bool {
+    matches!(
+        ty.unwrap_const(),
+        Ty::I32 | Ty::I8 | Ty::U8 | Ty::U32 | Ty::I64 | Ty::U64 | Ty::Bool
+    )
+}
+
+impl Module {
+    /// 对函数体中每个不可折叠的结构报告 `NonConstFnBody`，已报告节点的子树不再检查
+    pub(crate) fn check_const_fn_body(&mut self, func: &FuncDef) {
+        let func_range = func.text_range();
+        let mut errors = Vec::new();
+
+        if let Some(sign) = func.sign() {
+            if let Some(params) = sign.params() {
+                for param in params.params() {
+                    let ok = !param.is_variadic()
+                        && param
+                            .name()
+                            .and_then(|n| n.var_range())
+                            .and_then(|r| self.variable_map.get(&r))
+                            .is_some_and(|id| is_const_fn_ty(&self.variables[**id].ty));
+                    if !ok {
+                        errors.push(utils::trim_node_text_range(&param));
+                    }
+                }
+            }
+            if let Some(ret) = sign.ret_type()
+                && let Some(name) = sign.name().and_then(|n| n.var_name())
+                && let Some(id) = self.function_map.get(&name)
+                && let Some(f) = self.get_function_by_id(*id)
+                && !is_const_fn_ty(&f.ret_type)
+                && f.ret_type != Ty::Void
+            {
+                errors.push(utils::trim_node_text_range(&ret));
+            }
+        }
+
+        let Some(block) = func.block() else {
+            return;
+        };
+        let mut preorder = block.syntax().preorder();
+        while let Some(event) = preorder.next() {
+            let WalkEvent::Enter(node) = event else {
+                continue;
+            };
+            if !self.is_const_fn_node(&node, func_range) {
+                errors.push(trim_range(&node));
+                preorder.skip_subtree();
+            }
+        }
+
+        for range in errors {
+            self.new_error(AnalyzeError::NonConstFnBody { range });
+        }
+    }
+
+    fn is_const_fn_node(&self, node: &SyntaxNode, func_range: tools::TextRange) -> bool {
+        match node.kind() {
+            SyntaxKind::CALL_EXPR => {
+                let Some(range) = CallExpr::cast(node.clone())
+                    .and_then(|c| c.name())
+                    .and_then(|n| n.var_range())
+                else {
+                    return true;
+                };
+                match self
+                    .reference_map
+                    .get(&range)
+                    .and_then(|id| self.get_reference_by_id(*id))
+                {
+                    Some(r) => match r.tag {
+                        ReferenceTag::FuncCall(func_id) => {
+                            self.get_function_by_id(func_id).is_some_and(|f| f.is_const)
+                        }
+                        _ => false,
+                    },
+                    // 未定义的函数已经报告过
+                    None => true,
+                }
+            }
+            SyntaxKind::UNARY_EXPR => UnaryExpr::cast(node.clone())
+                .and_then(|u| u.op())
+                .is_none_or(|op| !matches!(op.op().kind(), SyntaxKind::AMP | SyntaxKind::STAR)),
+            SyntaxKind::POSTFIX_EXPR => false,
+            SyntaxKind::LITERAL => Literal::cast(node.clone())
+                .is_some_and(|l| l.string_token().is_none() && l.null_token().is_none()),
+            SyntaxKind::INDEX_VAL => {
+                let Some(index_val) = IndexVal::cast(node.clone()) else {
+                    return true;
+                };
+                // 下标访问只允许完全常量的情况，如常量数组的固定元素
+                if index_val.indices().next().is_some() {
+                    return self.is_compile_time_constant(index_val.text_range());
+                }
+                let Some(var_id) = self.const_fn_var_ref(&index_val) else {
+                    return true;
+                };
+                let var = &self.variables[*var_id];
+                // 函数外的变量只能是常量
+                var.ty.is_const() || func_range.contains_range(*var.range)
+            }
+            SyntaxKind::VAR_DEF => VarDef::cast(node.clone())
+                .and_then(|d| d.name())
+                .and_then(|n| n.var_range())
+                .and_then(|r| self.variable_map.get(&r))
+                .is_none_or(|id| is_const_fn_ty(&self.variables[**id].ty)),
+            _ => true,
+        }
+    }
+
+    /// IndexVal 引用的变量
+    fn const_fn_var_ref(&self, index_val: &IndexVal) -> Option<VariableID> {
+        let range = index_val.name()?.var_range()?;
+        let ref_id = self.reference_map.get(&range)?;
+        match self.get_reference_by_id(*ref_id)?.tag {
+            ReferenceTag::VarRead(var_id) => Some(var_id),
+            _ => None,
+        }
+    }
+
+    /// 以常量参数调用 `const fn`，无法求值时返回 None
+    pub(crate) fn eval_const_fn_call(
+        &self,
+        func_id: FunctionID,
+        args: Vec<Value>,
+    ) -> Option<Value> {
+        let mut eval = ConstEval {
+            module: self,
+            steps: 0,
+            depth: 0,
+        };
+        eval.call(func_id, args)
+    }
+}
+
+fn trim_range(node: &SyntaxNode) -> tools::TextRange {
+    match Expr::cast(node.clone()) {
+        Some(expr) => utils::trim_node_text_range(&expr),
+        None => match VarDef::cast(node.clone()) {
+            Some(def) => utils::trim_node_text_range(&def),
+            None => node.text_range().into(),
+        },
+    }
+}
+
+/// 语句执行后的控制流
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Option<Value>),
+}
+
+struct ConstEval<'a> {
+    module: &'a Module,
+    steps: usize,
+    depth: usize,
+}
+
+type Env = HashMap<VariableID, Value>;
+
+impl ConstEval<'_> {
+    fn tick(&mut self) -> Option<()> {
+        self.steps += 1;
+        (self.steps <= MAX_STEPS).then_some(())
+    }
+
+    fn call(&mut self, func_id: FunctionID, args: Vec<Value>) -> Option<Value> {
+        let module = self.module;
+        if func_id.module != module.file_id || self.depth >= MAX_DEPTH {
+            return None;
+        }
+        let func = module.functions.get(func_id.index)?;
+        if !func.is_const || func.params.len() != args.len() {
+            return None;
+        }
+        let root = SyntaxNode::new_root(module.green_tree.clone());
+        let func_def = utils::find_node_by_range::<FuncDef>(&root, func.range)?;
+        let block = func_def.block()?;
+
+        let mut env = Env::new();
+        for (var_id, arg) in func.params.iter().zip(args) {
+            let ty = &module.variables.get(**var_id)?.ty;
+            env.insert(*var_id, arg.convert_to(ty, module).ok()?);
+        }
+
+        self.depth += 1;
+        let flow = self.exec_block(&block, &mut env);
+        self.depth -= 1;
+        match flow? {
+            Flow::Return(Some(v)) => v.convert_to(&func.ret_type, module).ok(),
+            _ => None,
+        }
+    }
+
+    fn exec_block(&mut self, block: &Block, env: &mut Env) -> Option<Flow> {
+        for item in block.items() {
+            let flow = match item {
+                BlockItem::VarDef(def) => {
+                    self.exec_var_def(&def, env)?;
+                    Flow::Normal
+                }
+                BlockItem::Stmt(stmt) => self.exec_stmt(&stmt, env)?,
+            };
+            if !matches!(flow, Flow::Normal) {
+                return Some(flow);
+            }
+        }
+        Some(Flow::Normal)
+    }
+
+    fn exec_var_def(&mut self, def: &VarDef, env: &mut Env) -> Option<()> {
+        self.tick()?;
+        let module = self.module;
+        let range = def.name()?.var_range()?;
+        let var_id = *module.variable_map.get(&range)?;
+        let ty = &module.variables.get(*var_id)?.ty;
+        let value = match def.init() {
+            Some(init) => self
+                .eval_expr(&init.expr()?, env)?
+                .convert_to(ty, module)
+                .ok()?,
+            None => ty.const_zero(),
+        };
+        env.insert(var_id, value);
+        Some(())
+    }
+
+    fn exec_stmt(&mut self, stmt: &Stmt, env: &mut Env) -> Option<Flow> {
+        self.tick()?;
+        let module = self.module;
+        match stmt {
+            Stmt::AssignStmt(assign) => {
+                let value = self.eval_expr(&assign.rhs()?, env)?;
+                let Expr::IndexVal(lhs) = assign.lhs()? else {
+                    return None;
+                };
+                let var_id = module.const_fn_var_ref(&lhs)?;
+                let ty = &module.variables.get(*var_id)?.ty;
+                env.insert(var_id, value.convert_to(ty, module).ok()?);
+                Some(Flow::Normal)
+            }
+            Stmt::ExprStmt(expr_stmt) => {
+                if let Some(expr) = expr_stmt.expr() {
+                    self.eval_expr(&expr, env)?;
+                }
+                Some(Flow::Normal)
+            }
+            Stmt::Block(block) => self.exec_block(block, env),
+            Stmt::IfStmt(if_stmt) => {
+                if self.eval_cond(&if_stmt.condition()?, env)? {
+                    self.exec_stmt(&if_stmt.then_branch()?, env)
+                } else if let Some(else_branch) = if_stmt.else_branch() {
+                    self.exec_stmt(&else_branch, env)
+                } else {
+                    Some(Flow::Normal)
+                }
+            }
+            Stmt::WhileStmt(while_stmt) => {
+                let cond = while_stmt.condition()?;
+                let body = while_stmt.body()?;
+                while self.eval_cond(&cond, env)? {
+                    match self.exec_stmt(&body, env)? {
+                        Flow::Break => break,
+                        Flow::Return(v) => return Some(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+                Some(Flow::Normal)
+            }
+            Stmt::ForStmt(for_stmt) => {
+                if let Some(init) = for_stmt.init() {
+                    for def in init.var_defs() {
+                        self.exec_var_def(&def, env)?;
+                    }
+                    if let Some(stmt) = init.stmt() {
+                        self.exec_stmt(&stmt, env)?;
+                    }
+                }
+                let body = for_stmt.body()?;
+                loop {
+                    if let Some(cond) = for_stmt.condition()
+                        && !self.eval_cond(&cond, env)?
+                    {
+                        break;
+                    }
+                    match self.exec_stmt(&body, env)? {
+                        Flow::Break => break,
+                        Flow::Return(v) => return Some(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    if let Some(step) = for_stmt.step().and_then(|s| s.stmt()) {
+                        self.exec_stmt(&step, env)?;
+                    }
+                }
+                Some(Flow::Normal)
+            }
+            Stmt::SwitchStmt(switch) => self.exec_switch(switch, env),
+            Stmt::BreakStmt(_) => Some(Flow::Break),
+            Stmt::ContinueStmt(_) => Some(Flow::Continue),
+            Stmt::GotoStmt(_) | Stmt::LabelStmt(_) => None,
+            Stmt::ReturnStmt(ret) => match ret.expr() {
+                Some(expr) => Some(Flow::Return(Some(self.eval_expr(&expr, env)?))),
+                None => Some(Flow::Return(None)),
+            },
+        }
+    }
+
+    /// 从匹配的分支开始依次执行，直到 break 或 switch 结束
+    fn exec_switch(&mut self, switch: &SwitchStmt, env: &mut Env) -> Option<Flow> {
+        let module = self.module;
+        let cond = self.eval_expr(&switch.condition()?, env)?.as_i64()?;
+        let cases: Vec<_> = switch.cases().collect();
+        let matched = cases.iter().position(|case| {
+            !case.is_default()
+                && case
+                    .value()
+                    .and_then(|v| module.get_value_by_range(v.text_range()))
+                    .and_then(|v| v.as_i64())
+                    == Some(cond)
+        });
+        let Some(start) = matched.or_else(|| cases.iter().position(|c| c.is_default())) else {
+            return Some(Flow::Normal);
+        };
+        for case in &cases[start..] {
+            for stmt in case.body() {
+                match self.exec_stmt(&stmt, env)? {
+                    Flow::Normal => {}
+                    Flow::Break => return Some(Flow::Normal),
+                    flow => return Some(flow),
+                }
+            }
+        }
+        Some(Flow::Normal)
+    }
+
+    fn eval_cond(&mut self, expr: &Expr, env: &mut Env) -> Option<bool> {
+        match self.eval_expr(expr, env)?.cast_to_bool().ok()? {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    fn eval_expr(&mut self, expr: &Expr, env: &mut Env) -> Option<Value> {
+        self.tick()?;
+        let module = self.module;
+        if let Some(v) = module.get_value_by_range(expr.text_range()) {
+            return Some(v.clone());
+        }
+        match expr {
+            Expr::ParenExpr(paren) => self.eval_expr(&paren.expr()?, env),
+            Expr::IndexVal(index_val) => {
+                if index_val.indices().next().is_some() {
+                    return None;
+                }
+                env.get(&module.const_fn_var_ref(index_val)?).cloned()
+            }
+            Expr::UnaryExpr(unary) => {
+                let val = self.eval_expr(&unary.expr()?, env)?;
+                Value::eval_unary(val, unary.op()?.op().kind()).ok()
+            }
+            Expr::BinaryExpr(binary) => {
+                let op = binary.op()?.op().kind();
+                let lhs = self.eval_expr(&binary.lhs()?, env)?;
+                // 短路求值，递归的 const fn 依赖它终止
+                if op.is_logical_op() {
+                    let Value::Bool(l) = lhs.cast_to_bool().ok()? else {
+                        return None;
+                    };
+                    if l == (op == SyntaxKind::PIPEPIPE) {
+                        return Some(Value::Bool(l));
+                    }
+                    let rhs = self.eval_expr(&binary.rhs()?, env)?;
+                    return rhs.cast_to_bool().ok();
+                }
+                let rhs = self.eval_expr(&binary.rhs()?, env)?;
+                Value::calc_binary_expr(&lhs, &rhs, op, module).ok()
+            }
+            Expr::CallExpr(call) => {
+                let range = call.name()?.var_range()?;
+                let ref_id = module.reference_map.get(&range)?;
+                let ReferenceTag::FuncCall(func_id) = module.get_reference_by_id(*ref_id)?.tag
+                else {
+                    return None;
+                };
+                let args = call
+                    .args()
+                    .map(|a| a.args().collect::<Vec<_>>())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|arg| self.eval_expr(arg, env))
+                    .collect::<Option<Vec<_>>>()?;
+                self.call(func_id, args)
+            }
+            _ => None,
+        }
+    }
+}
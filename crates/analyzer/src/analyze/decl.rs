@@ -1,11 +1,15 @@
 //! 声明相关的语义分析
 
+use std::collections::{HashMap, HashSet};
+
 use syntax::ast::*;
 use syntax::visitor::DeclVisitor;
+use syntax::{SyntaxKind, SyntaxNode, Visitor};
 
 use crate::array::ArrayTree;
 use crate::error::AnalyzeError;
-use crate::module::Module;
+use crate::module::{ConstGlobal, Module};
+use crate::r#type::Ty;
 use crate::utils::parse_type_node;
 use crate::value::Value;
 
@@ -13,9 +17,14 @@ impl DeclVisitor for Module {
     fn enter_comp_unit(&mut self, node: CompUnit) {
         self.analyzing.current_scope = self.new_scope(None, node.text_range());
         self.global_scope = self.analyzing.current_scope;
+        self.check_const_init_cycles(&node);
+        self.check_restrict_placement(&node);
     }
 
     fn leave_struct_def(&mut self, node: StructDef) {
+        if node.is_forward_decl() {
+            return;
+        }
         // 获取 struct 名称
         let Some(Some(name)) = node.name().map(|n| n.var_name()) else {
             return;
@@ -24,6 +33,9 @@ impl DeclVisitor for Module {
         let Some(struct_id) = self.get_struct_id_by_name(&name) else {
             return;
         };
+        if let Some(struct_def) = self.get_struct_mut_by_id(struct_id) {
+            struct_def.doc = utils::leading_comments(node.syntax());
+        }
 
         let mut field_names = std::collections::HashSet::new();
         let mut field_list = vec![];
@@ -60,6 +72,8 @@ impl DeclVisitor for Module {
                 self.new_error(AnalyzeError::VariableDefined { name, range });
                 continue;
             }
+            // 字段只挂在 struct 定义上，通过 `Struct::field` 查找，不进入任何作用域，
+            // 因此可以和变量同名
             let field_id = self.new_field(name.clone(), ty, range);
             field_list.push(field_id);
         }
@@ -70,6 +84,111 @@ impl DeclVisitor for Module {
         struct_def.fields = field_list;
     }
 
+    /// 别名在 `Project::fill_definitions` 中已经解析，这里带上常量表重新解析，补上数组大小
+    fn leave_type_alias(&mut self, node: TypeAlias) {
+        let Some(name) = node.name().and_then(|n| n.var_name()) else {
+            return;
+        };
+        let Some(ty_node) = node.ty() else {
+            return;
+        };
+        if !matches!(self.type_aliases.get(&name), Some(Some(_))) {
+            return;
+        }
+        match parse_type_node(self, &ty_node, Some(&self.value_table)) {
+            Ok(Some(ty)) => {
+                self.type_aliases.insert(name, Some(ty));
+            }
+            Ok(None) => {}
+            Err(e) => self.new_error(e),
+        }
+    }
+
+    /// 每个成员注册为所在作用域中名为 `Enum::Variant` 的整数常量
+    ///
+    /// 未写值的成员取上一个成员的值加一，第一个成员默认为 0
+    fn leave_enum_def(&mut self, node: EnumDef) {
+        let Some(Some(enum_name)) = node.name().map(|n| n.var_name()) else {
+            return;
+        };
+
+        let backing_ty = match node.ty() {
+            Some(ty_node) => match parse_type_node(self, &ty_node, Some(&self.value_table)) {
+                Ok(Some(ty)) => ty,
+                Ok(None) => return,
+                Err(e) => {
+                    self.new_error(e);
+                    return;
+                }
+            },
+            None => Ty::I32,
+        };
+        if Value::from_i64(0, &backing_ty).is_none() || backing_ty.is_const() {
+            self.new_error(AnalyzeError::InvalidEnumBackingType {
+                ty: backing_ty,
+                range: node
+                    .ty()
+                    .map(|t| utils::trim_node_text_range(&t))
+                    .unwrap_or_default(),
+            });
+            return;
+        }
+
+        let mut next = 0i64;
+        for variant in node.variants() {
+            let Some((variant_name, range)) = variant
+                .name()
+                .and_then(|n| utils::extract_name_and_range(&n))
+            else {
+                continue;
+            };
+
+            let current = match variant.value() {
+                Some(expr) => {
+                    let Some(v) = self
+                        .value_table
+                        .get(&expr.text_range())
+                        .and_then(|v| v.as_i64())
+                    else {
+                        self.new_error(AnalyzeError::ConstantExprExpected {
+                            range: utils::trim_node_text_range(&expr),
+                        });
+                        continue;
+                    };
+                    v
+                }
+                None => next,
+            };
+
+            let qualified = format!("{enum_name}::{variant_name}");
+            let Some(value) = Value::from_i64(current, &backing_ty) else {
+                self.new_error(AnalyzeError::ConstArithmeticOverflow {
+                    message: format!("{qualified} = {current} overflows {backing_ty}"),
+                    range,
+                });
+                continue;
+            };
+            next = current.wrapping_add(1);
+
+            let scope = self.scopes.get_mut(*self.analyzing.current_scope).unwrap();
+            if scope.have_variable_def(&qualified) {
+                self.new_error(AnalyzeError::VariableDefined {
+                    name: qualified,
+                    range,
+                });
+                continue;
+            }
+            let _ = scope.new_variable(
+                &mut self.variables,
+                &mut self.variable_map,
+                qualified,
+                Ty::Const(Box::new(backing_ty.clone())),
+                range,
+            );
+            self.value_table.insert(range, value);
+        }
+    }
+
     fn leave_init_val(&mut self, node: InitVal) {
         if let Some(expr) = node.expr() {
             let expr_range = expr.text_range();
@@ -135,7 +254,8 @@ impl DeclVisitor for Module {
             let init_range_trimmed = utils::trim_node_text_range(&init_val_node);
             // 如果 InitVal 包含一个表达式，使用表达式的范围
             let need_list = var_type.is_struct() || var_type.is_array();
-            let expr_range = if let Some(expr) = init_val_node.expr() {
+            let init_expr = init_val_node.expr();
+            let expr_range = if let Some(expr) = &init_expr {
                 if need_list {
                     self.new_error(AnalyzeError::InitializerMismatch {
                         expected: "list initializer".to_string(),
@@ -147,9 +267,15 @@ impl DeclVisitor for Module {
                 expr.text_range()
             } else {
                 if !need_list {
+                    // `{}` 只能零初始化数组和 struct，标量写 0
+                    let found = if init_val_node.inits().next().is_none() {
+                        "zero initializer `{}`"
+                    } else {
+                        "list initializer"
+                    };
                     self.new_error(AnalyzeError::InitializerMismatch {
                         expected: "expression".to_string(),
-                        found: "list initializer".to_string(),
+                        found: found.to_string(),
                         range: utils::trim_node_text_range(&init_val_node),
                     });
                     return;
@@ -161,10 +287,7 @@ impl DeclVisitor for Module {
                     match ArrayTree::new(self, &var_type, init_val_node) {
                         Ok(s) => s,
                         Err(e) => {
-                            self.new_error(AnalyzeError::ArrayError {
-                                message: Box::new(e),
-                                range: init_range_trimmed,
-                            });
+                            self.new_error(e.into_analyze_error(init_range_trimmed));
                             return;
                         }
                     };
@@ -219,8 +342,15 @@ impl DeclVisitor for Module {
                     }
                 }
                 None => {
-                    // global 变量必须编译时能求值
-                    if is_global {
+                    // global 变量必须编译时能求值（循环依赖和溢出已单独报告）
+                    // 全局变量的地址 `&g` 也可以作为初始值
+                    if is_global
+                        && !self.analyzing.cyclic_consts.contains(&var_name)
+                        && !self.has_const_overflow_in(expr_range)
+                        && init_expr
+                            .as_ref()
+                            .is_none_or(|e| self.global_address_of(e).is_none())
+                    {
                         self.new_error(AnalyzeError::ConstantExprExpected {
                             range: init_range_trimmed,
                         });
@@ -238,12 +368,173 @@ impl DeclVisitor for Module {
         }
 
         let scope = self.scopes.get_mut(*self.analyzing.current_scope).unwrap();
-        let _ = scope.new_variable(
+        let var_id = scope.new_variable(
             &mut self.variables,
             &mut self.variable_map,
             var_name,
             var_type,
             var_range,
         );
+        if is_global {
+            self.variables[*var_id].doc = utils::leading_comments(def.syntax());
+        }
+    }
+}
+
+impl Module {
+    /// `restrict` 只能修饰函数参数最外层的指针，其他位置（变量、字段、返回值、内层指针）报错
+    fn check_restrict_placement(&mut self, node: &CompUnit) {
+        let misplaced: Vec<_> = node
+            .syntax()
+            .descendants()
+            .filter_map(Pointer::cast)
+            .filter_map(|p| p.restrict_token().map(|t| (p, t)))
+            .filter(|(p, _)| {
+                p.syntax()
+                    .parent()
+                    .and_then(|ty| ty.parent())
+                    .is_none_or(|n| n.kind() != SyntaxKind::FUNC_F_PARAM)
+            })
+            .map(|(_, t)| t.text_range().into())
+            .collect();
+        for range in misplaced {
+            self.new_error(AnalyzeError::MisplacedRestrict { range });
+        }
+    }
+
+    /// 检查全局常量初始化表达式之间的循环依赖（直接或间接）
+    ///
+    /// 在 DFS 过程中维护“正在求值”的集合，遇到已在集合中的常量即说明成环
+    fn check_const_init_cycles(&mut self, node: &CompUnit) {
+        let mut deps: HashMap<String, ConstGlobal> = HashMap::new();
+        let mut order = Vec::new();
+        for decl in node.global_decls() {
+            let GlobalDecl::VarDef(def) = decl else {
+                continue;
+            };
+            if def.ty().is_none_or(|t| t.const_token().is_none()) {
+                continue;
+            }
+            let Some((name, range)) = def.name().and_then(|n| utils::extract_name_and_range(&n))
+            else {
+                continue;
+            };
+            let Some(init) = def.init() else {
+                continue;
+            };
+            let refs = init
+                .syntax()
+                .descendants()
+                .filter_map(IndexVal::cast)
+                .filter_map(|v| v.name().and_then(|n| n.var_name()))
+                .collect();
+            if deps.contains_key(&name) {
+                continue;
+            }
+            order.push(name.clone());
+            deps.insert(
+                name,
+                ConstGlobal {
+                    range,
+                    refs,
+                    def_range: def.text_range(),
+                },
+            );
+        }
+
+        fn visit(
+            name: &str,
+            deps: &HashMap<String, ConstGlobal>,
+            evaluating: &mut Vec<String>,
+            finished: &mut HashSet<String>,
+            cyclic: &mut HashSet<String>,
+        ) {
+            if finished.contains(name) {
+                return;
+            }
+            if let Some(pos) = evaluating.iter().position(|n| n == name) {
+                cyclic.extend(evaluating[pos..].iter().cloned());
+                return;
+            }
+            let Some(global) = deps.get(name) else {
+                return;
+            };
+            evaluating.push(name.to_string());
+            for dep in &global.refs {
+                visit(dep, deps, evaluating, finished, cyclic);
+            }
+            evaluating.pop();
+            finished.insert(name.to_string());
+        }
+
+        let mut evaluating = Vec::new();
+        let mut finished = HashSet::new();
+        let mut cyclic = HashSet::new();
+        for name in &order {
+            visit(name, &deps, &mut evaluating, &mut finished, &mut cyclic);
+        }
+
+        for name in order {
+            if cyclic.contains(&name) {
+                let range = deps[&name].range;
+                self.new_error(AnalyzeError::CyclicConstInit { name, range });
+            }
+        }
+        self.analyzing.cyclic_consts = cyclic;
+        self.analyzing.const_globals = deps;
+    }
+
+    /// 遍历到全局常量定义前，先分析它引用的、定义在后面的全局常量，
+    /// 使初始化表达式按依赖顺序求值。返回 true 表示该定义已经提前分析过，需要跳过
+    pub(crate) fn prepare_const_global(&mut self, node: &SyntaxNode) -> bool {
+        if node
+            .parent()
+            .is_none_or(|p| p.kind() != SyntaxKind::COMP_UNIT)
+        {
+            return false;
+        }
+        let Some(name) = VarDef::cast(node.clone())
+            .and_then(|def| def.name())
+            .and_then(|n| utils::extract_name_and_range(&n))
+        else {
+            return false;
+        };
+        match self.analyzing.const_globals.get(&name.0) {
+            Some(global) if global.range == name.1 => {}
+            _ => return false,
+        }
+        if self.analyzing.analyzed_consts.contains(&name.1) {
+            return true;
+        }
+        self.analyze_const_deps(&name.0);
+        false
+    }
+
+    /// 按依赖顺序提前分析 `name` 引用的全局常量，成环的常量已报告过错误，不再处理
+    fn analyze_const_deps(&mut self, name: &str) {
+        let Some(global) = self.analyzing.const_globals.get(name) else {
+            return;
+        };
+        if self.analyzing.cyclic_consts.contains(name)
+            || !self.analyzing.analyzed_consts.insert(global.range)
+        {
+            return;
+        }
+        let refs = global.refs.clone();
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        for dep in &refs {
+            let Some(dep_global) = self.analyzing.const_globals.get(dep) else {
+                continue;
+            };
+            if self.analyzing.analyzed_consts.contains(&dep_global.range) {
+                continue;
+            }
+            let Some(dep_def) = utils::find_node_by_range::<VarDef>(&root, dep_global.def_range)
+            else {
+                continue;
+            };
+            self.analyze_const_deps(dep);
+            self.walk(dep_def.syntax());
+        }
     }
 }
@@ -0,0 +1,300 @@
+//! 确定赋值检查：没有初始化的局部变量必须在所有路径上先赋值再读取
+//!
+//! 只跟踪非 const 的标量局部变量（整数、bool、指针），数组和 struct 通常按元素赋值，不做检查。
+//! 分支保守处理：只有 if/else 两个分支都赋值过才算已赋值；while 循环体可能一次也不执行，
+//! 循环体内的赋值不影响循环之后的状态。
+
+use std::collections::HashSet;
+
+use syntax::ast::*;
+use syntax::{SyntaxKind, SyntaxNode};
+
+use crate::error::AnalyzeError;
+use crate::module::{Module, ReferenceTag, VariableID};
+
+/// 单条控制流路径上的状态
+#[derive(Debug, Clone, Default)]
+struct InitState {
+    /// 尚未确定赋值的变量
+    unassigned: HashSet<VariableID>,
+    /// 当前路径已经 return/break/continue，后续语句不可达
+    diverged: bool,
+}
+
+impl InitState {
+    /// 合并两个分支：任一分支未赋值即视为未赋值，已发散的分支不参与合并
+    fn merge(self, other: InitState) -> InitState {
+        match (self.diverged, other.diverged) {
+            (true, true) => InitState {
+                unassigned: self.unassigned,
+                diverged: true,
+            },
+            (true, false) => other,
+            (false, true) => self,
+            (false, false) => InitState {
+                unassigned: self.unassigned.union(&other.unassigned).copied().collect(),
+                diverged: false,
+            },
+        }
+    }
+}
+
+impl Module {
+    pub(crate) fn check_definite_init(&mut self) {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let Some(comp_unit) = CompUnit::cast(root) else {
+            return;
+        };
+
+        let mut reported = HashSet::new();
+        for item in comp_unit.global_decls() {
+            if let GlobalDecl::FuncDef(func) = item
+                && let Some(block) = func.block()
+            {
+                let mut state = InitState::default();
+                self.init_check_block(&block, &mut state, &mut reported);
+            }
+        }
+    }
+
+    fn init_check_block(
+        &mut self,
+        block: &Block,
+        state: &mut InitState,
+        reported: &mut HashSet<VariableID>,
+    ) {
+        for item in block.items() {
+            // 不可达代码不检查
+            if state.diverged {
+                break;
+            }
+            match item {
+                BlockItem::VarDef(def) => self.init_check_var_def(&def, state, reported),
+                BlockItem::Stmt(stmt) => self.init_check_stmt(&stmt, state, reported),
+            }
+        }
+    }
+
+    fn init_check_var_def(
+        &mut self,
+        def: &VarDef,
+        state: &mut InitState,
+        reported: &mut HashSet<VariableID>,
+    ) {
+        if let Some(init) = def.init() {
+            // 初始化列表可能嵌套，只取直接挂在 InitVal 下的表达式
+            let exprs = init
+                .syntax()
+                .descendants()
+                .filter(|n| n.parent().is_some_and(|p| p.kind() == SyntaxKind::INIT_VAL))
+                .filter_map(Expr::cast);
+            for expr in exprs {
+                self.init_check_expr(&expr, state, reported);
+            }
+            return;
+        }
+
+        let Some(range) = def.name().and_then(|n| n.var_range()) else {
+            return;
+        };
+        let Some(var_id) = self.variable_map.get(&range).copied() else {
+            return;
+        };
+        let ty = &self.variables.get(*var_id).unwrap().ty;
+        if !ty.is_const() && !ty.is_array() && !ty.is_struct() {
+            state.unassigned.insert(var_id);
+        }
+    }
+
+    fn init_check_stmt(
+        &mut self,
+        stmt: &Stmt,
+        state: &mut InitState,
+        reported: &mut HashSet<VariableID>,
+    ) {
+        match stmt {
+            Stmt::AssignStmt(assign) => {
+                if let Some(rhs) = assign.rhs() {
+                    self.init_check_expr(&rhs, state, reported);
+                }
+                let Some(lhs) = assign.lhs() else {
+                    return;
+                };
+                // `x = ...` 是对 x 的赋值，其他左值（`a[i]`、`*p`、`s.f`）中的变量都是读取
+                if let Expr::IndexVal(index_val) = &lhs
+                    && index_val.indices().next().is_none()
+                    && let Some(var_id) = self.init_check_var_ref(index_val)
+                {
+                    state.unassigned.remove(&var_id);
+                } else {
+                    self.init_check_expr(&lhs, state, reported);
+                }
+            }
+            Stmt::ExprStmt(expr_stmt) => {
+                if let Some(expr) = expr_stmt.expr() {
+                    self.init_check_expr(&expr, state, reported);
+                }
+            }
+            Stmt::Block(block) => self.init_check_block(block, state, reported),
+            Stmt::IfStmt(if_stmt) => {
+                if let Some(cond) = if_stmt.condition() {
+                    self.init_check_expr(&cond, state, reported);
+                }
+                let mut then_state = state.clone();
+                if let Some(then_branch) = if_stmt.then_branch() {
+                    self.init_check_stmt(&then_branch, &mut then_state, reported);
+                }
+                let mut else_state = state.clone();
+                if let Some(else_branch) = if_stmt.else_branch() {
+                    self.init_check_stmt(&else_branch, &mut else_state, reported);
+                }
+                *state = then_state.merge(else_state);
+            }
+            Stmt::WhileStmt(while_stmt) => {
+                if let Some(cond) = while_stmt.condition() {
+                    self.init_check_expr(&cond, state, reported);
+                }
+                // 循环体可能不执行，循环之后沿用进入循环前的状态
+                let mut body_state = state.clone();
+                if let Some(body) = while_stmt.body() {
+                    self.init_check_stmt(&body, &mut body_state, reported);
+                }
+            }
+            Stmt::ForStmt(for_stmt) => {
+                if let Some(init) = for_stmt.init() {
+                    for def in init.var_defs() {
+                        self.init_check_var_def(&def, state, reported);
+                    }
+                    if let Some(stmt) = init.stmt() {
+                        self.init_check_stmt(&stmt, state, reported);
+                    }
+                }
+                if let Some(cond) = for_stmt.condition() {
+                    self.init_check_expr(&cond, state, reported);
+                }
+                // 与 while 相同，循环体和步进可能不执行
+                let mut body_state = state.clone();
+                if let Some(body) = for_stmt.body() {
+                    self.init_check_stmt(&body, &mut body_state, reported);
+                }
+                if !body_state.diverged
+                    && let Some(step) = for_stmt.step().and_then(|s| s.stmt())
+                {
+                    self.init_check_stmt(&step, &mut body_state, reported);
+                }
+            }
+            Stmt::SwitchStmt(switch) => self.init_check_switch(switch, state, reported),
+            Stmt::BreakStmt(_) | Stmt::ContinueStmt(_) | Stmt::GotoStmt(_) => state.diverged = true,
+            Stmt::LabelStmt(_) => {}
+            Stmt::ReturnStmt(ret) => {
+                if let Some(expr) = ret.expr() {
+                    self.init_check_expr(&expr, state, reported);
+                }
+                state.diverged = true;
+            }
+        }
+    }
+
+    /// 每个分支从进入 switch 时的状态开始，并合并上一个贯穿下来的分支；
+    /// 发散的分支按跳出 switch 处理，没有 default 时还要合并不进入任何分支的路径
+    fn init_check_switch(
+        &mut self,
+        switch: &SwitchStmt,
+        state: &mut InitState,
+        reported: &mut HashSet<VariableID>,
+    ) {
+        if let Some(cond) = switch.condition() {
+            self.init_check_expr(&cond, state, reported);
+        }
+
+        let mut exits: Vec<InitState> = Vec::new();
+        let mut fall_through: Option<InitState> = None;
+        let mut has_default = false;
+        for case in switch.cases() {
+            has_default |= case.is_default();
+            let mut case_state = match fall_through.take() {
+                Some(prev) => prev.merge(state.clone()),
+                None => state.clone(),
+            };
+            for stmt in case.body() {
+                if case_state.diverged {
+                    break;
+                }
+                self.init_check_stmt(&stmt, &mut case_state, reported);
+            }
+            if case_state.diverged {
+                case_state.diverged = false;
+                exits.push(case_state);
+            } else {
+                fall_through = Some(case_state);
+            }
+        }
+        exits.extend(fall_through);
+        if !has_default {
+            exits.push(state.clone());
+        }
+
+        if let Some(merged) = exits.into_iter().reduce(InitState::merge) {
+            *state = merged;
+        }
+    }
+
+    fn init_check_expr(
+        &mut self,
+        expr: &Expr,
+        state: &mut InitState,
+        reported: &mut HashSet<VariableID>,
+    ) {
+        match expr {
+            Expr::IndexVal(index_val) => {
+                if let Some(var_id) = self.init_check_var_ref(index_val)
+                    && state.unassigned.contains(&var_id)
+                    && reported.insert(var_id)
+                    && let Some((name, range)) = index_val
+                        .name()
+                        .and_then(|n| utils::extract_name_and_range(&n))
+                {
+                    self.new_error(AnalyzeError::UseBeforeInit { name, range });
+                }
+                for index in index_val.indices() {
+                    self.init_check_expr(&index, state, reported);
+                }
+            }
+            Expr::UnaryExpr(unary)
+                if unary.op().map(|op| op.op().kind()) == Some(SyntaxKind::AMP)
+                    && let Some(Expr::IndexVal(index_val)) = unary.expr()
+                    && index_val.indices().next().is_none() =>
+            {
+                // 取地址后可能经由指针赋值，视为已赋值
+                if let Some(var_id) = self.init_check_var_ref(&index_val) {
+                    state.unassigned.remove(&var_id);
+                }
+            }
+            _ => {
+                for child in expr.syntax().children().filter_map(Expr::cast) {
+                    self.init_check_expr(&child, state, reported);
+                }
+                // 调用参数、字段下标等包在中间节点里
+                for child in expr
+                    .syntax()
+                    .children()
+                    .filter(|n| Expr::cast(n.clone()).is_none())
+                {
+                    for inner in child.children().filter_map(Expr::cast) {
+                        self.init_check_expr(&inner, state, reported);
+                    }
+                }
+            }
+        }
+    }
+
+    /// IndexVal 引用的局部变量
+    fn init_check_var_ref(&self, index_val: &IndexVal) -> Option<VariableID> {
+        let range = index_val.name()?.var_range()?;
+        let ref_id = self.reference_map.get(&range)?;
+        match self.get_reference_by_id(*ref_id)?.tag {
+            ReferenceTag::VarRead(var_id) => Some(var_id),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! 表达式求值：在文件中某个位置的作用域里分析一段表达式，得到折叠出的常量（编辑器内求值）
+
+use parser::parse::Parser;
+use rowan::{GreenNode, NodeOrToken};
+use syntax::ast::{AstNode, Expr};
+use syntax::{SyntaxKind, SyntaxNode, Visitor};
+
+use crate::error::AnalyzeError;
+use crate::module::{Module, ScopeID};
+use crate::value::Value;
+
+impl Module {
+    /// 在 `offset` 处的作用域中分析表达式 `source`
+    ///
+    /// 表达式是编译期常量时返回 `Ok(Some(value))`，不是常量返回 `Ok(None)`，
+    /// 有语法或语义错误时返回这些错误。模块本身不会被修改
+    pub fn eval_expr_at(
+        &self,
+        source: &str,
+        offset: u32,
+    ) -> Result<Option<Value>, Vec<AnalyzeError>> {
+        let (expr, errors) = Parser::parse_expr(source);
+        if !errors.is_empty() {
+            return Err(errors
+                .into_iter()
+                .map(|e| AnalyzeError::ParserError(Box::new(e)))
+                .collect());
+        }
+        let Some(expr) = expr else {
+            return Ok(None);
+        };
+
+        // 各张表以文本范围为键。把文件的语法树作为表达式的前一个兄弟节点（共享 green 节点，
+        // 不复制文本），表达式的范围就落在文件末尾之后，不会与文件中的节点冲突
+        let root = GreenNode::new(
+            SyntaxKind::EXPR_STMT.into(),
+            [
+                NodeOrToken::Node(self.green_tree.clone()),
+                NodeOrToken::Node(expr.syntax().green().into_owned()),
+            ],
+        );
+        let Some(expr) = SyntaxNode::new_root(root).last_child().and_then(Expr::cast) else {
+            return Ok(None);
+        };
+
+        let mut scratch = self.fork();
+        scratch.analyzing.current_scope = self.scope_at(offset);
+        scratch.walk(expr.syntax());
+        if !scratch.semantic_errors.is_empty() {
+            return Err(scratch.semantic_errors);
+        }
+        Ok(scratch.value_table.remove(&expr.text_range()))
+    }
+
+    /// 包含 `offset` 的最内层作用域
+    fn scope_at(&self, offset: u32) -> ScopeID {
+        self.scopes
+            .iter()
+            .filter(|(_, scope)| scope.range.contains_inclusive(offset.into()))
+            .min_by_key(|(_, scope)| scope.range.len())
+            .map_or(self.global_scope, |(idx, _)| ScopeID(idx))
+    }
+}
@@ -3,10 +3,13 @@
 use syntax::SyntaxKind;
 use syntax::ast::*;
 use syntax::visitor::ExprVisitor;
+use tools::TextRange;
 
+use crate::analyze::{ALLOC, CFG, SIZEOF, STATIC_ASSERT, STREQ};
 use crate::array::ArrayTreeValue;
 use crate::error::AnalyzeError;
 use crate::module::{Module, ReferenceTag};
+use crate::profile::ProfilePhase;
 use crate::r#type::{Ty, UnaryOpError};
 use crate::value::Value;
 
@@ -20,6 +23,36 @@ impl ExprVisitor for Module {
 
         // 检查函数是否已定义
         let Some(func_id) = self.get_function_id_by_name(&func_name) else {
+            if func_name == STATIC_ASSERT {
+                self.check_static_assert(&node, func_name, func_range);
+                return;
+            }
+            if func_name == ALLOC {
+                self.check_alloc(&node, func_name, func_range);
+                return;
+            }
+            if func_name == CFG {
+                self.check_cfg(&node, func_name, func_range);
+                return;
+            }
+            if func_name == SIZEOF {
+                self.check_sizeof(&node, func_name, func_range);
+                return;
+            }
+            if func_name == STREQ {
+                self.check_streq(&node, func_name, func_range);
+                return;
+            }
+            // 名字解析到变量时给出更准确的诊断
+            if let Some(var_id) = self.find_variable_def(&func_name) {
+                let ty = self.variables.get(*var_id).unwrap().ty.clone();
+                self.new_error(AnalyzeError::NotCallable {
+                    name: func_name,
+                    ty,
+                    range: func_range,
+                });
+                return;
+            }
             self.new_error(AnalyzeError::FunctionUndefined {
                 name: func_name,
                 range: func_range,
@@ -29,6 +62,13 @@ impl ExprVisitor for Module {
 
         self.new_reference(func_range, ReferenceTag::FuncCall(func_id));
 
+        if let Some(type_arg) = node.type_arg() {
+            self.new_error(AnalyzeError::UnexpectedTypeArgument {
+                name: func_name.clone(),
+                range: utils::trim_node_text_range(&type_arg),
+            });
+        }
+
         // 获取函数定义（支持跨模块访问）
         let Some(func) = self.get_function_by_id(func_id) else {
             // 理论上不应该发生（函数 ID 存在但找不到定义）
@@ -51,8 +91,9 @@ impl ExprVisitor for Module {
                 self.new_error(AnalyzeError::ArgumentCountMismatch {
                     function_name: func_name.clone(),
                     expected: expected_params.len(),
+                    at_least: true,
                     found: actual_args.len(),
-                    range: node.args().map(|a| a.text_range()).unwrap_or(func_range),
+                    range: Self::call_parens_range(&node).unwrap_or(func_range),
                 });
                 // 即使参数个数不匹配，也设置返回类型以便后续分析
                 self.set_expr_type(node.text_range(), func.ret_type.clone());
@@ -64,8 +105,9 @@ impl ExprVisitor for Module {
                 self.new_error(AnalyzeError::ArgumentCountMismatch {
                     function_name: func_name.clone(),
                     expected: expected_params.len(),
+                    at_least: false,
                     found: actual_args.len(),
-                    range: node.args().map(|a| a.text_range()).unwrap_or(func_range),
+                    range: Self::call_parens_range(&node).unwrap_or(func_range),
                 });
                 self.set_expr_type(node.text_range(), func.ret_type.clone());
                 return;
@@ -94,6 +136,21 @@ impl ExprVisitor for Module {
 
         // 设置返回类型
         self.set_expr_type(node.text_range(), func.ret_type.clone());
+
+        // 参数都是常量时在分析阶段求值 const fn
+        if func.is_const
+            && let Some(args) = actual_args
+                .iter()
+                .map(|arg| self.get_value_by_range(arg.text_range()).cloned())
+                .collect::<Option<Vec<_>>>()
+        {
+            self.profile_start(ProfilePhase::ConstFolding);
+            let value = self.eval_const_fn_call(func_id, args);
+            self.profile_stop();
+            if let Some(value) = value {
+                self.value_table.insert(node.text_range(), value);
+            }
+        }
     }
 
     fn leave_binary_expr(&mut self, node: BinaryExpr) {
@@ -107,6 +164,20 @@ impl ExprVisitor for Module {
             return;
         };
         let op_kind = op.op().kind();
+        if !op_kind.is_binary_op() {
+            return;
+        }
+
+        // 数组名只在需要指针的地方 decay（传参、赋值给指针），不能直接参与运算
+        if let Some(array_side) = [&lhs, &rhs]
+            .into_iter()
+            .find(|e| self.array_lvalue_type(e).is_some())
+        {
+            self.new_error(AnalyzeError::ArrayInScalarContext {
+                range: utils::trim_node_text_range(array_side),
+            });
+            return;
+        }
 
         let lhs_ty = self.get_expr_type(lhs.text_range()).cloned();
         let rhs_ty = self.get_expr_type(rhs.text_range()).cloned();
@@ -114,9 +185,48 @@ impl ExprVisitor for Module {
         if let (Some(l), Some(r)) = (&lhs_ty, &rhs_ty) {
             match Ty::compute_binary_result_type(l, r, op_kind) {
                 Some(result_ty) => {
-                    self.set_expr_type(node.text_range(), result_ty);
+                    self.set_expr_type(node.text_range(), result_ty.clone());
+                    // 常量移位数必须在 [0, 位宽) 内
+                    if op_kind.is_shift_op()
+                        && let Some(count) = self.get_value_by_range(rhs.text_range())
+                    {
+                        // 超出 i64 的 u64 一定越界
+                        let count = match count {
+                            Value::Bool(v) => i64::from(*v),
+                            v => v.as_i64().unwrap_or(i64::MAX),
+                        };
+                        let width = result_ty.int_bits().unwrap_or(32);
+                        if !(0..i64::from(width)).contains(&count) {
+                            self.new_error(AnalyzeError::InvalidShiftCount {
+                                count,
+                                width,
+                                range: utils::trim_node_text_range(&rhs),
+                            });
+                            return;
+                        }
+                    }
                 }
                 None => {
+                    // 整数常量之间的比较按数值进行，不要求符号相同（如 `sizeof(i64) == 8`）
+                    if let (Some(l), Some(r)) = (
+                        self.get_value_by_range(lhs.text_range()),
+                        self.get_value_by_range(rhs.text_range()),
+                    ) && let Some(result) = Value::compare_integers(l, r, op_kind)
+                    {
+                        self.set_expr_type(node.text_range(), Ty::Bool);
+                        self.value_table.insert(node.text_range(), result);
+                        return;
+                    }
+                    // void 操作数单独报错，指向没有值的那一侧
+                    if let Some(void_side) = [(&lhs, l), (&rhs, r)]
+                        .into_iter()
+                        .find(|(_, ty)| ty.unwrap_const() == Ty::Void)
+                    {
+                        self.new_error(AnalyzeError::VoidInExpression {
+                            range: utils::trim_node_text_range(void_side.0),
+                        });
+                        return;
+                    }
                     self.new_error(AnalyzeError::BinaryOpTypeMismatch {
                         op: op.op_str(),
                         lhs: l.clone(),
@@ -131,10 +241,13 @@ impl ExprVisitor for Module {
         if self.is_compile_time_constant(lhs.text_range())
             && self.is_compile_time_constant(rhs.text_range())
         {
+            self.profile_start(ProfilePhase::ConstFolding);
             let lhs_val = self.value_table.get(&lhs.text_range()).unwrap();
             let rhs_val = self.value_table.get(&rhs.text_range()).unwrap();
+            let result = Value::calc_binary_expr(lhs_val, rhs_val, op.op().kind(), self);
+            self.profile_stop();
 
-            match Value::calc_binary_expr(lhs_val, rhs_val, op.op().kind(), self) {
+            match result {
                 Ok(val) => {
                     self.value_table.insert(node.text_range(), val);
                 }
@@ -160,6 +273,9 @@ impl ExprVisitor for Module {
             return;
         };
         let op_kind = op.op().kind();
+        if !op_kind.is_unary_op() {
+            return;
+        }
 
         if let Some(inner_ty) = self.get_expr_type(expr.text_range()) {
             // 特殊处理取地址操作（需要检查左值）
@@ -170,6 +286,11 @@ impl ExprVisitor for Module {
                 return;
             }
 
+            // `&arr` 取整个数组的地址，操作数不 decay
+            let inner_ty = match op_kind {
+                SyntaxKind::AMP => self.array_lvalue_type(&expr).unwrap_or(inner_ty.clone()),
+                _ => inner_ty.clone(),
+            };
             match inner_ty.validate_unary_op(op_kind) {
                 Ok(result_ty) => {
                     self.set_expr_type(node.text_range(), result_ty);
@@ -202,7 +323,10 @@ impl ExprVisitor for Module {
         if self.is_compile_time_constant(expr.text_range()) {
             let val = self.value_table.get(&expr.text_range()).unwrap().clone();
 
-            match Value::eval_unary(val.clone(), op.op().kind()) {
+            self.profile_start(ProfilePhase::ConstFolding);
+            let result = Value::eval_unary(val.clone(), op.op().kind());
+            self.profile_stop();
+            match result {
                 Ok(res) => {
                     self.value_table.insert(node.text_range(), res);
                 }
@@ -217,7 +341,8 @@ impl ExprVisitor for Module {
                             .contains_key(&expr_range)
                     {
                         // 这是字面量溢出的情况，已经在字面量溢出检测中处理
-                        // 不报告一元运算溢出
+                        // 不报告一元运算溢出；`-128i8` 这样的最小值取负后仍是自身
+                        self.value_table.insert(node.text_range(), val.clone());
                     } else {
                         // 普通的一元运算溢出
                         self.new_error(AnalyzeError::ConstArithmeticOverflow {
@@ -291,6 +416,16 @@ impl ExprVisitor for Module {
 
         // 查找变量定义
         let Some(var_id) = self.find_variable_def(&var_name) else {
+            // 循环依赖的全局常量已经报告过错误
+            if self.analyzing.current_scope == self.global_scope
+                && self.analyzing.cyclic_consts.contains(&var_name)
+            {
+                return;
+            }
+            // `sizeof(Int)` 中的别名由 check_sizeof 处理
+            if self.is_sizeof_alias_arg(&node) {
+                return;
+            }
             self.new_error(AnalyzeError::VariableUndefined {
                 name: var_name.to_string(),
                 range: var_range,
@@ -302,6 +437,14 @@ impl ExprVisitor for Module {
 
         let var = self.variables.get(*var_id).unwrap();
         let index_count = node.indices().count();
+        // 标量不能下标访问，单独报错而不是当成下标过深
+        if index_count > 0 && !var.ty.is_array() && !var.ty.is_pointer() {
+            self.new_error(AnalyzeError::NotIndexable {
+                ty: var.ty.clone(),
+                range: var_range,
+            });
+            return;
+        }
         let result_ty = match Self::compute_indexed_type(
             &var.ty,
             index_count,
@@ -376,6 +519,38 @@ impl ExprVisitor for Module {
         self.value_table.insert(range, value.clone());
     }
 
+    fn leave_enum_variant_expr(&mut self, node: EnumVariantExpr) {
+        let Some(enum_name) = node.enum_name().and_then(|n| n.var_name()) else {
+            return;
+        };
+        let Some((variant, variant_range)) = node
+            .variant()
+            .and_then(|n| utils::extract_name_and_range(&n))
+        else {
+            return;
+        };
+
+        // 成员以 `Enum::Variant` 的名字注册为常量
+        let Some(var_id) = self.find_variable_def(&format!("{enum_name}::{variant}")) else {
+            self.new_error(AnalyzeError::EnumVariantUndefined {
+                enum_name,
+                variant,
+                range: utils::trim_node_text_range(&node),
+            });
+            return;
+        };
+        self.new_reference(variant_range, ReferenceTag::VarRead(var_id));
+
+        let var = self.variables.get(*var_id).unwrap();
+        let ty = var.ty.clone();
+        let value = self.value_table.get(&var.range).cloned();
+        let range = node.text_range();
+        self.set_expr_type(range, ty);
+        if let Some(value) = value {
+            self.value_table.insert(range, value);
+        }
+    }
+
     fn leave_postfix_expr(&mut self, node: PostfixExpr) {
         let range = node.text_range();
 
@@ -406,6 +581,25 @@ impl ExprVisitor for Module {
             return;
         };
 
+        // 数组的 `.len` 折叠为声明的长度，struct 的 len 字段按普通字段处理
+        if op_kind == SyntaxKind::DOT
+            && member_name == "len"
+            && field_access_node.indices().next().is_none()
+            && base_ty.as_struct_id().is_none()
+        {
+            let array_ty = self.array_lvalue_type(&base_expr);
+            if let Some(Ty::Array(_, Some(size))) = array_ty.as_ref().map(Ty::unwrap_const) {
+                self.set_expr_type(range, Ty::Const(Box::new(Ty::U64)));
+                self.value_table.insert(range, Value::U64(size as u64));
+            } else {
+                self.new_error(AnalyzeError::LenOnNonArray {
+                    ty: array_ty.unwrap_or_else(|| base_ty.clone()),
+                    range: utils::trim_node_text_range(&node),
+                });
+            }
+            return;
+        }
+
         // 根据操作符提取 struct ID
         let struct_id = match op_kind {
             SyntaxKind::DOT => {
@@ -444,12 +638,26 @@ impl ExprVisitor for Module {
 
         // 查找 struct 定义
         let struct_def = self.get_struct_by_id(struct_id).unwrap();
+        if struct_def.is_incomplete {
+            self.new_error(AnalyzeError::IncompleteStruct {
+                name: struct_def.name,
+                range: utils::trim_node_text_range(&base_expr),
+            });
+            return;
+        }
 
         // 查找字段并设置类型
         if let Some(field_id) = struct_def.field(self, &member_name) {
             let field = self.get_field_by_id(field_id).unwrap();
             // 计算索引后的类型（如果有数组索引）
             let indices: Vec<_> = field_access_node.indices().collect();
+            if !indices.is_empty() && !field.ty.is_array() && !field.ty.is_pointer() {
+                self.new_error(AnalyzeError::NotIndexable {
+                    ty: field.ty.clone(),
+                    range: member_range,
+                });
+                return;
+            }
             let result_ty = match Self::compute_indexed_type(
                 &field.ty,
                 indices.len(),
@@ -530,7 +738,7 @@ impl ExprVisitor for Module {
             );
             // 获取字符串内容（去掉引号）
             let string_token = node.string_token().unwrap();
-            let s = string_token.text().to_string();
+            let s = lexer::splice_line_continuations(string_token.text());
             let content = match snailquote::unescape(&s) {
                 Ok(s) => s,
                 Err(e) => {
@@ -648,21 +856,20 @@ impl ExprVisitor for Module {
                 Ok(v) => v,
                 Err(_) => {
                     // 解析失败（数字太大或格式错误）
-                    self.semantic_errors
-                        .push(crate::error::AnalyzeError::IntegerLiteralOverflow {
-                            literal: s.to_string(),
-                            ty: match ty {
-                                Ty::I8 => "i8",
-                                Ty::U8 => "u8",
-                                Ty::I32 => "i32",
-                                Ty::U32 => "u32",
-                                Ty::I64 => "i64",
-                                Ty::U64 => "u64",
-                                _ => unreachable!(),
-                            }
-                            .to_string(),
-                            range,
-                        });
+                    self.new_error(crate::error::AnalyzeError::IntegerLiteralOverflow {
+                        literal: s.to_string(),
+                        ty: match ty {
+                            Ty::I8 => "i8",
+                            Ty::U8 => "u8",
+                            Ty::I32 => "i32",
+                            Ty::U32 => "u32",
+                            Ty::I64 => "i64",
+                            Ty::U64 => "u64",
+                            _ => unreachable!(),
+                        }
+                        .to_string(),
+                        range,
+                    });
                     return;
                 }
             };
@@ -703,3 +910,292 @@ impl ExprVisitor for Module {
         self.value_table.insert(range, v);
     }
 }
+
+impl Module {
+    /// 调用的括号 `(...)` 的范围，缺少右括号时到调用末尾为止
+    fn call_parens_range(node: &CallExpr) -> Option<TextRange> {
+        let start = node.l_paren_token()?.text_range().start();
+        let end = node
+            .r_paren_token()
+            .map_or(node.syntax().text_range().end(), |t| t.text_range().end());
+        Some(TextRange::new(start.into(), end.into()))
+    }
+
+    /// `static_assert(cond)`：cond 折叠为 false 时报错，结果类型为 void
+    fn check_static_assert(&mut self, node: &CallExpr, func_name: String, func_range: TextRange) {
+        self.set_expr_type(node.text_range(), Ty::Void);
+        if let Some(type_arg) = node.type_arg() {
+            self.new_error(AnalyzeError::UnexpectedTypeArgument {
+                name: func_name.clone(),
+                range: utils::trim_node_text_range(&type_arg),
+            });
+        }
+
+        let args: Vec<_> = node
+            .args()
+            .map(|args| args.args().collect())
+            .unwrap_or_default();
+        let [cond] = args.as_slice() else {
+            self.new_error(AnalyzeError::ArgumentCountMismatch {
+                function_name: func_name,
+                expected: 1,
+                at_least: false,
+                found: args.len(),
+                range: node.args().map(|a| a.text_range()).unwrap_or(func_range),
+            });
+            return;
+        };
+
+        let Some(value) = self.get_value_by_range(cond.text_range()) else {
+            self.new_error(AnalyzeError::ConstantExprExpected {
+                range: cond.text_range(),
+            });
+            return;
+        };
+        match value.cast_to_bool() {
+            Ok(Value::Bool(true)) => {}
+            Ok(_) => self.new_error(AnalyzeError::StaticAssertFailed {
+                range: node.text_range(),
+            }),
+            Err(_) => {
+                let found = self
+                    .get_expr_type(cond.text_range())
+                    .cloned()
+                    .unwrap_or(Ty::Void);
+                self.new_error(AnalyzeError::TypeMismatch {
+                    expected: Ty::Bool,
+                    found,
+                    range: cond.text_range(),
+                });
+            }
+        }
+    }
+
+    /// `cfg("name")`：参数必须是常量字符串，结果是 bool 常量
+    fn check_cfg(&mut self, node: &CallExpr, func_name: String, func_range: TextRange) {
+        self.set_expr_type(node.text_range(), Ty::Bool);
+        if let Some(type_arg) = node.type_arg() {
+            self.new_error(AnalyzeError::UnexpectedTypeArgument {
+                name: func_name.clone(),
+                range: utils::trim_node_text_range(&type_arg),
+            });
+        }
+
+        let args: Vec<_> = node
+            .args()
+            .map(|args| args.args().collect())
+            .unwrap_or_default();
+        let [feature] = args.as_slice() else {
+            self.new_error(AnalyzeError::ArgumentCountMismatch {
+                function_name: func_name,
+                expected: 1,
+                at_least: false,
+                found: args.len(),
+                range: node.args().map(|a| a.text_range()).unwrap_or(func_range),
+            });
+            return;
+        };
+        let Some(Value::String(name)) = self.get_value_by_range(feature.text_range()) else {
+            self.new_error(AnalyzeError::InvalidCfgArgument {
+                range: utils::trim_node_text_range(feature),
+            });
+            return;
+        };
+        let enabled = self.config.features.contains(name);
+        self.value_table
+            .insert(node.text_range(), Value::Bool(enabled));
+    }
+
+    /// `alloc<T>(n)`：n 按 i64 参数检查，结果类型为 `*mut T`
+    fn check_alloc(&mut self, node: &CallExpr, func_name: String, func_range: TextRange) {
+        let Some(type_arg) = node.type_arg() else {
+            self.new_error(AnalyzeError::MissingTypeArgument {
+                name: func_name,
+                range: func_range,
+            });
+            return;
+        };
+        let elem_ty = match crate::utils::parse_type_node(self, &type_arg, Some(&self.value_table))
+        {
+            Ok(Some(ty)) => ty,
+            Ok(None) => return,
+            Err(e) => {
+                self.new_error(e);
+                return;
+            }
+        };
+        if elem_ty.is_invalid_void_usage() {
+            self.new_error(AnalyzeError::InvalidVoidUsage {
+                range: utils::trim_node_text_range(&type_arg),
+            });
+            return;
+        }
+        // 参数有误时仍给出结果类型，便于后续分析
+        self.set_expr_type(
+            node.text_range(),
+            Ty::Pointer {
+                pointee: Box::new(elem_ty),
+                is_const: false,
+            },
+        );
+
+        let args: Vec<_> = node
+            .args()
+            .map(|args| args.args().collect())
+            .unwrap_or_default();
+        let [count] = args.as_slice() else {
+            self.new_error(AnalyzeError::ArgumentCountMismatch {
+                function_name: func_name,
+                expected: 1,
+                at_least: false,
+                found: args.len(),
+                range: node.args().map(|a| a.text_range()).unwrap_or(func_range),
+            });
+            return;
+        };
+        if let Some(count_ty) = self.get_expr_type(count.text_range())
+            && !Ty::I64.assign_to_me_is_ok(count_ty)
+        {
+            self.new_error(AnalyzeError::ArgumentTypeMismatch(Box::new(
+                crate::error::ArgumentTypeMismatchData {
+                    function_name: func_name,
+                    param_name: "n".to_string(),
+                    arg_index: 1,
+                    expected: Ty::I64,
+                    found: count_ty.clone(),
+                    range: count.text_range(),
+                },
+            )));
+        }
+    }
+
+    /// `sizeof(T)`：不接受普通参数，结果为 u64 常量
+    fn check_sizeof(&mut self, node: &CallExpr, func_name: String, func_range: TextRange) {
+        let (ty, ty_range) = if let Some(type_arg) = node.type_arg() {
+            if let Some(args) = node.args() {
+                self.new_error(AnalyzeError::ArgumentCountMismatch {
+                    function_name: func_name,
+                    expected: 0,
+                    at_least: false,
+                    found: args.args().count(),
+                    range: args.text_range(),
+                });
+                return;
+            }
+            match crate::utils::parse_type_node(self, &type_arg, Some(&self.value_table)) {
+                Ok(Some(ty)) => (ty, utils::trim_node_text_range(&type_arg)),
+                Ok(None) => return,
+                Err(e) => {
+                    self.new_error(e);
+                    return;
+                }
+            }
+        } else if let Some((alias, range)) = self.sizeof_alias_arg(node) {
+            // 无法解析的别名已经报过错
+            let Some(Some(ty)) = self.type_aliases.get(&alias) else {
+                return;
+            };
+            (ty.clone(), range)
+        } else {
+            self.new_error(AnalyzeError::MissingTypeArgument {
+                name: func_name,
+                range: func_range,
+            });
+            return;
+        };
+        let Some((size, _)) = ty.size_and_align(self) else {
+            self.new_error(AnalyzeError::InvalidVoidUsage { range: ty_range });
+            return;
+        };
+        self.set_expr_type(node.text_range(), Ty::U64);
+        self.value_table.insert(node.text_range(), Value::U64(size));
+    }
+
+    /// `sizeof(Name)` 中 Name 是类型别名时返回别名和范围
+    ///
+    /// 解析器不知道哪些名字是别名，`sizeof(Int)` 的参数按变量表达式解析
+    fn sizeof_alias_arg(&self, node: &CallExpr) -> Option<(String, TextRange)> {
+        let args: Vec<_> = node.args()?.args().collect();
+        let [Expr::IndexVal(arg)] = args.as_slice() else {
+            return None;
+        };
+        if arg.indices().next().is_some() {
+            return None;
+        }
+        let name = arg.name()?.var_name()?;
+        self.type_aliases
+            .contains_key(&name)
+            .then(|| (name, utils::trim_node_text_range(arg)))
+    }
+
+    /// 变量表达式是否是 `sizeof(Name)` 中作为类型别名的参数
+    fn is_sizeof_alias_arg(&self, node: &IndexVal) -> bool {
+        node.syntax()
+            .parent()
+            .and_then(|params| params.parent())
+            .and_then(CallExpr::cast)
+            .filter(|call| call.name().and_then(|n| n.var_name()).as_deref() == Some(SIZEOF))
+            .is_some_and(|call| self.sizeof_alias_arg(&call).is_some())
+    }
+
+    /// `streq(a, b)`：参数按 `*const u8` 检查，结果类型为 bool，两边都是字符串常量时折叠
+    fn check_streq(&mut self, node: &CallExpr, func_name: String, func_range: TextRange) {
+        self.set_expr_type(node.text_range(), Ty::Bool);
+        if let Some(type_arg) = node.type_arg() {
+            self.new_error(AnalyzeError::UnexpectedTypeArgument {
+                name: func_name.clone(),
+                range: utils::trim_node_text_range(&type_arg),
+            });
+        }
+
+        let args: Vec<_> = node
+            .args()
+            .map(|args| args.args().collect())
+            .unwrap_or_default();
+        let [a, b] = args.as_slice() else {
+            self.new_error(AnalyzeError::ArgumentCountMismatch {
+                function_name: func_name,
+                expected: 2,
+                at_least: false,
+                found: args.len(),
+                range: Self::call_parens_range(node).unwrap_or(func_range),
+            });
+            return;
+        };
+
+        let expected = Ty::Pointer {
+            pointee: Box::new(Ty::U8),
+            is_const: true,
+        };
+        let mut ok = true;
+        for (i, (arg, param_name)) in [(a, "a"), (b, "b")].into_iter().enumerate() {
+            if let Some(arg_ty) = self.get_expr_type(arg.text_range())
+                && !expected.assign_to_me_is_ok(arg_ty)
+            {
+                self.new_error(AnalyzeError::ArgumentTypeMismatch(Box::new(
+                    crate::error::ArgumentTypeMismatchData {
+                        function_name: func_name.clone(),
+                        param_name: param_name.to_string(),
+                        arg_index: i + 1,
+                        expected: expected.clone(),
+                        found: arg_ty.clone(),
+                        range: arg.text_range(),
+                    },
+                )));
+                ok = false;
+            }
+        }
+        if !ok {
+            return;
+        }
+
+        if let (Some(Value::String(a)), Some(Value::String(b))) = (
+            self.get_value_by_range(a.text_range()),
+            self.get_value_by_range(b.text_range()),
+        ) {
+            let equal = a.as_bytes() == b.as_bytes();
+            self.value_table
+                .insert(node.text_range(), Value::Bool(equal));
+        }
+    }
+}
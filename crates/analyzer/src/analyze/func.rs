@@ -8,13 +8,49 @@ use crate::error::AnalyzeError;
 use crate::module::Module;
 use crate::r#type::Ty;
 
+/// 已知的函数属性
+const KNOWN_ATTRIBUTES: &[&str] = &["inline", "noinline"];
+
 impl FuncVisitor for Module {
     fn enter_func_def(&mut self, node: FuncDef) {
+        for attr in node.attributes() {
+            let Some((name, range)) = attr.name().and_then(|n| utils::extract_name_and_range(&n))
+            else {
+                continue;
+            };
+            if !KNOWN_ATTRIBUTES.contains(&name.as_str()) {
+                self.new_error(AnalyzeError::UnknownAttribute { name, range });
+            }
+        }
+
+        if let Some(abi) = node.abi()
+            && abi.text() != "\"C\""
+        {
+            self.new_error(AnalyzeError::UnsupportedAbi {
+                abi: abi.text().to_string(),
+                range: abi.text_range().into(),
+            });
+        }
+        // extern 函数由外部提供实现，不能有函数体
+        if node.is_extern()
+            && node.block().is_some()
+            && let Some((name, range)) = node
+                .sign()
+                .and_then(|s| s.name())
+                .and_then(|n| utils::extract_name_and_range(&n))
+        {
+            self.new_error(AnalyzeError::ImplementExternalFunction { name, range });
+        }
+
         self.analyzing.current_scope =
             self.new_scope(Some(self.analyzing.current_scope), node.text_range());
     }
 
-    fn leave_func_def(&mut self, _: FuncDef) {
+    fn leave_func_def(&mut self, node: FuncDef) {
+        if node.is_const() {
+            self.check_const_fn_body(&node);
+        }
+
         let Some(scope) = self.scopes.get(*self.analyzing.current_scope) else {
             return;
         };
@@ -28,6 +64,7 @@ impl FuncVisitor for Module {
     fn leave_func_sign(&mut self, node: FuncSign) {
         let mut param_list = vec![];
         let mut meta_type_list = vec![];
+        let mut restrict_params = vec![];
         let mut is_variadic = false;
 
         let Some(scope) = self.scopes.get(*self.analyzing.current_scope) else {
@@ -46,18 +83,24 @@ impl FuncVisitor for Module {
                 let Some(name_node) = param.name() else {
                     return;
                 };
-                let Some(ident) = name_node.ident() else {
+                let Some(name) = name_node.var_name() else {
                     return;
                 };
-                let name = ident.text();
-                let Some(vid) = scope.look_up_variable(self, name) else {
+                let Some(vid) = scope.look_up_variable(self, &name) else {
                     return;
                 };
                 let Some(var) = self.get_varaible_by_id(vid) else {
                     return;
                 };
+                if param
+                    .ty()
+                    .and_then(|ty| ty.pointer())
+                    .is_some_and(|p| p.is_restrict())
+                {
+                    restrict_params.push(param_list.len());
+                }
                 param_list.push(vid);
-                meta_type_list.push((name.to_string(), var.ty.clone()));
+                meta_type_list.push((name, var.ty.clone()));
             }
         }
 
@@ -80,12 +123,30 @@ impl FuncVisitor for Module {
             return;
         };
 
-        let have_impl = node
-            .syntax()
-            .parent()
-            .and_then(FuncDef::cast)
-            .and_then(|x| x.block())
-            .is_some();
+        // main 的返回值是进程退出码，只能是整数或 void，bool 不算整数
+        if name == "main"
+            && ret_type != Ty::Void
+            && ret_type.int_bits().is_none()
+            && let Some(ty_node) = node.ret_type()
+        {
+            self.new_error(AnalyzeError::InvalidMainSignature {
+                ty: ret_type.clone(),
+                range: utils::trim_node_text_range(&ty_node),
+            });
+        }
+
+        if name == "main"
+            && !is_main_params(&meta_type_list, is_variadic)
+            && let Some(params) = node.params()
+        {
+            self.new_error(AnalyzeError::UnsupportedMainParams {
+                range: utils::trim_node_text_range(&params),
+            });
+        }
+
+        let func_def = node.syntax().parent().and_then(FuncDef::cast);
+        let have_impl = func_def.as_ref().and_then(|x| x.block()).is_some();
+        let doc = func_def.and_then(|x| utils::leading_comments(x.syntax()));
 
         if let Some(&func_id) = self.function_map.get(&name) {
             // 更新现有的 Function，填充参数
@@ -95,6 +156,8 @@ impl FuncVisitor for Module {
                 func_data.ret_type = ret_type.clone();
                 func_data.have_local_impl = have_impl;
                 func_data.is_variadic = is_variadic;
+                func_data.restrict_params = restrict_params;
+                func_data.doc = doc;
             }
         } else {
             debug_assert!(false);
@@ -122,7 +185,7 @@ impl FuncVisitor for Module {
                     return;
                 }
                 Err(e) => {
-                    self.semantic_errors.push(e);
+                    self.new_error(e);
                     return;
                 }
             };
@@ -169,8 +232,12 @@ impl FuncVisitor for Module {
             return;
         };
 
-        // 不能实现外部函数
-        if func_id.module != self.file_id {
+        // 不能实现外部函数：其他模块的函数和 extern 函数
+        if func_id.module != self.file_id
+            || self
+                .get_function_by_id(func_id)
+                .is_some_and(|func| func.is_extern)
+        {
             self.new_error(AnalyzeError::ImplementExternalFunction {
                 name: func_name,
                 range: func_var,
@@ -211,3 +278,22 @@ impl FuncVisitor for Module {
         self.analyzing.current_function_ret_type = None;
     }
 }
+
+/// `main` 只支持无参数或 `(argc: i32, argv: *mut *mut u8)`，指针可以是 const，元素也可以是 `i8`
+fn is_main_params(params: &[(String, Ty)], is_variadic: bool) -> bool {
+    if is_variadic {
+        return false;
+    }
+    match params {
+        [] => true,
+        [(_, argc), (_, argv)] => {
+            let is_char = |ty: &Ty| matches!(ty.unwrap_const(), Ty::I8 | Ty::U8);
+            matches!(argc.unwrap_const(), Ty::I32)
+                && argv
+                    .pointer_inner()
+                    .and_then(|p| p.pointer_inner())
+                    .is_some_and(is_char)
+        }
+        _ => false,
+    }
+}
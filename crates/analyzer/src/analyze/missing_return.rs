@@ -0,0 +1,113 @@
+//! 缺少返回值检查：非 void 函数的每条路径都必须以 return 结束
+//!
+//! 保守判断：if 两个分支都返回才算返回；条件为常量 true 且没有 break 的 while/for 是死循环，
+//! 之后的代码不可达；switch 需要有 default、没有跳出 switch 的 break，并且最后一个分支返回
+//! （前面的分支要么返回，要么贯穿到后面）。
+//! 与 C 一致，`main` 函数末尾隐式返回 0，不做检查。
+
+use syntax::SyntaxNode;
+use syntax::ast::*;
+
+use crate::error::AnalyzeError;
+use crate::module::Module;
+use crate::r#type::Ty;
+
+impl Module {
+    pub(crate) fn check_missing_return(&mut self) {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let Some(comp_unit) = CompUnit::cast(root) else {
+            return;
+        };
+
+        for item in comp_unit.global_decls() {
+            let (name, block) = match item {
+                GlobalDecl::FuncDef(func) => (func.sign().and_then(|s| s.name()), func.block()),
+                GlobalDecl::FuncAttach(attach) => (attach.name(), attach.block()),
+                _ => continue,
+            };
+            let (Some((name, range)), Some(block)) =
+                (name.and_then(|n| utils::extract_name_and_range(&n)), block)
+            else {
+                continue;
+            };
+            if name == "main" {
+                continue;
+            }
+            let Some(func) = self
+                .get_function_id_by_name(&name)
+                .and_then(|id| self.get_function_by_id(id))
+            else {
+                continue;
+            };
+            if func.ret_type.unwrap_const() == Ty::Void {
+                continue;
+            }
+            if !self.block_returns(&block) {
+                self.new_error(AnalyzeError::MissingReturn { name, range });
+            }
+        }
+    }
+
+    fn block_returns(&self, block: &Block) -> bool {
+        block.items().any(|item| match item {
+            BlockItem::Stmt(stmt) => self.stmt_returns(&stmt),
+            BlockItem::VarDef(_) => false,
+        })
+    }
+
+    fn stmt_returns(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::ReturnStmt(_) => true,
+            Stmt::Block(block) => self.block_returns(block),
+            Stmt::IfStmt(if_stmt) => {
+                if_stmt.then_branch().is_some_and(|s| self.stmt_returns(&s))
+                    && if_stmt.else_branch().is_some_and(|s| self.stmt_returns(&s))
+            }
+            Stmt::WhileStmt(while_stmt) => {
+                while_stmt
+                    .condition()
+                    .is_some_and(|cond| self.is_const_true(&cond))
+                    && !while_stmt.body().is_some_and(|s| Self::has_break(&s))
+            }
+            Stmt::ForStmt(for_stmt) => {
+                for_stmt
+                    .condition()
+                    .is_none_or(|cond| self.is_const_true(&cond))
+                    && !for_stmt.body().is_some_and(|s| Self::has_break(&s))
+            }
+            Stmt::SwitchStmt(switch) => {
+                let cases: Vec<_> = switch.cases().collect();
+                cases.iter().any(|c| c.is_default())
+                    && !cases
+                        .iter()
+                        .flat_map(|c| c.body())
+                        .any(|s| Self::has_break(&s))
+                    && cases
+                        .last()
+                        .is_some_and(|c| c.body().any(|s| self.stmt_returns(&s)))
+            }
+            Stmt::AssignStmt(_)
+            | Stmt::ExprStmt(_)
+            | Stmt::BreakStmt(_)
+            | Stmt::ContinueStmt(_)
+            | Stmt::GotoStmt(_)
+            | Stmt::LabelStmt(_) => false,
+        }
+    }
+
+    /// 语句中是否有跳出当前循环或 switch 的 break，内层循环和 switch 中的 break 不算
+    fn has_break(stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::BreakStmt(_) => true,
+            Stmt::Block(block) => block.items().any(|item| match item {
+                BlockItem::Stmt(stmt) => Self::has_break(&stmt),
+                BlockItem::VarDef(_) => false,
+            }),
+            Stmt::IfStmt(if_stmt) => {
+                if_stmt.then_branch().is_some_and(|s| Self::has_break(&s))
+                    || if_stmt.else_branch().is_some_and(|s| Self::has_break(&s))
+            }
+            _ => false,
+        }
+    }
+}
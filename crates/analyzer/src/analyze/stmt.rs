@@ -1,11 +1,14 @@
 //! 语句相关的语义分析
 
+use std::collections::HashSet;
+
 use syntax::ast::*;
 use syntax::visitor::StmtVisitor;
 
 use crate::error::AnalyzeError;
 use crate::module::Module;
 use crate::r#type::Ty;
+use crate::value::Value;
 
 impl StmtVisitor for Module {
     fn enter_block(&mut self, node: Block) {
@@ -26,8 +29,34 @@ impl StmtVisitor for Module {
         self.analyzing.loop_depth += 1;
     }
 
-    fn leave_while_stmt(&mut self, _node: WhileStmt) {
+    fn leave_if_stmt(&mut self, node: IfStmt) {
+        self.check_scalar_condition(node.condition());
+        self.check_constant_condition(node.condition(), false);
+    }
+
+    fn leave_while_stmt(&mut self, node: WhileStmt) {
         self.analyzing.loop_depth -= 1;
+        self.check_scalar_condition(node.condition());
+        self.check_constant_condition(node.condition(), true);
+    }
+
+    /// for 有自己的作用域，init 中声明的变量在循环之后不可见
+    fn enter_for_stmt(&mut self, node: ForStmt) {
+        self.analyzing.current_scope =
+            self.new_scope(Some(self.analyzing.current_scope), node.text_range());
+        self.analyzing.loop_depth += 1;
+    }
+
+    fn leave_for_stmt(&mut self, node: ForStmt) {
+        self.analyzing.loop_depth -= 1;
+        self.check_scalar_condition(node.condition());
+        self.check_constant_condition(node.condition(), true);
+        self.analyzing.current_scope = self
+            .scopes
+            .get(*self.analyzing.current_scope)
+            .unwrap()
+            .parent
+            .unwrap();
     }
 
     fn leave_assign_stmt(&mut self, node: AssignStmt) {
@@ -72,8 +101,69 @@ impl StmtVisitor for Module {
         }
     }
 
+    fn enter_switch_stmt(&mut self, _node: SwitchStmt) {
+        self.analyzing.switch_depth += 1;
+    }
+
+    /// 条件必须是整数，case 标签必须是互不相同的编译期常量
+    fn leave_switch_stmt(&mut self, node: SwitchStmt) {
+        self.analyzing.switch_depth -= 1;
+
+        let Some(cond) = node.condition() else {
+            return;
+        };
+        let Some(cond_ty) = self.get_expr_type(cond.text_range()).map(Ty::unwrap_const) else {
+            return;
+        };
+        if Value::from_i64(0, &cond_ty).is_none() {
+            self.new_error(AnalyzeError::SwitchConditionNotInteger {
+                ty: cond_ty,
+                range: utils::trim_node_text_range(&cond),
+            });
+            return;
+        }
+
+        let mut labels = HashSet::new();
+        let mut has_default = false;
+        for case in node.cases() {
+            if case.is_default() {
+                if has_default {
+                    self.new_error(AnalyzeError::DuplicateCaseLabel {
+                        label: "default".to_string(),
+                        range: utils::trim_node_text_range(&case),
+                    });
+                }
+                has_default = true;
+                continue;
+            }
+            let Some(label) = case.value() else {
+                continue;
+            };
+            let range = utils::trim_node_text_range(&label);
+            let Some(v) = self
+                .get_value_by_range(label.text_range())
+                .and_then(Value::as_i64)
+            else {
+                self.new_error(AnalyzeError::ConstantExprExpected { range });
+                continue;
+            };
+            if Value::from_i64(v, &cond_ty).is_none() {
+                self.new_error(AnalyzeError::IntegerLiteralOverflow {
+                    ty: cond_ty.to_string(),
+                    literal: v.to_string(),
+                    range,
+                });
+            } else if !labels.insert(v) {
+                self.new_error(AnalyzeError::DuplicateCaseLabel {
+                    label: v.to_string(),
+                    range,
+                });
+            }
+        }
+    }
+
     fn enter_break_stmt(&mut self, node: BreakStmt) {
-        if self.analyzing.loop_depth == 0 {
+        if self.analyzing.loop_depth == 0 && self.analyzing.switch_depth == 0 {
             self.new_error(AnalyzeError::BreakOutsideLoop {
                 range: utils::trim_node_text_range(&node),
             });
@@ -81,6 +171,7 @@ impl StmtVisitor for Module {
     }
 
     fn enter_continue_stmt(&mut self, node: ContinueStmt) {
+        // switch 只能被 break 跳出，continue 必须有外层循环
         if self.analyzing.loop_depth == 0 {
             self.new_error(AnalyzeError::ContinueOutsideLoop {
                 range: utils::trim_node_text_range(&node),
@@ -88,6 +179,20 @@ impl StmtVisitor for Module {
         }
     }
 
+    fn enter_goto_stmt(&mut self, node: GotoStmt) {
+        self.new_error(AnalyzeError::UnsupportedFeature {
+            feature: "goto".to_string(),
+            range: utils::trim_node_text_range(&node),
+        });
+    }
+
+    fn enter_label_stmt(&mut self, node: LabelStmt) {
+        self.new_error(AnalyzeError::UnsupportedFeature {
+            feature: "labeled statement".to_string(),
+            range: utils::trim_node_text_range(&node),
+        });
+    }
+
     fn leave_return_stmt(&mut self, node: ReturnStmt) {
         // 获取当前函数的返回类型
         let Some(expected_ret_type) = &self.analyzing.current_function_ret_type else {
@@ -115,3 +220,38 @@ impl StmtVisitor for Module {
         }
     }
 }
+
+impl Module {
+    /// 条件中的数组名会 decay 成指针，恒为真，多半是漏写了下标
+    fn check_scalar_condition(&mut self, cond: Option<Expr>) {
+        if let Some(cond) = cond
+            && self.array_lvalue_type(&cond).is_some()
+        {
+            self.new_error(AnalyzeError::ArrayInScalarContext {
+                range: utils::trim_node_text_range(&cond),
+            });
+        }
+    }
+
+    /// 折叠成常量的条件恒为真或恒为假，给出提示
+    ///
+    /// 循环的 `while (1)` 是惯用的无限循环，`is_loop` 时恒为真不提示
+    fn check_constant_condition(&mut self, cond: Option<Expr>, is_loop: bool) {
+        let Some(cond) = cond else {
+            return;
+        };
+        let Some(Ok(Value::Bool(value))) = self
+            .get_value_by_range(cond.text_range())
+            .map(|v| v.cast_to_bool())
+        else {
+            return;
+        };
+        if is_loop && value {
+            return;
+        }
+        self.new_error(AnalyzeError::ConstantCondition {
+            value,
+            range: utils::trim_node_text_range(&cond),
+        });
+    }
+}
@@ -0,0 +1,140 @@
+//! 无条件递归检查：函数的每条路径都在返回之前调用自身时给出警告
+//!
+//! 保守判断：只有一定会执行的表达式中的自调用才算（`&&`/`||` 的右操作数不算），
+//! if 两个分支都递归才算递归；在递归之前可能 return、break 或 continue 的语句块不算。
+//! 循环体只在条件为常量 true 时才一定执行。
+
+use syntax::SyntaxNode;
+use syntax::ast::*;
+use syntax::syntax_kind::SyntaxKind;
+
+use crate::error::AnalyzeError;
+use crate::module::Module;
+
+impl Module {
+    pub(crate) fn check_unconditional_recursion(&mut self) {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let Some(comp_unit) = CompUnit::cast(root) else {
+            return;
+        };
+
+        for item in comp_unit.global_decls() {
+            let (name, block) = match item {
+                GlobalDecl::FuncDef(func) => (func.sign().and_then(|s| s.name()), func.block()),
+                GlobalDecl::FuncAttach(attach) => (attach.name(), attach.block()),
+                _ => continue,
+            };
+            let (Some((name, range)), Some(block)) =
+                (name.and_then(|n| utils::extract_name_and_range(&n)), block)
+            else {
+                continue;
+            };
+            if self.block_recurses(&block, &name) {
+                self.new_error(AnalyzeError::UnconditionalRecursion { name, range });
+            }
+        }
+    }
+
+    /// 顺序执行的语句中，在可能离开之前一定会调用 `name`
+    fn block_recurses(&self, block: &Block, name: &str) -> bool {
+        for item in block.items() {
+            let recurses = match &item {
+                BlockItem::Stmt(stmt) => self.stmt_recurses(stmt, name),
+                BlockItem::VarDef(def) => Self::node_calls(def.syntax(), name),
+            };
+            if recurses {
+                return true;
+            }
+            if Self::may_leave(item.syntax()) {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn stmt_recurses(&self, stmt: &Stmt, name: &str) -> bool {
+        match stmt {
+            Stmt::ExprStmt(_) | Stmt::AssignStmt(_) | Stmt::ReturnStmt(_) => {
+                Self::node_calls(stmt.syntax(), name)
+            }
+            Stmt::Block(block) => self.block_recurses(block, name),
+            Stmt::IfStmt(if_stmt) => {
+                if_stmt
+                    .condition()
+                    .is_some_and(|c| Self::expr_calls(&c, name))
+                    || (if_stmt
+                        .then_branch()
+                        .is_some_and(|s| self.stmt_recurses(&s, name))
+                        && if_stmt
+                            .else_branch()
+                            .is_some_and(|s| self.stmt_recurses(&s, name)))
+            }
+            Stmt::WhileStmt(while_stmt) => match while_stmt.condition() {
+                Some(cond) if Self::expr_calls(&cond, name) => true,
+                Some(cond) => {
+                    self.is_const_true(&cond)
+                        && while_stmt
+                            .body()
+                            .is_some_and(|s| self.stmt_recurses(&s, name))
+                }
+                None => false,
+            },
+            Stmt::ForStmt(for_stmt) => {
+                for_stmt
+                    .init()
+                    .is_some_and(|init| Self::node_calls(init.syntax(), name))
+                    || for_stmt
+                        .condition()
+                        .is_some_and(|c| Self::expr_calls(&c, name))
+            }
+            Stmt::SwitchStmt(switch) => switch
+                .condition()
+                .is_some_and(|c| Self::expr_calls(&c, name)),
+            Stmt::BreakStmt(_) | Stmt::ContinueStmt(_) | Stmt::GotoStmt(_) | Stmt::LabelStmt(_) => {
+                false
+            }
+        }
+    }
+
+    /// 节点中一定会求值的表达式是否调用了 `name`
+    fn node_calls(node: &SyntaxNode, name: &str) -> bool {
+        node.children()
+            .any(|child| match Expr::cast(child.clone()) {
+                Some(expr) => Self::expr_calls(&expr, name),
+                None => Self::node_calls(&child, name),
+            })
+    }
+
+    fn expr_calls(expr: &Expr, name: &str) -> bool {
+        match expr {
+            Expr::CallExpr(call)
+                if call
+                    .name()
+                    .and_then(|n| n.var_name())
+                    .is_some_and(|n| n == name) =>
+            {
+                true
+            }
+            // 短路运算的右操作数不一定求值
+            Expr::BinaryExpr(binary)
+                if binary.op().is_some_and(|op| op.op().kind().is_logical_op()) =>
+            {
+                binary.lhs().is_some_and(|lhs| Self::expr_calls(&lhs, name))
+            }
+            _ => Self::node_calls(expr.syntax(), name),
+        }
+    }
+
+    /// 语句中是否有 return、break、continue 或 goto，可能在递归之前离开
+    fn may_leave(node: &SyntaxNode) -> bool {
+        node.descendants().any(|n| {
+            matches!(
+                n.kind(),
+                SyntaxKind::RETURN_STMT
+                    | SyntaxKind::BREAK_STMT
+                    | SyntaxKind::CONTINUE_STMT
+                    | SyntaxKind::GOTO_STMT
+            )
+        })
+    }
+}
@@ -4,7 +4,7 @@
 use std::collections::HashMap;
 
 use miette::Diagnostic;
-use syntax::ast::{Expr, InitVal};
+use syntax::ast::{Designator, Expr, InitVal};
 use syntax::{AirycLanguage, AstNode};
 use thiserror::Error;
 use tools::TextRange;
@@ -92,6 +92,53 @@ pub enum ArrayInitError {
     #[diagnostic(code(array::initial_struct_value))]
     InitialStructValue(#[from] AnalyzeError),
 
+    #[error("array designator must be an integer constant")]
+    #[diagnostic(code(array::non_const_designator))]
+    NonConstDesignator {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("array designator index {index} is out of range for array of length {len}")]
+    #[diagnostic(code(array::designator_out_of_range))]
+    DesignatorOutOfRange {
+        index: i64,
+        len: i32,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("array repeat count must be an integer constant")]
+    #[diagnostic(code(array::non_const_repeat_count))]
+    NonConstRepeatCount {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("array repeat count {count} is out of range for array of length {len}")]
+    #[diagnostic(code(array::repeat_count_out_of_range))]
+    RepeatCountOutOfRange {
+        count: i64,
+        len: i32,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("repeated array element must be a constant without designator")]
+    #[diagnostic(code(array::invalid_repeat_value))]
+    InvalidRepeatValue {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("array element {index} is initialized more than once")]
+    #[diagnostic(code(array::duplicate_index))]
+    DuplicateIndex {
+        index: usize,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("expected {expected}, found {found}")]
     #[diagnostic(code(array::type_mismatch))]
     TypeMismatch {
@@ -102,6 +149,38 @@ pub enum ArrayInitError {
     },
 }
 
+impl ArrayInitError {
+    /// 转换为 AnalyzeError：元素类型不匹配、下标指示符和 struct 初始化错误报告在出错的元素上，
+    /// 其他错误报告在整个初始化列表 `range` 上
+    pub(crate) fn into_analyze_error(self, range: TextRange) -> AnalyzeError {
+        match self {
+            Self::TypeMismatch {
+                expected,
+                found,
+                range,
+            } => AnalyzeError::TypeMismatch {
+                expected,
+                found,
+                range,
+            },
+            Self::InitialStructValue(e) => e,
+            e @ (Self::NonConstDesignator { range }
+            | Self::DesignatorOutOfRange { range, .. }
+            | Self::NonConstRepeatCount { range }
+            | Self::RepeatCountOutOfRange { range, .. }
+            | Self::InvalidRepeatValue { range }
+            | Self::DuplicateIndex { range, .. }) => AnalyzeError::ArrayError {
+                message: Box::new(e),
+                range,
+            },
+            e => AnalyzeError::ArrayError {
+                message: Box::new(e),
+                range,
+            },
+        }
+    }
+}
+
 impl ArrayTree {
     /// 由调用方处理常量表
     pub fn new(
@@ -109,22 +188,72 @@ impl ArrayTree {
         ty: &Ty,
         init_val: InitVal,
     ) -> Result<(ArrayTree, bool), ArrayInitError> {
-        let Some(first_child) = init_val.first_child() else {
+        if init_val.first_child().is_none() {
             return Ok((ArrayTree::Val(ArrayTreeValue::Empty), true));
-        };
+        }
         let mut is_const = true;
+        let array_tree = Self::build_list(m, ty, &init_val, &mut is_const)?;
+        Ok((array_tree, is_const))
+    }
+
+    /// 构建一层带括号的初始化列表，`{ value; count }` 展开成 count 个相同的元素
+    fn build_list(
+        m: &mut Module,
+        ty: &Ty,
+        list: &InitVal,
+        is_const: &mut bool,
+    ) -> Result<ArrayTree, ArrayInitError> {
+        let mut first_child = list.first_child();
+        let Some(repeat) = list.repeat() else {
+            return Self::build(m, ty, &mut first_child, is_const, true);
+        };
+        let Ty::Array(_, Some(len)) = ty.unwrap_const() else {
+            return Err(ArrayInitError::AssignArrayToNumber);
+        };
 
-        match Self::build(m, ty, &mut Some(first_child), &mut is_const) {
-            Ok(array_tree) => Ok((array_tree, is_const)),
-            Err(e) => Err(e),
+        let range = utils::trim_node_text_range(&repeat);
+        let count = repeat
+            .count()
+            .and_then(|e| m.value_table.get(&e.text_range()))
+            .and_then(Value::as_i64)
+            .ok_or(ArrayInitError::NonConstRepeatCount { range })?;
+        let count = usize::try_from(count)
+            .ok()
+            .filter(|c| *c <= len.max(0) as usize)
+            .ok_or(ArrayInitError::RepeatCountOutOfRange { count, len, range })?;
+
+        // 重复的元素只构建一次，必须是常量，否则副作用会被重复执行
+        let invalid_value = ArrayInitError::InvalidRepeatValue {
+            range: first_child
+                .as_ref()
+                .map_or(range, utils::trim_node_text_range),
+        };
+        if first_child
+            .as_ref()
+            .is_none_or(|v| v.designator().is_some())
+        {
+            return Err(invalid_value);
+        }
+        let mut value_is_const = true;
+        let ArrayTree::Children(mut children) =
+            Self::build(m, ty, &mut first_child, &mut value_is_const, true)?
+        else {
+            unreachable!("array type builds children");
+        };
+        match children.pop() {
+            Some(element) if value_is_const => Ok(ArrayTree::Children(vec![element; count])),
+            _ => Err(invalid_value),
         }
     }
 
+    /// `braced` 表示 `cursor` 是这一层数组自己的 `{...}` 列表；
+    /// 为 false 时省略了括号，元素从外层列表中顺序取用
     fn build(
         m: &mut Module,
         ty: &Ty,
         cursor: &mut Option<InitVal>,
         is_const: &mut bool,
+        braced: bool,
     ) -> Result<ArrayTree, ArrayInitError> {
         match ty {
             Ty::I32
@@ -177,42 +306,101 @@ impl ArrayTree {
                 // 如果 count 为 None，说明还没有常量折叠，暂时返回错误
                 let count_val = count.ok_or(ArrayInitError::MisMatchIndexAndType)?;
                 let mut children_vec = Vec::with_capacity(count_val as usize);
-                for _ in 0..count_val {
-                    let Some(u) = cursor else {
-                        break;
-                    };
-                    if u.is_subtree() {
+                // 下一个元素的位置，遇到下标指示符时跳转；未初始化的位置用 Empty 占位
+                let mut index = 0usize;
+                let mut initialized = vec![false; count_val.max(0) as usize];
+                let mut is_first = true;
+                while let Some(u) = cursor.clone() {
+                    let designator = u.designator();
+                    if let Some(designator) = &designator {
+                        // 省略括号时，后面的指示符属于外层列表
+                        if !braced && !is_first {
+                            break;
+                        }
+                        if braced {
+                            index = Self::designator_index(m, designator, count_val)?;
+                        }
+                    }
+                    is_first = false;
+                    if index >= initialized.len() {
+                        if !braced {
+                            break;
+                        }
+                        // 多余元素直接忽略，但后面可能还有指示符
+                        *cursor = u.next_sibling();
+                        continue;
+                    }
+
+                    let subtree = if u.is_subtree() {
                         let sibling = u.next_sibling();
                         let subtree = if inner.is_array() {
-                            let mut first_child = u.first_child();
                             // 可能有多余元素，直接忽略
-                            Self::build(m, inner, &mut first_child, is_const)?
+                            Self::build_list(m, inner, &u, is_const)?
                         } else {
                             // 否则应该是 Struct
-                            Self::build(m, inner, cursor, is_const)?
+                            Self::build(m, inner, cursor, is_const, false)?
                         };
-                        children_vec.push(subtree);
                         *cursor = sibling;
+                        subtree
                     } else if u.try_expr().is_some() {
-                        let subtree = Self::build(m, inner, cursor, is_const)?;
-                        children_vec.push(subtree);
+                        Self::build(m, inner, cursor, is_const, false)?
                     } else {
                         // {}
                         if inner.is_array() {
-                            children_vec.push(ArrayTree::Val(ArrayTreeValue::Empty));
                             *cursor = u.next_sibling();
+                            ArrayTree::Val(ArrayTreeValue::Empty)
+                        } else if inner.is_struct() {
+                            // 零初始化的 struct
+                            let sibling = u.next_sibling();
+                            let subtree = Self::build(m, inner, cursor, is_const, false)?;
+                            *cursor = sibling;
+                            subtree
                         } else {
                             return Err(ArrayInitError::AssignArrayToNumber);
                         }
+                    };
+
+                    if initialized[index] {
+                        return Err(ArrayInitError::DuplicateIndex {
+                            index,
+                            range: designator.map_or_else(
+                                || utils::trim_node_text_range(&u),
+                                |d| utils::trim_node_text_range(&d),
+                            ),
+                        });
                     }
+                    initialized[index] = true;
+                    if children_vec.len() <= index {
+                        children_vec.resize(index + 1, ArrayTree::Val(ArrayTreeValue::Empty));
+                    }
+                    children_vec[index] = subtree;
+                    index += 1;
                 }
                 Ok(ArrayTree::Children(children_vec))
             }
-            Ty::Const(inner) => Self::build(m, inner, cursor, is_const),
+            Ty::Const(inner) => Self::build(m, inner, cursor, is_const, braced),
             Ty::Void => unreachable!(),
         }
     }
 
+    /// 下标指示符的值，必须是 `[0, len)` 内的整数常量
+    fn designator_index(
+        m: &Module,
+        designator: &Designator,
+        len: i32,
+    ) -> Result<usize, ArrayInitError> {
+        let range = utils::trim_node_text_range(designator);
+        let index = designator
+            .index()
+            .and_then(|e| m.value_table.get(&e.text_range()))
+            .and_then(Value::as_i64)
+            .ok_or(ArrayInitError::NonConstDesignator { range })?;
+        usize::try_from(index)
+            .ok()
+            .filter(|i| *i < len.max(0) as usize)
+            .ok_or(ArrayInitError::DesignatorOutOfRange { index, len, range })
+    }
+
     /// 获取叶子节点
     pub fn get_leaf(&self, indices: &[i32]) -> Result<ArrayTreeValue, ArrayInitError> {
         let mut u = self;
@@ -1,6 +1,8 @@
 #![allow(unused_assignments)]
 
 mod recursive_type;
+mod unused_function;
+mod unused_variable;
 
 use std::{collections::HashMap, fmt::Debug};
 
@@ -9,6 +11,8 @@ use vfs::FileID;
 use crate::{error::AnalyzeError, module::Module};
 
 pub use recursive_type::RecursiveTypeChecker;
+pub use unused_function::UnusedFunctionChecker;
+pub use unused_variable::UnusedVariableChecker;
 
 /// Project 级别的检查
 pub trait ProjectChecker: Send + Sync + Debug {
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use vfs::FileID;
+
+use crate::{checker::ProjectChecker, error::AnalyzeError, module::Module};
+
+/// 检查从未被调用的函数
+///
+/// 跨文件调用也计入引用；`main` 和只有声明（外部实现）的函数不检查
+#[derive(Debug, Default)]
+pub struct UnusedFunctionChecker;
+
+impl ProjectChecker for UnusedFunctionChecker {
+    fn check_project(
+        &mut self,
+        modules: &HashMap<FileID, Module>,
+    ) -> HashMap<FileID, Vec<AnalyzeError>> {
+        let mut errors: HashMap<FileID, Vec<AnalyzeError>> = HashMap::new();
+
+        for (file_id, module) in modules {
+            for (name, func_id) in &module.function_map {
+                // 导入的函数由定义它的模块负责检查
+                if func_id.module != *file_id || name == "main" {
+                    continue;
+                }
+                let Some(func) = module.functions.get(func_id.index) else {
+                    continue;
+                };
+                if !func.have_local_impl {
+                    continue;
+                }
+                if module
+                    .index
+                    .function_reference
+                    .get(func_id)
+                    .is_some_and(|citers| !citers.is_empty())
+                {
+                    continue;
+                }
+                errors
+                    .entry(*file_id)
+                    .or_default()
+                    .push(AnalyzeError::UnusedFunction {
+                        name: name.clone(),
+                        range: func.range,
+                    });
+            }
+        }
+
+        for errors in errors.values_mut() {
+            errors.sort_by_key(|e| *e.range());
+        }
+        errors
+    }
+}
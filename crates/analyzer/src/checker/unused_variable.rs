@@ -0,0 +1,58 @@
+use std::collections::{HashMap, HashSet};
+
+use vfs::FileID;
+
+use crate::{checker::ProjectChecker, error::AnalyzeError, module::Module};
+
+/// 检查从未被引用的局部变量
+///
+/// 全局变量、函数参数以及以 `_` 开头的变量不检查
+#[derive(Debug, Default)]
+pub struct UnusedVariableChecker;
+
+impl ProjectChecker for UnusedVariableChecker {
+    fn check_project(
+        &mut self,
+        modules: &HashMap<FileID, Module>,
+    ) -> HashMap<FileID, Vec<AnalyzeError>> {
+        let mut errors: HashMap<FileID, Vec<AnalyzeError>> = HashMap::new();
+
+        for (file_id, module) in modules {
+            let mut skipped: HashSet<_> = module
+                .functions
+                .iter()
+                .flat_map(|(_, func)| func.params.iter().copied())
+                .collect();
+            if let Some(global) = module.scopes.get(*module.global_scope) {
+                skipped.extend(global.variables.values().copied());
+            }
+
+            // variable_map 按定义位置排序
+            for var_id in module.variable_map.values() {
+                if skipped.contains(var_id) {
+                    continue;
+                }
+                let Some(var) = module.variables.get(**var_id) else {
+                    continue;
+                };
+                if var.name.starts_with('_')
+                    || module
+                        .index
+                        .variable_reference
+                        .get(var_id)
+                        .is_some_and(|citers| !citers.is_empty())
+                {
+                    continue;
+                }
+                errors
+                    .entry(*file_id)
+                    .or_default()
+                    .push(AnalyzeError::UnusedVariable {
+                        name: var.name.clone(),
+                        range: var.range,
+                    });
+            }
+        }
+        errors
+    }
+}
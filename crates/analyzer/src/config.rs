@@ -0,0 +1,16 @@
+//! 分析配置
+
+use std::collections::HashSet;
+
+/// 分析配置，由 Project 共享给所有模块
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzeConfig {
+    /// 启用的特性，`cfg("name")` 在 name 启用时折叠为 true
+    pub features: HashSet<String>,
+    /// 把警告和提示当作错误，用于 CI
+    pub deny_warnings: bool,
+    /// 统计各分析阶段的耗时，由 `Module::analyze` 返回
+    pub profile: bool,
+    /// 编译可执行文件：所有模块都没有 main 时给出提示
+    pub executable: bool,
+}
@@ -0,0 +1,31 @@
+//! 统一的诊断信息，词法、语法和语义错误都转换成同一种结构，供语言服务器等使用
+
+use miette::{Diagnostic as _, Severity};
+use tools::TextRange;
+
+use crate::error::AnalyzeError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: TextRange,
+    pub severity: Severity,
+    /// 稳定错误码，见 `AnalyzeError::error_code`
+    pub code: &'static str,
+    /// 错误信息，有帮助信息时附在后面
+    pub message: String,
+}
+
+impl From<&AnalyzeError> for Diagnostic {
+    fn from(error: &AnalyzeError) -> Self {
+        let mut message = error.to_string();
+        if let Some(help) = error.help() {
+            message = format!("{message}\nhelp: {help}");
+        }
+        Self {
+            range: *error.range(),
+            severity: error.severity().unwrap_or(Severity::Error),
+            code: error.error_code(),
+            message,
+        }
+    }
+}
@@ -147,6 +147,39 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("type alias '{name}' is already defined")]
+    #[diagnostic(code(semantic::type_alias_defined))]
+    TypeAliasDefined {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("type '{name}' undefined")]
+    #[diagnostic(code(semantic::type_undefined))]
+    TypeUndefined {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("enum backing type must be an integer type, found {ty}")]
+    #[diagnostic(code(semantic::invalid_enum_backing_type))]
+    InvalidEnumBackingType {
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("variant '{variant}' not found in enum '{enum_name}'")]
+    #[diagnostic(code(semantic::enum_variant_undefined))]
+    EnumVariantUndefined {
+        enum_name: String,
+        variant: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("field '{field_name}' not found in struct '{struct_name}'")]
     #[diagnostic(code(semantic::field_not_found))]
     FieldNotFound {
@@ -200,6 +233,16 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("void value used in expression")]
+    #[diagnostic(
+        code(semantic::void_in_expression),
+        help("a call to a function returning void has no value")
+    )]
+    VoidInExpression {
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("cannot dereference void pointer")]
     #[diagnostic(code(semantic::void_pointer_deref))]
     VoidPointerDeref {
@@ -207,6 +250,17 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("variable '{name}' is used before being assigned")]
+    #[diagnostic(
+        code(semantic::use_before_init),
+        help("assign a value on every path before reading it, or add an initializer")
+    )]
+    UseBeforeInit {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("function '{name}' is not defined")]
     #[diagnostic(code(semantic::function_undefined))]
     FunctionUndefined {
@@ -215,13 +269,67 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("function '{name}' is never used")]
+    #[diagnostic(code(semantic::unused_function), severity(Advice))]
+    UnusedFunction {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("variable '{name}' is never used")]
+    #[diagnostic(
+        code(semantic::unused_variable),
+        severity(Advice),
+        help("prefix the name with '_' or add `// nanoc:allow(unused_variable)`")
+    )]
+    UnusedVariable {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("'{name}' is a variable of type {ty}, not a function")]
+    #[diagnostic(code(semantic::not_callable))]
+    NotCallable {
+        name: String,
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("function '{name}' does not take a type argument")]
+    #[diagnostic(
+        code(semantic::unexpected_type_argument),
+        help("only the builtin 'alloc<T>(n)' takes a type argument")
+    )]
+    UnexpectedTypeArgument {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("'{name}' requires a type argument")]
+    #[diagnostic(
+        code(semantic::missing_type_argument),
+        help("write it as alloc<T>(n) or sizeof(T)")
+    )]
+    MissingTypeArgument {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error(
-        "function '{function_name}' argument count mismatch: expected {expected}, found {found}"
+        "function '{function_name}' argument count mismatch: expected {}{expected}, found {found}",
+        if *.at_least { "at least " } else { "" }
     )]
     #[diagnostic(code(semantic::argument_count_mismatch))]
     ArgumentCountMismatch {
         function_name: String,
         expected: usize,
+        /// 可变参数函数只要求不少于 `expected` 个参数
+        at_least: bool,
         found: usize,
         #[label("here")]
         range: TextRange,
@@ -246,13 +354,60 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("switch condition must be an integer type, found {ty}")]
+    #[diagnostic(code(semantic::switch_condition_not_integer))]
+    SwitchConditionNotInteger {
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("'restrict' is only allowed on the outermost pointer of a function parameter")]
+    #[diagnostic(code(semantic::misplaced_restrict))]
+    MisplacedRestrict {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("array used where a scalar is expected")]
+    #[diagnostic(
+        code(semantic::array_in_scalar_context),
+        help("index an element, or take '&arr[0]' for a pointer")
+    )]
+    ArrayInScalarContext {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("duplicate case label {label}")]
+    #[diagnostic(code(semantic::duplicate_case_label))]
+    DuplicateCaseLabel {
+        label: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("continue statement outside loop")]
-    #[diagnostic(code(semantic::continue_outside_loop))]
+    #[diagnostic(
+        code(semantic::continue_outside_loop),
+        help("a switch is not a loop; continue needs an enclosing while or for")
+    )]
     ContinueOutsideLoop {
         #[label("here")]
         range: TextRange,
     },
 
+    #[error("{feature} is not supported")]
+    #[diagnostic(
+        code(semantic::unsupported_feature),
+        help("use while/for with break and continue instead")
+    )]
+    UnsupportedFeature {
+        feature: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("return type mismatch: expected {expected}, found {found}")]
     #[diagnostic(code(semantic::return_type_mismatch))]
     ReturnTypeMismatch {
@@ -313,6 +468,48 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("type alias `{name}` refers to itself")]
+    #[diagnostic(
+        code(semantic::cyclic_type_alias),
+        help("{}", cycle.join(" -> "))
+    )]
+    CyclicTypeAlias {
+        name: String,
+        cycle: Vec<String>,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("`.len` is only available on arrays with a known size, found {ty}")]
+    #[diagnostic(code(semantic::len_on_non_array))]
+    LenOnNonArray {
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("type {ty} cannot be indexed")]
+    #[diagnostic(
+        code(semantic::not_indexable),
+        help("only arrays and pointers support `[]`")
+    )]
+    NotIndexable {
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("struct '{name}' is declared but never defined")]
+    #[diagnostic(
+        code(semantic::incomplete_struct),
+        help("only pointers to a forward-declared struct can be used until it is defined")
+    )]
+    IncompleteStruct {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("initializer type mismatch: expected {expected}, found {found}")]
     #[diagnostic(code(semantic::initializer_mismatch))]
     InitializerMismatch {
@@ -332,6 +529,145 @@ pub enum AnalyzeError {
         range: TextRange,
     },
 
+    #[error("cyclic dependency in initializer of constant '{name}'")]
+    #[diagnostic(code(semantic::cyclic_const_init))]
+    CyclicConstInit {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("unsupported ABI {abi}")]
+    #[diagnostic(
+        code(semantic::unsupported_abi),
+        help("only extern \"C\" is supported")
+    )]
+    UnsupportedAbi {
+        abi: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("unknown attribute '@{name}'")]
+    #[diagnostic(
+        code(semantic::unknown_attribute),
+        help("known attributes: @inline, @noinline")
+    )]
+    UnknownAttribute {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("operation not allowed in const fn")]
+    #[diagnostic(
+        code(semantic::non_const_fn_body),
+        help(
+            "const fn may only use integer and bool values, arithmetic, control flow and calls to other const fns"
+        )
+    )]
+    NonConstFnBody {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("function '{name}' may reach the end without returning a value")]
+    #[diagnostic(
+        code(semantic::missing_return),
+        help("add a return statement at the end of the function body")
+    )]
+    MissingReturn {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("function '{name}' calls itself on every path and will never return")]
+    #[diagnostic(
+        code(semantic::unconditional_recursion),
+        severity(Warning),
+        help("add a base case that returns without calling '{name}'")
+    )]
+    UnconditionalRecursion {
+        name: String,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("condition is always {value}")]
+    #[diagnostic(
+        code(semantic::constant_condition),
+        severity(Advice),
+        help("the code that never runs can be removed")
+    )]
+    ConstantCondition {
+        value: bool,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("'main' must return an integer type or void, found {ty}")]
+    #[diagnostic(
+        code(semantic::invalid_main_signature),
+        help("the return value of 'main' is the process exit code")
+    )]
+    InvalidMainSignature {
+        ty: Ty,
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("no 'main' function found")]
+    #[diagnostic(
+        code(semantic::missing_main),
+        severity(Advice),
+        help("an executable needs an entry point such as `fn main() -> i32`")
+    )]
+    MissingMain {
+        /// 针对整个文件，没有具体位置，取文件开头的空范围
+        range: TextRange,
+    },
+
+    #[error("unsupported parameters for 'main'")]
+    #[diagnostic(
+        code(semantic::main_params),
+        severity(Warning),
+        help("expected no parameters or (argc: i32, argv: *mut *mut u8)")
+    )]
+    UnsupportedMainParams {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("static assertion failed")]
+    #[diagnostic(code(semantic::static_assert_failed))]
+    StaticAssertFailed {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("cfg expects a constant string feature name")]
+    #[diagnostic(
+        code(semantic::invalid_cfg_argument),
+        help("write it as cfg(\"name\")")
+    )]
+    InvalidCfgArgument {
+        #[label("here")]
+        range: TextRange,
+    },
+
+    #[error("shift count {count} is out of range for a {width}-bit integer")]
+    #[diagnostic(
+        code(semantic::invalid_shift_count),
+        help("the shift count must be in 0..{width}")
+    )]
+    InvalidShiftCount {
+        count: i64,
+        width: u32,
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("{err}")]
     #[diagnostic(code(unescape))]
     UnescapeError {
@@ -359,20 +695,41 @@ impl AnalyzeError {
             | Self::ArrayError { range, .. }
             | Self::StructDefined { range, .. }
             | Self::StructUndefined { range, .. }
+            | Self::TypeAliasDefined { range, .. }
+            | Self::TypeUndefined { range, .. }
+            | Self::CyclicTypeAlias { range, .. }
+            | Self::LenOnNonArray { range, .. }
+            | Self::NotIndexable { range, .. }
+            | Self::IncompleteStruct { range, .. }
+            | Self::InvalidEnumBackingType { range, .. }
+            | Self::EnumVariantUndefined { range, .. }
             | Self::FieldNotFound { range, .. }
             | Self::NotAStruct { range, .. }
             | Self::NotAStructPointer { range, .. }
             | Self::StructInitFieldCountMismatch { range, .. }
+            | Self::UseBeforeInit { range, .. }
             | Self::FunctionUndefined { range, .. }
+            | Self::NotCallable { range, .. }
+            | Self::UnexpectedTypeArgument { range, .. }
+            | Self::MissingTypeArgument { range, .. }
+            | Self::UnusedFunction { range, .. }
+            | Self::UnusedVariable { range, .. }
+            | Self::ConstantCondition { range, .. }
             | Self::ArgumentCountMismatch { range, .. }
             | Self::AssignToConst { range, .. }
             | Self::BreakOutsideLoop { range }
+            | Self::SwitchConditionNotInteger { range, .. }
+            | Self::ArrayInScalarContext { range }
+            | Self::MisplacedRestrict { range }
+            | Self::UnsupportedFeature { range, .. }
+            | Self::DuplicateCaseLabel { range, .. }
             | Self::ContinueOutsideLoop { range }
             | Self::ReturnTypeMismatch { range, .. }
             | Self::NotALValue { range }
             | Self::ApplyOpOnType { range, .. }
             | Self::InvalidVoidUsage { range }
             | Self::VoidPointerDeref { range }
+            | Self::VoidInExpression { range }
             | Self::AddressOfRight { range }
             | Self::FunctionImplemented { range, .. }
             | Self::ImplementExternalFunction { range, .. }
@@ -382,7 +739,99 @@ impl AnalyzeError {
             | Self::RecursiveType { range, .. }
             | Self::InitializerMismatch { range, .. }
             | Self::BinaryOpTypeMismatch { range, .. }
+            | Self::CyclicConstInit { range, .. }
+            | Self::UnknownAttribute { range, .. }
+            | Self::UnsupportedAbi { range, .. }
+            | Self::NonConstFnBody { range }
+            | Self::UnsupportedMainParams { range }
+            | Self::InvalidMainSignature { range, .. }
+            | Self::MissingMain { range }
+            | Self::MissingReturn { range, .. }
+            | Self::UnconditionalRecursion { range, .. }
+            | Self::StaticAssertFailed { range }
+            | Self::InvalidCfgArgument { range }
+            | Self::InvalidShiftCount { range, .. }
             | Self::UnescapeError { range, .. } => range,
         }
     }
+
+    /// 稳定的错误码，编号含义见 README 的“错误码”一节
+    ///
+    /// 分组：E01xx 词法与语法，E02xx 名称与定义，E03xx 类型，E04xx 常量，
+    /// E05xx 语句与左值，E06xx 导入。已分配的错误码不再改动或复用
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::ParserError(_) => "E0100",
+            Self::IntegerLiteralOverflow { .. } => "E0101",
+            Self::InvalidCharLiteral { .. } => "E0102",
+            Self::UnescapeError { .. } => "E0103",
+            Self::UnknownAttribute { .. } => "E0104",
+            Self::UnsupportedAbi { .. } => "E0105",
+            Self::VariableDefined { .. } => "E0201",
+            Self::FunctionDefined { .. } => "E0202",
+            Self::FunctionImplemented { .. } => "E0203",
+            Self::ImplementExternalFunction { .. } => "E0204",
+            Self::VariableUndefined { .. } => "E0205",
+            Self::StructDefined { .. } => "E0206",
+            Self::StructUndefined { .. } => "E0207",
+            Self::EnumVariantUndefined { .. } => "E0208",
+            Self::FieldNotFound { .. } => "E0209",
+            Self::FunctionUndefined { .. } => "E0210",
+            Self::NotCallable { .. } => "E0211",
+            Self::UnusedFunction { .. } => "E0212",
+            Self::UseBeforeInit { .. } => "E0213",
+            Self::TypeAliasDefined { .. } => "E0214",
+            Self::TypeUndefined { .. } => "E0215",
+            Self::UnusedVariable { .. } => "E0216",
+            Self::MissingMain { .. } => "E0217",
+            Self::TypeMismatch { .. } => "E0301",
+            Self::ArgumentTypeMismatch(_) => "E0302",
+            Self::ArgumentCountMismatch { .. } => "E0303",
+            Self::ReturnTypeMismatch { .. } => "E0304",
+            Self::BinaryOpTypeMismatch { .. } => "E0305",
+            Self::ApplyOpOnType { .. } => "E0306",
+            Self::InitializerMismatch { .. } => "E0307",
+            Self::ArrayError { .. } => "E0308",
+            Self::NotAStruct { .. } => "E0309",
+            Self::NotAStructPointer { .. } => "E0310",
+            Self::StructInitFieldCountMismatch { .. } => "E0311",
+            Self::InvalidVoidUsage { .. } => "E0312",
+            Self::VoidInExpression { .. } => "E0313",
+            Self::VoidPointerDeref { .. } => "E0314",
+            Self::InvalidEnumBackingType { .. } => "E0315",
+            Self::RecursiveType { .. } => "E0316",
+            Self::SwitchConditionNotInteger { .. } => "E0317",
+            Self::UnexpectedTypeArgument { .. } => "E0318",
+            Self::MissingTypeArgument { .. } => "E0319",
+            Self::ArrayInScalarContext { .. } => "E0320",
+            Self::MisplacedRestrict { .. } => "E0321",
+            Self::CyclicTypeAlias { .. } => "E0322",
+            Self::LenOnNonArray { .. } => "E0323",
+            Self::NotIndexable { .. } => "E0324",
+            Self::IncompleteStruct { .. } => "E0325",
+            Self::ConstantExprExpected { .. } => "E0401",
+            Self::ConstArithmeticOverflow { .. } => "E0402",
+            Self::ExpectInitialVal { .. } => "E0403",
+            Self::AssignToConst { .. } => "E0404",
+            Self::CyclicConstInit { .. } => "E0405",
+            Self::NonConstFnBody { .. } => "E0406",
+            Self::StaticAssertFailed { .. } => "E0407",
+            Self::DuplicateCaseLabel { .. } => "E0408",
+            Self::InvalidCfgArgument { .. } => "E0409",
+            Self::InvalidShiftCount { .. } => "E0410",
+            Self::BreakOutsideLoop { .. } => "E0501",
+            Self::ContinueOutsideLoop { .. } => "E0502",
+            Self::NotALValue { .. } => "E0503",
+            Self::AddressOfRight { .. } => "E0504",
+            Self::UnsupportedMainParams { .. } => "E0505",
+            Self::MissingReturn { .. } => "E0506",
+            Self::UnsupportedFeature { .. } => "E0507",
+            Self::UnconditionalRecursion { .. } => "E0508",
+            Self::ConstantCondition { .. } => "E0509",
+            Self::InvalidMainSignature { .. } => "E0510",
+            Self::ImportPathNotFound { .. } => "E0601",
+            Self::ImportSymbolNotFound { .. } => "E0602",
+            Self::ImportSymbolConflict { .. } => "E0603",
+        }
+    }
 }
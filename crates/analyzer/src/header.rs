@@ -92,11 +92,13 @@ impl HeaderAnalyzer {
 
     /// 将单个模块的导入信息应用到该模块（写入操作）
     pub fn apply_module_imports(module: &mut Module, module_imports: ModuleImports) {
-        module.semantic_errors.extend(module_imports.errors);
+        for e in module_imports.errors {
+            module.new_error(e);
+        }
 
         for (import_info, range) in module_imports.imports {
             if let Err(e) = Self::apply_imports_to_module(module, import_info, range) {
-                module.semantic_errors.push(e);
+                module.new_error(e);
             }
         }
     }
@@ -123,7 +125,9 @@ impl HeaderAnalyzer {
             })?;
         let header_path = PathBuf::from(unescape_path);
 
-        let symbol_name = path_node.symbol().map(|s| s.text().to_string());
+        let symbol_name = path_node
+            .symbol()
+            .map(|s| syntax::ast::strip_raw_prefix(s.text()).to_string());
 
         let mut target_path: PathBuf = current_dir.into();
         target_path.push(header_path);
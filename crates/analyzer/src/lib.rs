@@ -1,9 +1,12 @@
 pub mod analyze;
 pub mod array;
 pub mod checker;
+pub mod config;
+pub mod diagnostic;
 pub mod error;
 pub mod header;
 pub mod module;
+pub mod profile;
 pub mod project;
 pub mod r#type;
 pub(crate) mod utils;
@@ -1,5 +1,6 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
     ops::Deref,
     sync::Arc,
 };
@@ -7,12 +8,23 @@ use std::{
 use rowan::GreenNode;
 use syntax::SyntaxNode;
 use syntax::Visitor;
+use syntax::ast::{AstNode, Expr, OpNode};
 use thunderdome::Arena;
 use tools::TextRange;
 use utils::{define_id_type, define_module_id_type};
 use vfs::FileID;
 
-use crate::{array::ArrayTree, error::AnalyzeError, r#type::Ty, value::Value};
+use crate::{
+    array::ArrayTree,
+    config::AnalyzeConfig,
+    error::AnalyzeError,
+    profile::{AnalyzeProfile, ProfilePhase, Profiler},
+    r#type::Ty,
+    value::Value,
+};
+
+mod allow;
+mod compact;
 
 #[derive(Debug)]
 pub struct Module {
@@ -47,12 +59,18 @@ pub struct Module {
     /// Function 索引
     pub function_map: HashMap<String, FunctionID>,
 
+    /// 类型别名：Name -> 目标类型，None 表示别名无法解析（已经报过错）
+    pub type_aliases: HashMap<String, Option<Ty>>,
+
     /// 表达式类型表：TextRange -> NType
     pub type_table: HashMap<TextRange, Ty>,
 
     /// 错误
     pub semantic_errors: Vec<AnalyzeError>,
 
+    /// 分析中每产生一个错误就调用一次，错误仍会放进 `semantic_errors`
+    pub on_error: Option<ErrorCallback>,
+
     /// 分析上下文，使用后清除
     pub(crate) analyzing: AnalyzeContext,
 
@@ -61,6 +79,18 @@ pub struct Module {
 
     /// 用于跨文件分析
     pub metadata: Option<Arc<HashMap<FileID, ThinModule>>>,
+
+    /// 分析配置，所有模块共享
+    pub config: Arc<AnalyzeConfig>,
+}
+
+/// 错误回调，用于语言服务器等场景边分析边推送诊断
+pub struct ErrorCallback(pub Box<dyn FnMut(&AnalyzeError) + Send + Sync>);
+
+impl fmt::Debug for ErrorCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorCallback")
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -85,7 +115,26 @@ pub(crate) struct AnalyzeContext {
     pub(crate) current_scope: ScopeID,
     pub(crate) current_function_ret_type: Option<Ty>,
     pub(crate) loop_depth: usize,
+    /// 嵌套的 switch 层数，switch 内的 break 跳出 switch
+    pub(crate) switch_depth: usize,
     pub(crate) overflowing_literals: HashMap<TextRange, String>,
+    /// 初始化表达式存在循环依赖的全局常量，已报告过错误
+    pub(crate) cyclic_consts: HashSet<String>,
+    /// 带初始化表达式的全局常量，按名字索引
+    pub(crate) const_globals: HashMap<String, ConstGlobal>,
+    /// 已经分析过（或正在分析）的全局常量定义
+    pub(crate) analyzed_consts: HashSet<TextRange>,
+    /// 开启 `AnalyzeConfig::profile` 时的计时器
+    pub(crate) profiler: Option<Profiler>,
+}
+
+/// 全局常量的定义及其初始化表达式引用的名字
+#[derive(Debug)]
+pub(crate) struct ConstGlobal {
+    pub(crate) range: TextRange,
+    pub(crate) refs: Vec<String>,
+    /// 整个 VarDef 节点的范围，SyntaxNode 不能跨线程，需要时从语法树中重新查找
+    pub(crate) def_range: TextRange,
 }
 
 #[derive(Debug, Default)]
@@ -96,6 +145,25 @@ pub struct ModuleIndex {
     pub scope_tree: HashMap<ScopeID, Vec<ScopeID>>,
 }
 
+/// `Module::dump_types` 的一项
+#[derive(Debug)]
+pub struct TypeDumpEntry<'a> {
+    pub range: TextRange,
+    pub ty: &'a Ty,
+    pub value: Option<&'a Value>,
+}
+
+/// `Module::node_info` 的结果，汇总一个表达式节点的分析结果
+#[derive(Debug)]
+pub struct NodeInfo<'a> {
+    pub ty: Option<&'a Ty>,
+    /// 编译期常量值
+    pub value: Option<&'a Value>,
+    /// 节点引用的符号：变量、字段或函数
+    pub reference: Option<ReferenceTag>,
+    pub is_lvalue: bool,
+}
+
 #[derive(Debug)]
 pub struct CiterInfo {
     pub file_id: FileID,
@@ -126,36 +194,104 @@ impl Module {
             reference_map: Default::default(),
             struct_map: Default::default(),
             function_map: Default::default(),
+            type_aliases: Default::default(),
             type_table: Default::default(),
             semantic_errors: Default::default(),
+            on_error: None,
             analyzing: Default::default(),
             index: Default::default(),
             metadata: None,
+            config: Default::default(),
+        }
+    }
+
+    /// 复制符号表和分析结果，用于在不修改本模块的前提下继续分析别的节点
+    ///
+    /// 错误、错误回调、分析上下文和引用索引不复制。不用 `..` 补全字段，
+    /// 新增字段时必须在这里决定是否复制
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            file_id: self.file_id,
+            variables: self.variables.clone(),
+            reference: self.reference.clone(),
+            functions: self.functions.clone(),
+            structs: self.structs.clone(),
+            fields: self.fields.clone(),
+            scopes: self.scopes.clone(),
+            global_scope: self.global_scope,
+            green_tree: self.green_tree.clone(),
+            value_table: self.value_table.clone(),
+            expand_array: self.expand_array.clone(),
+            variable_map: self.variable_map.clone(),
+            reference_map: self.reference_map.clone(),
+            struct_map: self.struct_map.clone(),
+            function_map: self.function_map.clone(),
+            type_aliases: self.type_aliases.clone(),
+            type_table: self.type_table.clone(),
+            semantic_errors: Vec::new(),
+            on_error: None,
+            analyzing: Default::default(),
+            index: Default::default(),
+            metadata: self.metadata.clone(),
+            config: Arc::clone(&self.config),
         }
     }
+
     /// 分析
-    pub fn analyze(&mut self) {
+    /// 开启 `AnalyzeConfig::profile` 时返回各阶段的耗时
+    pub fn analyze(&mut self) -> Option<AnalyzeProfile> {
+        if self.config.profile {
+            self.analyzing.profiler = Some(Profiler::default());
+        }
         let root = SyntaxNode::new_root(self.green_tree.clone());
         self.walk(&root);
+        self.profile_start(ProfilePhase::Checks);
+        self.check_definite_init();
+        self.check_missing_return();
+        self.check_unconditional_recursion();
+        self.profile_stop();
 
         // 检查未处理的溢出字面量（没有被一元负号包裹的）
-        for (range, literal_text) in self.analyzing.overflowing_literals.drain() {
-            // 从字面量文本中提取类型
-            let ty_str = if literal_text.ends_with("i8") {
-                "i8"
-            } else {
-                "i32"
-            };
-
-            self.semantic_errors
-                .push(AnalyzeError::IntegerLiteralOverflow {
-                    literal: literal_text,
-                    ty: ty_str.to_string(),
-                    range,
-                });
+        for (range, literal_text) in std::mem::take(&mut self.analyzing.overflowing_literals) {
+            // 字面量的类型在 leave_literal 中按后缀确定
+            let ty = self
+                .get_expr_type(range)
+                .map_or_else(|| Ty::I32.to_string(), Ty::to_string);
+
+            self.new_error(AnalyzeError::IntegerLiteralOverflow {
+                literal: literal_text,
+                ty,
+                range,
+            });
         }
 
-        self.analyzing = AnalyzeContext::default();
+        self.apply_allow_directives();
+
+        std::mem::take(&mut self.analyzing)
+            .profiler
+            .map(Profiler::finish)
+    }
+
+    /// 本模块是否定义了带函数体的 main
+    pub fn has_main(&self) -> bool {
+        self.function_map.get("main").is_some_and(|id| {
+            id.module == self.file_id
+                && self
+                    .functions
+                    .get(id.index)
+                    .is_some_and(|f| f.have_local_impl)
+        })
+    }
+
+    /// 是否有错误级别的诊断；开启 `deny_warnings` 时警告和提示也算错误
+    pub fn has_errors(&self) -> bool {
+        self.semantic_errors.iter().any(|e| {
+            self.config.deny_warnings
+                || matches!(
+                    miette::Diagnostic::severity(e),
+                    None | Some(miette::Severity::Error)
+                )
+        })
     }
 
     /// 检查是否为编译时常量
@@ -167,6 +303,43 @@ impl Module {
         self.value_table.get(&range)
     }
 
+    /// 条件折叠成常量且为真，如 `while (1)`
+    pub(crate) fn is_const_true(&self, cond: &Expr) -> bool {
+        matches!(
+            self.get_value_by_range(cond.text_range())
+                .map(|v| v.cast_to_bool()),
+            Some(Ok(Value::Bool(true)))
+        )
+    }
+
+    /// `&g`（外层可以带括号）取的是本模块全局变量的地址时返回该变量
+    ///
+    /// 全局变量的地址在链接时确定，可以作为全局变量的常量初始值
+    pub fn global_address_of(&self, expr: &Expr) -> Option<&Variable> {
+        let mut expr = expr.clone();
+        while let Expr::ParenExpr(paren) = expr {
+            expr = paren.expr()?;
+        }
+        let Expr::UnaryExpr(unary) = expr else {
+            return None;
+        };
+        if unary.op()?.op().kind() != syntax::SyntaxKind::AMP {
+            return None;
+        }
+        let Some(Expr::IndexVal(index_val)) = unary.expr() else {
+            return None;
+        };
+        if index_val.indices().next().is_some() {
+            return None;
+        }
+        let (name, range) = utils::extract_name_and_range(&index_val.name()?)?;
+        let ReferenceTag::VarRead(id) = self.get_reference_by_range(range)?.tag else {
+            return None;
+        };
+        let global = self.scopes.get(*self.global_scope)?;
+        (global.variables.get(&name) == Some(&id)).then(|| self.variables.get(*id))?
+    }
+
     pub fn set_expr_type(&mut self, range: TextRange, ty: Ty) {
         self.type_table.insert(range, ty);
     }
@@ -175,6 +348,72 @@ impl Module {
         self.type_table.get(&range)
     }
 
+    /// 按源码位置列出所有表达式的类型以及折叠出的常量
+    ///
+    /// 类型表以节点范围为键（含前导空白），这里去掉首尾空白，
+    /// 去空白后范围相同的项（如 InitVal 与其中的表达式）只保留一个
+    pub fn dump_types(&self) -> Vec<TypeDumpEntry<'_>> {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let text = root.text().to_string();
+
+        let mut entries: Vec<_> = self
+            .type_table
+            .iter()
+            .map(|(range, ty)| {
+                let (start, end): (u32, u32) = (range.start().into(), range.end().into());
+                let slice = &text[start as usize..end as usize];
+                let leading = (slice.len() - slice.trim_start().len()) as u32;
+                let trailing = (slice.len() - slice.trim_end().len()) as u32;
+                let trimmed = if leading as usize == slice.len() {
+                    *range
+                } else {
+                    TextRange::new(start + leading, end - trailing)
+                };
+                TypeDumpEntry {
+                    range: trimmed,
+                    ty,
+                    value: self.value_table.get(range),
+                }
+            })
+            .collect();
+        entries.sort_by_key(|e| e.range);
+        entries.dedup_by_key(|e| e.range);
+        entries
+    }
+
+    /// 查询 `range` 处表达式节点的类型、常量值、引用的符号以及是否为左值
+    ///
+    /// `range` 是节点的 `text_range()`；名字的引用记录在名字范围上，
+    /// 对变量、字段访问和函数调用会取其中名字的引用
+    pub fn node_info(&self, range: TextRange) -> NodeInfo<'_> {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let expr = root
+            .covering_element(*range)
+            .ancestors()
+            .take_while(|n| n.text_range() == *range)
+            .find_map(Expr::cast);
+
+        let name_range = expr.as_ref().and_then(|expr| match expr {
+            Expr::IndexVal(v) => v.name()?.var_range(),
+            Expr::PostfixExpr(p) => p.field()?.name()?.var_range(),
+            Expr::CallExpr(c) => c.name()?.var_range(),
+            _ => None,
+        });
+        let reference = [Some(range), name_range]
+            .into_iter()
+            .flatten()
+            .find_map(|r| self.reference_map.get(&r))
+            .and_then(|id| self.get_reference_by_id(*id))
+            .map(|r| r.tag);
+
+        NodeInfo {
+            ty: self.get_expr_type(range),
+            value: self.value_table.get(&range),
+            reference,
+            is_lvalue: expr.is_some_and(|e| self.is_lvalue_expr(&e)),
+        }
+    }
+
     pub fn new_scope(&mut self, parent: Option<ScopeID>, range: TextRange) -> ScopeID {
         let scope = Scope {
             parent,
@@ -209,6 +448,10 @@ impl Module {
             have_local_impl: have_impl,
             is_variadic,
             range,
+            doc: None,
+            is_const: false,
+            is_extern: false,
+            restrict_params: Vec::new(),
         };
         let id = self.functions.insert(function);
         FunctionID::new(self.file_id, id)
@@ -300,6 +543,8 @@ impl Module {
             name,
             fields,
             range,
+            doc: None,
+            is_incomplete: false,
         };
         let id = self.structs.insert(struct_def);
         StructID::new(self.file_id, id)
@@ -332,11 +577,25 @@ impl Module {
 
     /// 记录引用
     pub fn new_reference(&mut self, range: TextRange, tag: ReferenceTag) {
+        self.profile_start(ProfilePhase::ReferenceRecording);
         let ref_var = Reference { range, tag };
         let ref_idx = self.reference.insert(ref_var);
         let ref_id = ReferenceID(ref_idx);
 
         self.reference_map.insert(range, ref_id);
+        self.profile_stop();
+    }
+
+    pub(crate) fn profile_start(&mut self, phase: ProfilePhase) {
+        if let Some(profiler) = &mut self.analyzing.profiler {
+            profiler.start(phase);
+        }
+    }
+
+    pub(crate) fn profile_stop(&mut self) {
+        if let Some(profiler) = &mut self.analyzing.profiler {
+            profiler.stop();
+        }
     }
 
     /// 查找变量定义，返回定义处的 VariableID
@@ -345,7 +604,15 @@ impl Module {
         scope.look_up_variable(self, var_name)
     }
 
+    /// 设置错误回调
+    pub fn set_on_error(&mut self, callback: impl FnMut(&AnalyzeError) + Send + Sync + 'static) {
+        self.on_error = Some(ErrorCallback(Box::new(callback)));
+    }
+
     pub(crate) fn new_error(&mut self, error: AnalyzeError) {
+        if let Some(ErrorCallback(callback)) = &mut self.on_error {
+            callback(&error);
+        }
         self.semantic_errors.push(error)
     }
 
@@ -373,6 +640,8 @@ pub struct Variable {
     pub name: String,
     pub ty: Ty,
     pub range: TextRange,
+    /// 全局变量前的文档注释
+    pub doc: Option<String>,
 }
 
 impl Variable {
@@ -403,6 +672,14 @@ pub struct Function {
     pub have_local_impl: bool,
     pub is_variadic: bool,
     pub range: TextRange,
+    /// 函数定义前的文档注释
+    pub doc: Option<String>,
+    /// `const fn`，常量参数的调用在分析阶段求值
+    pub is_const: bool,
+    /// `extern fn`，由外部（C 运行时）提供实现，按原名以外部链接声明
+    pub is_extern: bool,
+    /// `*restrict` 修饰的指针参数下标，codegen 降级为 noalias 属性
+    pub restrict_params: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -410,6 +687,10 @@ pub struct Struct {
     pub name: String,
     pub fields: Vec<FieldID>,
     pub range: TextRange,
+    /// struct 定义前的文档注释
+    pub doc: Option<String>,
+    /// 只有前向声明 `struct Name;`，没有定义
+    pub is_incomplete: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -452,7 +733,7 @@ impl Struct {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Scope {
     pub parent: Option<ScopeID>,
     pub variables: HashMap<String, VariableID>,
@@ -472,6 +753,7 @@ impl Scope {
             name: name.clone(),
             ty,
             range,
+            doc: None,
         });
         let var_id = VariableID(idx);
         self.variables.insert(name, var_id);
@@ -0,0 +1,107 @@
+//! `// nanoc:allow(name, ...)` 注释：抑制指定的警告和提示
+//!
+//! 与代码同一行的注释作用于所在行，单独成行的注释作用于下一行；
+//! name 是诊断代码去掉 `semantic::` 前缀的部分，如 `unused_variable`。错误不能被抑制。
+
+use std::collections::{HashMap, HashSet};
+
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use tools::LineIndex;
+
+use super::Module;
+
+const ALLOW_PREFIX: &str = "nanoc:allow(";
+
+impl Module {
+    /// 去掉被 allow 注释抑制的警告和提示
+    pub(crate) fn apply_allow_directives(&mut self) {
+        let root = SyntaxNode::new_root(self.green_tree.clone());
+        let text = root.text().to_string();
+        if !text.contains(ALLOW_PREFIX) {
+            return;
+        }
+        let line_index = LineIndex::from_text(&text);
+        let allowed = collect_allow_directives(&root, &line_index);
+        if allowed.is_empty() {
+            return;
+        }
+
+        self.semantic_errors.retain(|e| {
+            if matches!(
+                miette::Diagnostic::severity(e),
+                None | Some(miette::Severity::Error)
+            ) {
+                return true;
+            }
+            let Some(code) = miette::Diagnostic::code(e).map(|c| c.to_string()) else {
+                return true;
+            };
+            let name = code.rsplit("::").next().unwrap_or(&code);
+            let (row, _) = line_index.get_row_column(e.range().start().into());
+            !allowed
+                .get(&row)
+                .is_some_and(|names| names.iter().any(|n| n == name))
+        });
+    }
+}
+
+/// 行号 -> 该行允许的诊断名
+fn collect_allow_directives(
+    root: &SyntaxNode,
+    line_index: &LineIndex,
+) -> HashMap<u32, Vec<String>> {
+    let row_of = |token: &SyntaxToken| {
+        line_index
+            .get_row_column(token.text_range().start().into())
+            .0
+    };
+    let tokens: Vec<_> = root
+        .descendants_with_tokens()
+        .filter_map(|e| e.into_token())
+        .collect();
+    // 含有代码（非空白、非注释 token）的行
+    let code_rows: HashSet<_> = tokens
+        .iter()
+        .filter(|t| !t.kind().is_trivia())
+        .map(row_of)
+        .collect();
+
+    let mut allowed: HashMap<u32, Vec<String>> = HashMap::new();
+    for token in &tokens {
+        if !matches!(
+            token.kind(),
+            SyntaxKind::COMMENT_LINE | SyntaxKind::COMMENT_BLOCK
+        ) {
+            continue;
+        }
+        let names = parse_allow(token.text());
+        if names.is_empty() {
+            continue;
+        }
+        let row = row_of(token);
+        let target = if code_rows.contains(&row) {
+            row
+        } else {
+            row + 1
+        };
+        allowed.entry(target).or_default().extend(names);
+    }
+    allowed
+}
+
+/// 解析注释中的 `nanoc:allow(a, b)`，返回 `["a", "b"]`
+fn parse_allow(comment: &str) -> Vec<String> {
+    let Some(start) = comment.find(ALLOW_PREFIX) else {
+        return Vec::new();
+    };
+    let rest = &comment[start + ALLOW_PREFIX.len()..];
+    let Some(end) = rest.find(')') else {
+        return Vec::new();
+    };
+    rest[..end]
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
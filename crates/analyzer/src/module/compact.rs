@@ -0,0 +1,146 @@
+//! 符号表压缩：重建 variable/function/struct 的 arena，回收已释放的槽位
+
+use std::collections::HashMap;
+
+use thunderdome::{Arena, Index};
+
+use super::{FunctionID, Module, ReferenceTag, StructID, VariableID};
+use crate::{
+    array::{ArrayTree, ArrayTreeValue},
+    r#type::Ty,
+    value::Value,
+};
+
+/// 旧 Index -> 新 Index
+type Remap = HashMap<Index, Index>;
+
+impl Module {
+    /// 按插入顺序紧凑地重建变量、函数和 struct 的 arena，并更新所有保存的 id
+    ///
+    /// 只重映射本模块的 id；其他模块保存的本模块 FunctionID/StructID 会失效，
+    /// 压缩后需要重新生成 `ThinModule` 元数据
+    pub fn compact(&mut self) {
+        let variables = compact_arena(&mut self.variables);
+        let functions = compact_arena(&mut self.functions);
+        let structs = compact_arena(&mut self.structs);
+
+        let file_id = self.file_id;
+        let var_id = |id: &mut VariableID| {
+            if let Some(new) = variables.get(&id.0) {
+                id.0 = *new;
+            }
+        };
+        let func_id = |id: &mut FunctionID| {
+            if id.module == file_id
+                && let Some(new) = functions.get(&id.index)
+            {
+                id.index = *new;
+            }
+        };
+        let struct_id = |id: &mut StructID| {
+            if id.module == file_id
+                && let Some(new) = structs.get(&id.index)
+            {
+                id.index = *new;
+            }
+        };
+
+        // 变量
+        self.variable_map.values_mut().for_each(var_id);
+        for (_, scope) in self.scopes.iter_mut() {
+            scope.variables.values_mut().for_each(var_id);
+        }
+        self.index.variable_reference = std::mem::take(&mut self.index.variable_reference)
+            .into_iter()
+            .map(|(mut id, citers)| {
+                var_id(&mut id);
+                (id, citers)
+            })
+            .collect();
+
+        // 函数
+        self.function_map.values_mut().for_each(func_id);
+        self.index.function_reference = std::mem::take(&mut self.index.function_reference)
+            .into_iter()
+            .map(|(mut id, citers)| {
+                func_id(&mut id);
+                (id, citers)
+            })
+            .collect();
+        for (_, function) in self.functions.iter_mut() {
+            function.params.iter_mut().for_each(var_id);
+            function
+                .meta_types
+                .iter_mut()
+                .for_each(|(_, ty)| remap_ty(ty, &struct_id));
+            remap_ty(&mut function.ret_type, &struct_id);
+        }
+
+        for (_, reference) in self.reference.iter_mut() {
+            match &mut reference.tag {
+                ReferenceTag::VarRead(id) => var_id(id),
+                ReferenceTag::FuncCall(id) => func_id(id),
+                ReferenceTag::FieldRead(_) => {}
+            }
+        }
+
+        // struct 以及所有保存了 StructID 的类型和常量
+        self.struct_map.values_mut().for_each(struct_id);
+        for (_, variable) in self.variables.iter_mut() {
+            remap_ty(&mut variable.ty, &struct_id);
+        }
+        for (_, field) in self.fields.iter_mut() {
+            remap_ty(&mut field.ty, &struct_id);
+        }
+        for ty in self.type_table.values_mut() {
+            remap_ty(ty, &struct_id);
+        }
+        for ty in self.type_aliases.values_mut().flatten() {
+            remap_ty(ty, &struct_id);
+        }
+        for value in self.value_table.values_mut() {
+            remap_value(value, &struct_id);
+        }
+        for tree in self.expand_array.values_mut() {
+            remap_array_tree(tree, &struct_id);
+        }
+    }
+}
+
+/// 重建 arena，容量等于元素个数，返回 id 映射
+fn compact_arena<T>(arena: &mut Arena<T>) -> Remap {
+    let old = std::mem::replace(arena, Arena::with_capacity(arena.len()));
+    old.into_iter()
+        .map(|(index, value)| (index, arena.insert(value)))
+        .collect()
+}
+
+fn remap_ty(ty: &mut Ty, f: &impl Fn(&mut StructID)) {
+    match ty {
+        Ty::Struct { id, .. } => f(id),
+        Ty::Array(inner, _) | Ty::Pointer { pointee: inner, .. } | Ty::Const(inner) => {
+            remap_ty(inner, f)
+        }
+        _ => {}
+    }
+}
+
+fn remap_value(value: &mut Value, f: &impl Fn(&mut StructID)) {
+    match value {
+        Value::Struct(id, fields) => {
+            f(id);
+            fields.iter_mut().for_each(|v| remap_value(v, f));
+        }
+        Value::StructZero(id) => f(id),
+        Value::Array(tree) => remap_array_tree(tree, f),
+        _ => {}
+    }
+}
+
+fn remap_array_tree(tree: &mut ArrayTree, f: &impl Fn(&mut StructID)) {
+    match tree {
+        ArrayTree::Children(children) => children.iter_mut().for_each(|c| remap_array_tree(c, f)),
+        ArrayTree::Val(ArrayTreeValue::Struct { struct_id, .. }) => f(struct_id),
+        ArrayTree::Val(_) => {}
+    }
+}
@@ -0,0 +1,125 @@
+//! 分析耗时统计，`AnalyzeConfig::profile` 开启时由 `Module::analyze` 返回
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use syntax::SyntaxKind;
+
+/// 计时的分析阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProfilePhase {
+    /// 声明：变量、函数、struct、enum 等
+    Declaration,
+    /// 语句
+    Statement,
+    /// 表达式和类型节点的类型推导
+    TypeInference,
+    /// 常量折叠和 const fn 求值
+    ConstFolding,
+    /// 记录符号引用
+    ReferenceRecording,
+    /// 遍历之后的检查：definite init、missing return 等
+    Checks,
+}
+
+impl ProfilePhase {
+    pub const ALL: [ProfilePhase; 6] = [
+        ProfilePhase::Declaration,
+        ProfilePhase::Statement,
+        ProfilePhase::TypeInference,
+        ProfilePhase::ConstFolding,
+        ProfilePhase::ReferenceRecording,
+        ProfilePhase::Checks,
+    ];
+
+    /// 访问该节点的 enter/leave 方法属于哪个阶段
+    pub fn of_node(kind: SyntaxKind) -> Self {
+        use SyntaxKind::*;
+        match kind {
+            BINARY_EXPR | UNARY_EXPR | POSTFIX_EXPR | CALL_EXPR | PAREN_EXPR | INDEX_VAL
+            | FIELD_ACCESS | ENUM_VARIANT_EXPR | LITERAL | TYPE | PRIMIT_TYPE | POINTER => {
+                ProfilePhase::TypeInference
+            }
+            BLOCK | IF_STMT | WHILE_STMT | FOR_STMT | FOR_INIT | FOR_STEP | ASSIGN_STMT
+            | EXPR_STMT | BREAK_STMT | CONTINUE_STMT | GOTO_STMT | LABEL_STMT | RETURN_STMT
+            | SWITCH_STMT | SWITCH_CASE => ProfilePhase::Statement,
+            _ => ProfilePhase::Declaration,
+        }
+    }
+}
+
+/// 单个阶段的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStat {
+    /// 进入该阶段的次数
+    pub count: usize,
+    /// 不含嵌套的其他阶段的耗时
+    pub time: Duration,
+}
+
+/// 各阶段耗时汇总
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzeProfile {
+    stats: [PhaseStat; ProfilePhase::ALL.len()],
+}
+
+impl AnalyzeProfile {
+    pub fn get(&self, phase: ProfilePhase) -> PhaseStat {
+        self.stats[phase as usize]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ProfilePhase, PhaseStat)> + '_ {
+        ProfilePhase::ALL.into_iter().map(|p| (p, self.get(p)))
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stats.iter().map(|s| s.time).sum()
+    }
+}
+
+impl fmt::Display for AnalyzeProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (phase, stat) in self.iter() {
+            writeln!(
+                f,
+                "{:<20} {:>8} {:>12.3?}",
+                format!("{phase:?}"),
+                stat.count,
+                stat.time
+            )?;
+        }
+        write!(f, "{:<20} {:>8} {:>12.3?}", "Total", "", self.total())
+    }
+}
+
+/// 按阶段计时，阶段可以嵌套，嵌套期间外层阶段暂停计时
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    profile: AnalyzeProfile,
+    stack: Vec<(ProfilePhase, Instant)>,
+}
+
+impl Profiler {
+    pub(crate) fn start(&mut self, phase: ProfilePhase) {
+        let now = Instant::now();
+        if let Some((outer, since)) = self.stack.last() {
+            self.profile.stats[*outer as usize].time += now - *since;
+        }
+        self.profile.stats[phase as usize].count += 1;
+        self.stack.push((phase, now));
+    }
+
+    pub(crate) fn stop(&mut self) {
+        let now = Instant::now();
+        if let Some((phase, since)) = self.stack.pop() {
+            self.profile.stats[phase as usize].time += now - since;
+        }
+        if let Some((_, since)) = self.stack.last_mut() {
+            *since = now;
+        }
+    }
+
+    pub(crate) fn finish(self) -> AnalyzeProfile {
+        self.profile
+    }
+}
@@ -1,29 +1,74 @@
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 
 use parser::parse::Parser;
 use rayon::prelude::*;
 use syntax::{
     AstNode as _, SyntaxNode,
-    ast::{FuncDef, StructDef},
+    ast::{FuncDef, PrimitType, StructDef, TypeAlias},
 };
+use tools::TextRange;
 use utils::extract_name_and_range;
 use vfs::{FileID, Vfs};
 
 use crate::{
     checker::ProjectChecker,
+    config::AnalyzeConfig,
+    diagnostic::Diagnostic,
+    error::AnalyzeError,
     header::HeaderAnalyzer,
-    module::{CiterInfo, Field, FieldID, Module, ModuleIndex, ThinModule},
+    module::{CiterInfo, Field, FieldID, FunctionID, Module, ModuleIndex, StructID, ThinModule},
     r#type::Ty,
 };
 
+/// 单个文件的分析统计
+#[derive(Debug, Clone)]
+pub struct FileStats {
+    pub file_id: FileID,
+    /// 语法树节点数量
+    pub node_count: usize,
+    /// 错误数量（含语法错误）
+    pub error_count: usize,
+    /// 解析、定义收集和语义分析的耗时，不含跨文件的导入、索引和检查器
+    pub elapsed: Duration,
+}
+
+/// 批量分析的统计结果，用于基准测试
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisStats {
+    pub files: Vec<FileStats>,
+    /// 分析总耗时
+    pub elapsed: Duration,
+}
+
+impl AnalysisStats {
+    /// 所有文件的节点总数
+    pub fn total_node_count(&self) -> usize {
+        self.files.iter().map(|f| f.node_count).sum()
+    }
+
+    /// 所有文件的错误总数
+    pub fn total_error_count(&self) -> usize {
+        self.files.iter().map(|f| f.error_count).sum()
+    }
+}
+
+/// 跨模块解析得到的符号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolRef {
+    Function(FunctionID),
+    Struct(StructID),
+}
+
 #[derive(Default, Debug)]
 pub struct Project {
     pub modules: HashMap<FileID, Module>,
     pub metadata: Arc<HashMap<FileID, ThinModule>>,
     pub(crate) checker: Vec<Box<dyn ProjectChecker>>,
+    pub config: Arc<AnalyzeConfig>,
 }
 
 impl Project {
@@ -31,35 +76,94 @@ impl Project {
         Self::default()
     }
 
+    pub fn with_config(mut self, config: AnalyzeConfig) -> Self {
+        self.config = Arc::new(config);
+        self
+    }
+
     pub fn with_checker<T: ProjectChecker + Default + 'static>(mut self) -> Self {
         self.checker.push(Box::new(T::default()));
         self
     }
 
+    /// 获取文件对应的模块
+    pub fn module_of(&self, file_id: FileID) -> Option<&Module> {
+        self.modules.get(&file_id)
+    }
+
+    /// 文件的所有诊断（词法、语法和语义错误），按位置排序
+    ///
+    /// 词法和语法错误在解析时已经放进模块的 `semantic_errors`，这里统一转换。
+    /// 开启 `deny_warnings` 时所有诊断都按错误报告
+    pub fn diagnostics(&self, file_id: FileID) -> Vec<Diagnostic> {
+        let Some(module) = self.module_of(file_id) else {
+            return Vec::new();
+        };
+        let mut diagnostics: Vec<_> = module
+            .semantic_errors
+            .iter()
+            .map(Diagnostic::from)
+            .map(|mut d| {
+                if self.config.deny_warnings {
+                    d.severity = miette::Severity::Error;
+                }
+                d
+            })
+            .collect();
+        diagnostics.sort_by_key(|d| d.range);
+        diagnostics
+    }
+
+    /// 在 `importing` 模块中按名字解析函数或结构体，返回定义该符号的模块和符号
+    ///
+    /// 导入的符号在分析头文件时已经并入模块的符号表，ID 中记录了定义所在的模块
+    pub fn resolve_cross_module(
+        &self,
+        importing: FileID,
+        name: &str,
+    ) -> Option<(FileID, SymbolRef)> {
+        let module = self.module_of(importing)?;
+        if let Some(&func_id) = module.function_map.get(name) {
+            return Some((func_id.module, SymbolRef::Function(func_id)));
+        }
+        let &struct_id = module.struct_map.get(name)?;
+        Some((struct_id.module, SymbolRef::Struct(struct_id)))
+    }
+
     /// 全量初始化
     pub fn full_initialize(&mut self, vfs: &Vfs) {
+        self.initialize(vfs);
+    }
+
+    /// `full_initialize` 的实现，返回每个文件在解析、定义收集和语义分析中的耗时
+    fn initialize(&mut self, vfs: &Vfs) -> HashMap<FileID, Duration> {
         self.modules.clear();
         self.metadata = Default::default();
 
         // 初始化所有 module，语法分析
         let file_ids = vfs.file_ids();
         let modules = RwLock::new(HashMap::new());
+        let durations: RwLock<HashMap<FileID, Duration>> = RwLock::new(HashMap::new());
+        let record = |file_id: FileID, start: Instant| {
+            *durations.write().unwrap().entry(file_id).or_default() += start.elapsed();
+        };
 
         file_ids.par_iter().for_each(|&file_id| {
             if let Some(file) = vfs.get_file_by_file_id(&file_id) {
+                let start = Instant::now();
                 let parser = Parser::new(&file.text);
                 let (green_tree, errors) = parser.parse();
 
                 let mut module = Module::new(green_tree.clone());
                 module.file_id = file_id;
+                module.config = Arc::clone(&self.config);
                 errors.into_iter().for_each(|e| {
-                    module
-                        .semantic_errors
-                        .push(crate::error::AnalyzeError::ParserError(Box::new(e)))
+                    module.new_error(crate::error::AnalyzeError::ParserError(Box::new(e)))
                 });
 
                 // 收集符号并分配 ID
                 Self::allocate_module_symbols(&mut module);
+                record(file_id, start);
 
                 modules.write().unwrap().insert(file_id, module);
             }
@@ -84,8 +188,10 @@ impl Project {
         }
 
         // 预处理元数据，跨文件使用
-        self.modules.par_iter_mut().for_each(|(_, module)| {
+        self.modules.par_iter_mut().for_each(|(file_id, module)| {
+            let start = Instant::now();
             Self::fill_definitions(module);
+            record(*file_id, start);
         });
 
         let metadata: HashMap<FileID, ThinModule> = self
@@ -96,10 +202,12 @@ impl Project {
 
         // 语义分析
         let metadata_rc = Arc::new(metadata);
-        self.modules.par_iter_mut().for_each(|(_, module)| {
+        self.modules.par_iter_mut().for_each(|(file_id, module)| {
+            let start = Instant::now();
             module.metadata = Some(Arc::clone(&metadata_rc));
             module.analyze();
             module.metadata = None;
+            record(*file_id, start);
         });
 
         // 重新拷贝分析完成的元数据
@@ -198,15 +306,56 @@ impl Project {
             let result = check.check_project(&self.modules);
             for (file_id, errors) in result {
                 if let Some(module) = self.modules.get_mut(&file_id) {
-                    module.semantic_errors.extend(errors);
+                    errors.into_iter().for_each(|e| module.new_error(e));
                 }
             }
         }
+        // 可执行文件需要入口，提示放在第一个文件上
+        if self.config.executable
+            && !self.modules.values().any(Module::has_main)
+            && let Some(module) = self.modules.values_mut().min_by_key(|m| m.file_id.slot())
+        {
+            module.new_error(AnalyzeError::MissingMain {
+                range: TextRange::default(),
+            });
+        }
+        // checker 的诊断也可以被 `nanoc:allow` 注释抑制
+        if !self.checker.is_empty() {
+            self.modules
+                .par_iter_mut()
+                .for_each(|(_, module)| module.apply_allow_directives());
+        }
+
+        durations.into_inner().unwrap()
+    }
+
+    /// 分析 Vfs 中的所有文件并返回统计信息
+    pub fn analyze_all(&mut self, vfs: &Vfs) -> AnalysisStats {
+        let start = Instant::now();
+        let mut durations = self.initialize(vfs);
+        let elapsed = start.elapsed();
+
+        let mut files: Vec<_> = self
+            .modules
+            .iter()
+            .map(|(file_id, module)| FileStats {
+                file_id: *file_id,
+                node_count: SyntaxNode::new_root(module.green_tree.clone())
+                    .descendants()
+                    .count(),
+                error_count: module.semantic_errors.len(),
+                elapsed: durations.remove(file_id).unwrap_or_default(),
+            })
+            .collect();
+        files.sort_by_key(|f| f.file_id.slot());
+
+        AnalysisStats { files, elapsed }
     }
 
     /// 为模块收集符号并分配 ID
     pub fn allocate_module_symbols(module: &mut Module) {
         let root = SyntaxNode::new_root(module.green_tree.clone());
+        let mut forward_decls = Vec::new();
         for ele in root.children() {
             if let Some(func_def) = FuncDef::cast(ele.clone()) {
                 if let Some((name, range)) = func_def
@@ -229,6 +378,10 @@ impl Project {
                         false,
                         range,
                     );
+                    if let Some(function) = module.get_function_mut_by_id(func_id) {
+                        function.is_const = func_def.is_const();
+                        function.is_extern = func_def.is_extern();
+                    }
                     module.function_map.insert(name, func_id);
                 }
             } else if let Some(struct_def) = StructDef::cast(ele)
@@ -236,6 +389,11 @@ impl Project {
                     .name()
                     .and_then(|n| utils::extract_name_and_range(&n))
             {
+                // 前向声明在所有定义之后处理，只为没有定义的名字创建 struct
+                if struct_def.is_forward_decl() {
+                    forward_decls.push((name, range));
+                    continue;
+                }
                 if module.struct_map.contains_key(&name) {
                     module.new_error(crate::error::AnalyzeError::StructDefined { name, range });
                     continue;
@@ -244,17 +402,34 @@ impl Project {
                 module.struct_map.insert(name, struct_id);
             }
         }
+
+        // 只有前向声明的 struct 是不完整类型，只能通过指针使用
+        for (name, range) in forward_decls {
+            if !module.struct_map.contains_key(&name) {
+                let struct_id = module.new_struct(name.clone(), vec![], range);
+                if let Some(struct_data) = module.get_struct_mut_by_id(struct_id) {
+                    struct_data.is_incomplete = true;
+                }
+                module.struct_map.insert(name, struct_id);
+            }
+        }
     }
 
     /// 填充模块的 struct 和 function 定义
+    /// TypeAlias: 目标类型
     /// Struct: 字段
     /// Function: 返回类型
     pub fn fill_definitions(module: &mut Module) {
         let root = SyntaxNode::new_root(module.green_tree.clone());
 
+        Self::resolve_type_aliases(module);
+
         let struct_defs: Vec<_> = root.children().filter_map(StructDef::cast).collect();
 
         for struct_def in struct_defs {
+            if struct_def.is_forward_decl() {
+                continue;
+            }
             if let Some(name) = struct_def.name().and_then(|n| n.var_name()) {
                 let Some(&struct_id) = module.struct_map.get(&name) else {
                     continue;
@@ -280,7 +455,7 @@ impl Project {
                             }
                             Ok(None) => {}
                             Err(e) => {
-                                module.semantic_errors.push(e);
+                                module.new_error(e);
                             }
                         }
                     }
@@ -323,7 +498,7 @@ impl Project {
                                 }
                                 Ok(None) => continue,
                                 Err(e) => {
-                                    module.semantic_errors.push(e);
+                                    module.new_error(e);
                                     continue;
                                 }
                             }
@@ -339,7 +514,7 @@ impl Project {
                             continue;
                         }
                         Err(e) => {
-                            module.semantic_errors.push(e);
+                            module.new_error(e);
                             Ty::Void
                         }
                     }
@@ -356,4 +531,90 @@ impl Project {
             }
         }
     }
+
+    /// 解析模块中的类型别名，别名之间可以互相引用，与定义顺序无关
+    ///
+    /// 由 `fill_definitions` 调用，单独分析一个模块时需要在 `Module::analyze` 之前调用
+    pub fn resolve_type_aliases(module: &mut Module) {
+        let root = SyntaxNode::new_root(module.green_tree.clone());
+        let mut aliases = HashMap::new();
+        let mut names = Vec::new();
+        for alias in root.children().filter_map(TypeAlias::cast) {
+            let Some((name, range)) = alias.name().and_then(|n| extract_name_and_range(&n)) else {
+                continue;
+            };
+            if aliases.contains_key(&name) {
+                module.new_error(AnalyzeError::TypeAliasDefined { name, range });
+                continue;
+            }
+            aliases.insert(name.clone(), (alias, range));
+            names.push(name);
+        }
+
+        let mut visiting = Vec::new();
+        for name in names {
+            Self::resolve_type_alias(module, &aliases, &name, &mut visiting);
+        }
+    }
+
+    /// 先解析别名引用的其他别名，再解析别名本身
+    ///
+    /// `visiting` 是正在解析的别名链，再次遇到链上的别名说明出现了循环
+    fn resolve_type_alias(
+        module: &mut Module,
+        aliases: &HashMap<String, (TypeAlias, TextRange)>,
+        name: &str,
+        visiting: &mut Vec<String>,
+    ) {
+        if module.type_aliases.contains_key(name) {
+            return;
+        }
+        let Some((alias, range)) = aliases.get(name) else {
+            return;
+        };
+        if let Some(pos) = visiting.iter().position(|n| n == name) {
+            let mut cycle = visiting[pos..].to_vec();
+            cycle.push(name.to_string());
+            module.new_error(AnalyzeError::CyclicTypeAlias {
+                name: name.to_string(),
+                cycle,
+                range: *range,
+            });
+            // 环上的别名都无法解析，后续使用时不再报错
+            for n in &visiting[pos..] {
+                module.type_aliases.insert(n.clone(), None);
+            }
+            return;
+        }
+        let Some(ty_node) = alias.ty() else {
+            module.type_aliases.insert(name.to_string(), None);
+            return;
+        };
+
+        visiting.push(name.to_string());
+        let deps: Vec<_> = ty_node
+            .syntax()
+            .descendants()
+            .filter_map(PrimitType::cast)
+            .filter(|t| t.struct_token().is_none())
+            .filter_map(|t| t.name().and_then(|n| n.var_name()))
+            .collect();
+        for dep in deps {
+            Self::resolve_type_alias(module, aliases, &dep, visiting);
+        }
+        visiting.pop();
+
+        // 在循环中时已经被标记为无法解析
+        if module.type_aliases.contains_key(name) {
+            return;
+        }
+        let ty = match crate::utils::parse_type_node(module, &ty_node, None) {
+            Ok(ty) => ty,
+            Err(e) => {
+                module.new_error(e);
+                None
+            }
+        };
+        module.type_aliases.insert(name.to_string(), ty);
+    }
 }
@@ -4,11 +4,20 @@ use std::path::PathBuf;
 use parser::parse::Parser;
 use vfs::Vfs;
 
+use crate::array::ArrayTree;
+use crate::checker::{UnusedFunctionChecker, UnusedVariableChecker};
+use crate::config::AnalyzeConfig;
 use crate::error::AnalyzeError;
-use crate::module::Module;
-use crate::project::Project;
+use crate::module::{Module, ReferenceTag, VariableID};
+use crate::project::{Project, SymbolRef};
+use crate::r#type::Ty;
+use crate::value::Value;
 
 pub(crate) fn analyze(source: &str) -> Module {
+    analyze_with_config(source, AnalyzeConfig::default())
+}
+
+fn analyze_with_config(source: &str, config: AnalyzeConfig) -> Module {
     let parser = Parser::new(source);
     let (tree, errors) = parser.parse();
 
@@ -22,8 +31,10 @@ pub(crate) fn analyze(source: &str) -> Module {
 
     let mut module = Module::new(tree);
     module.file_id = file_id;
+    module.config = std::sync::Arc::new(config);
 
     Project::allocate_module_symbols(&mut module);
+    Project::resolve_type_aliases(&mut module);
 
     module.analyze();
 
@@ -157,6 +168,7 @@ fn test_function_definition() {
     let source = r#"
     fn add(a: i32, b: i32) -> i32 {
         let result: i32;
+        return a + b;
     }
 
     fn main() -> i32 {
@@ -172,6 +184,7 @@ fn test_function_parameters() {
     let source = r#"
     fn sum(a: i32, b: i32, c: i32) -> i32 {
         let x: i32;
+        return a + b + c;
     }
     "#;
     let module = analyze(source);
@@ -423,10 +436,12 @@ fn test_multiple_functions() {
 
     fn func2(x: i32) -> i32 {
         let b: i32;
+        return x;
     }
 
     fn func3(a: i32, b: i32) -> i32 {
         let c: i32;
+        return a;
     }
     "#;
     let module = analyze(source);
@@ -567,6 +582,33 @@ fn test_function_undefined_error() {
     }
 }
 
+#[test]
+fn test_not_callable_error() {
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        x();
+        missing();
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert_eq!(module.semantic_errors.len(), 2);
+    match &module.semantic_errors[0] {
+        AnalyzeError::NotCallable { name, ty, .. } => {
+            assert_eq!(name, "x");
+            assert_eq!(ty.to_string(), "i32");
+        }
+        e => panic!("Expected NotCallable error, got {e:?}"),
+    }
+    match &module.semantic_errors[1] {
+        AnalyzeError::FunctionUndefined { name, .. } => {
+            assert_eq!(name, "missing");
+        }
+        e => panic!("Expected FunctionUndefined error, got {e:?}"),
+    }
+}
+
 // #[test]
 // fn test_function_argument_count_mismatch() {
 //     let source = r#"
@@ -857,14 +899,25 @@ fn test_variadic_function_too_few_args() {
     "#;
     let module = analyze(source);
     assert_eq!(module.semantic_errors.len(), 1);
-    assert!(matches!(
-        &module.semantic_errors[0],
-        AnalyzeError::ArgumentCountMismatch {
-            expected: 1,
-            found: 0,
-            ..
-        }
-    ));
+    let error = &module.semantic_errors[0];
+    let AnalyzeError::ArgumentCountMismatch {
+        expected: 1,
+        at_least: true,
+        found: 0,
+        range,
+        ..
+    } = error
+    else {
+        panic!("{error:?}");
+    };
+    assert!(
+        error.to_string().ends_with("expected at least 1, found 0"),
+        "{error}"
+    );
+    // 报告在空的参数列表上
+    let call = source.find("printf()").unwrap();
+    assert_eq!(usize::from(range.start()), call + "printf".len());
+    assert_eq!(usize::from(range.end()), call + "printf()".len());
 }
 
 #[test]
@@ -1239,6 +1292,78 @@ fn test_const_arithmetic_overflow_i64() {
     assert!(!module.semantic_errors.is_empty());
 }
 
+#[test]
+fn test_const_negation_overflow() {
+    // 字面量 -128i8 是合法的最小值
+    let module = analyze("let a: const i8 = -128i8;\nlet b: const i32 = -2147483648;");
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("a"), Some(Value::I8(i8::MIN)));
+    assert_eq!(value_of("b"), Some(Value::I32(i32::MIN)));
+
+    // 对计算出的最小值取负会溢出
+    for source in [
+        "let a: const i8 = -(-128i8);",
+        "let m: const i8 = -128i8;\nlet a: const i8 = -m;",
+        "let a: const u32 = -1u32;",
+    ] {
+        let module = analyze(source);
+        assert!(
+            matches!(
+                module.semantic_errors.as_slice(),
+                [AnalyzeError::ConstArithmeticOverflow { .. }]
+            ),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+    }
+}
+
+#[test]
+fn test_signed_min_literal() {
+    let cases = [
+        ("i8", "128i8", Value::I8(i8::MIN)),
+        ("i32", "2147483648", Value::I32(i32::MIN)),
+        ("i64", "9223372036854775808i64", Value::I64(i64::MIN)),
+    ];
+    for (ty, literal, min) in cases {
+        // 取负后是最小值，不报溢出
+        let source = format!("let a: const {ty} = -{literal};");
+        let module = analyze(&source);
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+        let (_, var) = module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == "a")
+            .unwrap();
+        assert_eq!(module.value_table.get(&var.range), Some(&min), "{source}");
+
+        // 不带负号时字面量溢出
+        let source = format!("let a: const {ty} = {literal};");
+        let module = analyze(&source);
+        let [
+            AnalyzeError::IntegerLiteralOverflow {
+                ty: overflow_ty, ..
+            },
+        ] = module.semantic_errors.as_slice()
+        else {
+            panic!("{source}: {:?}", module.semantic_errors);
+        };
+        assert_eq!(overflow_ty, ty, "{source}");
+    }
+}
+
 #[test]
 fn test_array_with_new_types() {
     let source = r#"
@@ -1279,3 +1404,3309 @@ fn test_function_params_new_types() {
     let module = analyze(source);
     assert!(module.semantic_errors.is_empty());
 }
+
+#[test]
+fn test_binary_op_unsupported_widths() {
+    let cases = [
+        ("i8", "u64", "+"),
+        ("u8", "bool", "+"),
+        ("i32", "u32", "-"),
+        ("u64", "i64", "*"),
+        ("i64", "u8", "<"),
+        ("u32", "i8", "=="),
+    ];
+    for (lhs, rhs, op) in cases {
+        let source =
+            format!("fn f(a: {lhs}, b: {rhs}) {{ let c: i32 = 0; if (a {op} b) {{ c = 1; }} }}");
+        let module = analyze(&source);
+        assert_eq!(module.semantic_errors.len(), 1, "{lhs} {op} {rhs}");
+        match &module.semantic_errors[0] {
+            AnalyzeError::BinaryOpTypeMismatch { op: found, .. } => assert_eq!(found, op),
+            e => panic!("Expected BinaryOpTypeMismatch for {lhs} {op} {rhs}, found {e:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_binary_op_supported_widths() {
+    let source = r#"
+    fn f(a: i8, b: i64, c: u8, d: u64, e: bool) -> i64 {
+        let x: i64 = a + b;
+        let y: u64 = c * d;
+        let z: i8 = a - e;
+        if (a < b && c != d) {
+            return x;
+        }
+        return b / a;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_cyclic_const_init_self_reference() {
+    let source = r#"
+    let x: const i32 = x;
+    "#;
+    let module = analyze(source);
+    assert_eq!(module.semantic_errors.len(), 1);
+    match &module.semantic_errors[0] {
+        AnalyzeError::CyclicConstInit { name, .. } => assert_eq!(name, "x"),
+        e => panic!("Expected CyclicConstInit error, found {e:?}"),
+    }
+}
+
+#[test]
+fn test_cyclic_const_init_two_globals() {
+    let source = r#"
+    let a: const i32 = b + 1;
+    let b: const i32 = a * 2;
+    let c: const i32 = 3;
+    "#;
+    let module = analyze(source);
+    let names: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| match e {
+            AnalyzeError::CyclicConstInit { name, .. } => name.as_str(),
+            e => panic!("Expected CyclicConstInit error, found {e:?}"),
+        })
+        .collect();
+    assert_eq!(names, vec!["a", "b"]);
+}
+
+#[test]
+fn test_const_init_forward_reference() {
+    let source = r#"
+    let c: const i32 = b * 2;
+    let N: const i32 = c + a;
+    let b: const i32 = a + 1;
+    let a: const i32 = 3;
+    fn main() -> i32 {
+        let arr: [i32; N];
+        return c;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("a"), Some(Value::I32(3)));
+    assert_eq!(value_of("b"), Some(Value::I32(4)));
+    assert_eq!(value_of("c"), Some(Value::I32(8)));
+    assert_eq!(value_of("N"), Some(Value::I32(11)));
+}
+
+#[test]
+fn test_analyze_all_stats() {
+    let vfs = Vfs::default();
+    let sources = [
+        "fn main() -> i32 { return 0; }",
+        "let g: const i32 = 1;\nfn f() -> i32 { return g + 1; }",
+        "fn h() { let x: i32 = y; }",
+    ];
+    for (i, source) in sources.iter().enumerate() {
+        vfs.new_file(PathBuf::from(format!("file{i}.airy")), source.to_string());
+    }
+
+    let mut project = Project::new();
+    let stats = project.analyze_all(&vfs);
+
+    assert_eq!(stats.files.len(), 3);
+    let sum: usize = project
+        .modules
+        .values()
+        .map(|m| {
+            syntax::SyntaxNode::new_root(m.green_tree.clone())
+                .descendants()
+                .count()
+        })
+        .sum();
+    assert_eq!(stats.total_node_count(), sum);
+    assert!(stats.files.iter().all(|f| f.node_count > 0));
+    assert_eq!(stats.total_error_count(), 1);
+    // 每个文件都单独记录了耗时，各文件并行分析，总和可能超过总耗时
+    assert!(
+        stats
+            .files
+            .iter()
+            .all(|f| f.elapsed > std::time::Duration::ZERO)
+    );
+}
+
+#[test]
+fn test_raw_identifier() {
+    let source = r#"
+    fn r#while(r#if: i32) -> i32 {
+        return r#if + 1;
+    }
+    fn main() -> i32 {
+        let r#if: i32 = 1;
+        let y: i32 = r#if + r#while(r#if);
+        return y;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert!(module.get_function_id_by_name("while").is_some());
+    assert!(module.variables.iter().any(|(_, v)| v.name == "if"));
+}
+
+#[test]
+fn test_function_attributes() {
+    let source = r#"
+    @inline fn add(a: i32, b: i32) -> i32 {
+        return a + b;
+    }
+    @noinline
+    fn main() -> i32 {
+        return add(1, 2);
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_unknown_attribute_error() {
+    let source = r#"
+    @bogus fn f() {}
+    "#;
+    let module = analyze(source);
+    assert_eq!(module.semantic_errors.len(), 1);
+    match &module.semantic_errors[0] {
+        AnalyzeError::UnknownAttribute { name, .. } => assert_eq!(name, "bogus"),
+        e => panic!("Expected UnknownAttribute error, found {e:?}"),
+    }
+}
+
+#[test]
+fn test_type_display() {
+    let ptr = |pointee: Ty, is_const: bool| Ty::Pointer {
+        pointee: Box::new(pointee),
+        is_const,
+    };
+    assert_eq!(ptr(Ty::I32, false).to_string(), "*mut i32");
+    assert_eq!(ptr(Ty::I8, true).to_string(), "*const i8");
+    assert_eq!(
+        Ty::Array(Box::new(Ty::I32), Some(10)).to_string(),
+        "[i32; 10]"
+    );
+    assert_eq!(Ty::Const(Box::new(Ty::U32)).to_string(), "const u32");
+    assert_eq!(
+        ptr(Ty::Array(Box::new(Ty::U8), Some(4)), false).to_string(),
+        "*mut [u8; 4]"
+    );
+
+    let module = analyze(
+        r#"
+    struct Foo { x: i32 }
+    fn main() {
+        let f: struct Foo;
+        let p: *mut i32 = f;
+    }
+    "#,
+    );
+    match &module.semantic_errors[..] {
+        [e @ AnalyzeError::TypeMismatch { .. }] => {
+            assert_eq!(
+                e.to_string(),
+                "type mismatch: expected *mut i32, found struct Foo"
+            );
+        }
+        e => panic!("Expected TypeMismatch error, found {e:?}"),
+    }
+}
+
+#[test]
+fn test_use_before_init_error() {
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32;
+        let y: i32 = x + 1;
+        let z: i32;
+        if (y > 0) {
+            z = 1;
+        }
+        return y + z;
+    }
+    "#;
+    let module = analyze(source);
+    let names: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| match e {
+            AnalyzeError::UseBeforeInit { name, .. } => name.as_str(),
+            e => panic!("Expected UseBeforeInit error, got {e:?}"),
+        })
+        .collect();
+    assert_eq!(names, ["x", "z"]);
+}
+
+#[test]
+fn test_assign_before_use_ok() {
+    let source = r#"
+    fn set(p: *mut i32) {
+        *p = 3;
+    }
+    fn main() -> i32 {
+        let x: i32;
+        x = 1;
+        let y: i32;
+        if (x > 0) {
+            y = 2;
+        } else {
+            return 0;
+        }
+        let z: i32;
+        set(&z);
+        let w: i32;
+        while (x < 10) {
+            w = x;
+            x = x + w;
+        }
+        return x + y + z;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+/// 按限定名查找枚举成员的常量值
+fn enum_variant_value(module: &Module, qualified: &str) -> Option<Value> {
+    let (_, var) = module.variables.iter().find(|(_, v)| v.name == qualified)?;
+    module.value_table.get(&var.range).cloned()
+}
+
+#[test]
+fn test_enum_auto_increment() {
+    let source = r#"
+    enum Color {
+        Red,
+        Green = 5,
+        Blue,
+    }
+    fn main() -> i32 {
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(
+        enum_variant_value(&module, "Color::Red"),
+        Some(Value::I32(0))
+    );
+    assert_eq!(
+        enum_variant_value(&module, "Color::Green"),
+        Some(Value::I32(5))
+    );
+    assert_eq!(
+        enum_variant_value(&module, "Color::Blue"),
+        Some(Value::I32(6))
+    );
+}
+
+#[test]
+fn test_enum_explicit_values() {
+    let source = r#"
+    enum Flag: u8 {
+        A = 1,
+        B = 2 * 2,
+        C = 254,
+        D,
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(enum_variant_value(&module, "Flag::A"), Some(Value::U8(1)));
+    assert_eq!(enum_variant_value(&module, "Flag::B"), Some(Value::U8(4)));
+    assert_eq!(enum_variant_value(&module, "Flag::D"), Some(Value::U8(255)));
+
+    let source = r#"
+    enum Flag: u8 {
+        A = 255,
+        B,
+    }
+    enum Bad: bool {
+        X,
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module
+            .semantic_errors
+            .iter()
+            .any(|e| matches!(e, AnalyzeError::ConstArithmeticOverflow { .. }))
+    );
+    assert!(
+        module
+            .semantic_errors
+            .iter()
+            .any(|e| matches!(e, AnalyzeError::InvalidEnumBackingType { .. }))
+    );
+}
+
+#[test]
+fn test_enum_bad_middle_variant() {
+    let source = r#"
+    let g: i32 = 1;
+    enum E: u8 {
+        A,
+        B = 300,
+        C = 7,
+        D,
+    }
+    enum F {
+        X,
+        Y = g,
+        Z,
+    }
+    fn main() -> i32 {
+        let e: u8 = E::C + E::D;
+        return F::Z;
+    }
+    "#;
+    let module = analyze(source);
+    // 出错的成员之后的成员仍然注册，使用处不会报 EnumVariantUndefined
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [
+                AnalyzeError::ConstArithmeticOverflow { .. },
+                AnalyzeError::ConstantExprExpected { .. }
+            ]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(enum_variant_value(&module, "E::C"), Some(Value::U8(7)));
+    assert_eq!(enum_variant_value(&module, "E::D"), Some(Value::U8(8)));
+    assert!(enum_variant_value(&module, "F::Z").is_some());
+}
+
+#[test]
+fn test_enum_variant_in_expr() {
+    let source = r#"
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+    fn main() -> i32 {
+        let c: i32 = Color::Blue + 1;
+        return c;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert!(module.value_table.values().any(|v| *v == Value::I32(3)));
+
+    let source = r#"
+    enum Color {
+        Red,
+    }
+    fn main() -> i32 {
+        return Color::Purple;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [AnalyzeError::EnumVariantUndefined { enum_name, variant, .. }]
+            if enum_name == "Color" && variant == "Purple"
+    ));
+}
+
+#[test]
+fn test_switch_duplicate_case_label() {
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 2;
+        switch (x) {
+            case 1:
+                return 1;
+            case 2:
+                break;
+            case 1:
+                return 3;
+            default:
+                return 4;
+            default:
+                return 5;
+        }
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    let labels: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| match e {
+            AnalyzeError::DuplicateCaseLabel { label, .. } => label.as_str(),
+            e => panic!("Expected DuplicateCaseLabel error, got {e:?}"),
+        })
+        .collect();
+    assert_eq!(labels, ["1", "default"]);
+}
+
+#[test]
+fn test_switch_case_checks() {
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 2;
+        let b: bool = true;
+        switch (b) {
+            case 1:
+                break;
+        }
+        switch (x) {
+            case x:
+                break;
+        }
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [
+            AnalyzeError::SwitchConditionNotInteger { .. },
+            AnalyzeError::ConstantExprExpected { .. }
+        ]
+    ));
+}
+
+#[test]
+fn test_switch_break_and_init() {
+    // switch 内 break 合法，所有分支都赋值（含 default）后变量已初始化
+    let source = r#"
+    let K: const i32 = 2;
+    fn main() -> i32 {
+        let x: i32 = 3;
+        let y: i32;
+        switch (x) {
+            case 1:
+            case K:
+                y = 1;
+                break;
+            default:
+                y = 2;
+        }
+        return y;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 3;
+        let y: i32;
+        switch (x) {
+            case 1:
+                y = 1;
+                break;
+        }
+        return y;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [AnalyzeError::UseBeforeInit { .. }]
+    ));
+}
+
+#[test]
+fn test_unused_function_hint() {
+    let vfs = Vfs::default();
+    let source = r#"
+    fn puts(s: *const i8) -> i32;
+    fn used() -> i32 { return 1; }
+    fn unused() -> i32 { return 2; }
+    fn main() -> i32 { return used(); }
+    "#;
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+
+    let mut project = Project::new().with_checker::<UnusedFunctionChecker>();
+    project.full_initialize(&vfs);
+
+    let errors = &project.modules[&file_id].semantic_errors;
+    assert!(matches!(
+        errors.as_slice(),
+        [AnalyzeError::UnusedFunction { name, .. }] if name == "unused"
+    ));
+    assert_eq!(
+        miette::Diagnostic::severity(&errors[0]),
+        Some(miette::Severity::Advice)
+    );
+}
+
+#[test]
+fn test_array_size_from_const_global() {
+    let source = r#"
+    let SIZE: const i32 = 4;
+    let grid: [[i32; SIZE]; SIZE * 2];
+    fn main() -> i32 {
+        let a: [i32; SIZE] = {1, 2, 3, 4};
+        return a[3];
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let (_, grid) = module
+        .variables
+        .iter()
+        .find(|(_, v)| v.name == "grid")
+        .unwrap();
+    assert_eq!(
+        grid.ty,
+        Ty::Array(Box::new(Ty::Array(Box::new(Ty::I32), Some(4))), Some(8))
+    );
+}
+
+#[test]
+fn test_array_size_from_mutable_global() {
+    let source = r#"
+    let size: i32 = 4;
+    fn main() -> i32 {
+        let a: [i32; size];
+        let b: [i32; missing];
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    // 可变全局变量不是常量；未定义的变量只报告一次，不再附带常量错误
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [
+                AnalyzeError::ConstantExprExpected { .. },
+                AnalyzeError::VariableUndefined { .. }
+            ]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_for_loop_scope() {
+    let source = r#"
+    fn main() -> i32 {
+        let sum: i32 = 0;
+        for (let i: i32 = 0; i < 10; i = i + 1) {
+            if (i == 5) {
+                continue;
+            }
+            sum = sum + i;
+        }
+        let j: i32;
+        for (j = 3; ; j = j - 1) {
+            if (j == 0) {
+                break;
+            }
+        }
+        return sum + i;
+    }
+    "#;
+    let module = analyze(source);
+    // 循环变量的作用域仅限于 for 本身
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::VariableUndefined { name, .. }] if name == "i"
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = r#"
+    // adds two numbers
+    fn add(a: i32, b: i32) -> i32 {
+        return a + b;
+    }
+
+    /*
+     * 二维点
+     *   x 和 y 都是整数
+     */
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    // 与下面的定义隔着空行，不算文档
+
+    let G: i32 = 1;
+
+    /// 计数器
+    /// 初始为 0
+    let COUNTER: i32 = 0;
+
+    fn main() -> i32 {
+        return add(G, COUNTER);
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let doc_of_function = |name: &str| {
+        let (_, f) = module
+            .functions
+            .iter()
+            .find(|(_, f)| f.name == name)
+            .unwrap();
+        f.doc.clone()
+    };
+    assert_eq!(doc_of_function("add").as_deref(), Some("adds two numbers"));
+    assert_eq!(doc_of_function("main"), None);
+
+    let (_, point) = module
+        .structs
+        .iter()
+        .find(|(_, s)| s.name == "Point")
+        .unwrap();
+    assert_eq!(point.doc.as_deref(), Some("二维点\n  x 和 y 都是整数"));
+
+    let doc_of_variable = |name: &str| {
+        let (_, v) = module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == name)
+            .unwrap();
+        v.doc.clone()
+    };
+    assert_eq!(doc_of_variable("G"), None);
+    assert_eq!(
+        doc_of_variable("COUNTER").as_deref(),
+        Some("计数器\n初始为 0")
+    );
+}
+
+#[test]
+fn test_main_signatures() {
+    for source in [
+        "fn main() { return; }",
+        "fn main() -> i32 { return 3; }",
+        "fn main(argc: i32, argv: *mut *mut u8) -> i32 { return argc; }",
+    ] {
+        let module = analyze(source);
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+    }
+
+    let module = analyze("fn main(x: i32, y: i32) -> i32 { return x + y; }");
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::UnsupportedMainParams { .. }]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(
+        miette::Diagnostic::severity(&module.semantic_errors[0]),
+        Some(miette::Severity::Warning)
+    );
+}
+
+#[test]
+fn test_continue_in_switch() {
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        switch (x) {
+            case 1:
+                continue;
+            default:
+                break;
+        }
+        return x;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::ContinueOutsideLoop { .. }]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        switch (x) {
+            case 1:
+                x = 2;
+                break;
+        }
+        return x;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 循环内的 switch 中，continue 作用于外层循环
+    let source = r#"
+    fn main() -> i32 {
+        let i: i32 = 0;
+        while (i < 10) {
+            i = i + 1;
+            switch (i) {
+                case 3:
+                    continue;
+                default:
+                    break;
+            }
+        }
+        return i;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_const_fn_in_array_size() {
+    let source = r#"
+    const fn square(x: i32) -> i32 {
+        return x * x;
+    }
+    const fn fact(n: i32) -> i32 {
+        let r: i32 = 1;
+        for (let i: i32 = 2; i <= n; i = i + 1) {
+            r = r * i;
+        }
+        return r;
+    }
+    const fn fib(n: i32) -> i32 {
+        if (n < 2) {
+            return n;
+        }
+        return fib(n - 1) + fib(n - 2);
+    }
+    let A: [i32; square(3)];
+    fn main() -> i32 {
+        let b: [i32; fact(4) + fib(10)];
+        return square(2);
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let array_size = |name: &str| {
+        let (_, v) = module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == name)
+            .unwrap();
+        match &v.ty {
+            Ty::Array(_, size) => *size,
+            ty => panic!("{name}: {ty:?}"),
+        }
+    };
+    assert_eq!(array_size("A"), Some(9));
+    assert_eq!(array_size("b"), Some(24 + 55));
+}
+
+#[test]
+fn test_const_fn_invalid_body() {
+    let source = r#"
+    fn printf(t: *const u8, ...);
+    let counter: i32 = 0;
+    const fn noisy(x: i32) -> i32 {
+        printf("%d", x);
+        return x + counter;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [
+                AnalyzeError::NonConstFnBody { .. },
+                AnalyzeError::NonConstFnBody { .. }
+            ]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_static_assert() {
+    let source = r#"
+    let N: const i32 = 4;
+    fn main() -> i32 {
+        static_assert(N * 4 == 16);
+        static_assert(N);
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        static_assert(1 == 2);
+        static_assert(x == 1);
+        static_assert();
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert_eq!(
+        module.semantic_errors.len(),
+        3,
+        "{:?}",
+        module.semantic_errors
+    );
+    assert!(matches!(
+        module.semantic_errors[0],
+        AnalyzeError::StaticAssertFailed { .. }
+    ));
+    assert!(matches!(
+        module.semantic_errors[1],
+        AnalyzeError::ConstantExprExpected { .. }
+    ));
+    assert!(matches!(
+        module.semantic_errors[2],
+        AnalyzeError::ArgumentCountMismatch {
+            expected: 1,
+            found: 0,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_struct_field_names_do_not_leak() {
+    let source = r#"
+    let x: i32 = 5;
+    struct P {
+        x: i32,
+        y: i32,
+    }
+    fn main() -> i32 {
+        let p: struct P;
+        p.x = x;
+        let y: i32 = x + p.x;
+        p.y = y;
+        return p.y;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 字段不作为变量注册到任何作用域
+    let globals: Vec<_> = module
+        .variables
+        .iter()
+        .filter(|(_, v)| v.name == "x")
+        .collect();
+    assert_eq!(globals.len(), 1);
+    let global_x = module.variable_map[&globals[0].1.range];
+
+    // `.x` 解析为字段，单独的 `x` 解析为全局变量
+    let (mut fields, mut vars) = (0, 0);
+    for reference in module.reference.iter().map(|(_, r)| r) {
+        let start = usize::from(reference.range.start());
+        let text = &source[start..usize::from(reference.range.end())];
+        if text != "x" {
+            continue;
+        }
+        match reference.tag {
+            ReferenceTag::FieldRead(_) => {
+                assert_eq!(&source[start - 1..start], ".");
+                fields += 1;
+            }
+            ReferenceTag::VarRead(var_id) => {
+                assert_ne!(&source[start - 1..start], ".");
+                assert_eq!(var_id, global_x);
+                vars += 1;
+            }
+            tag => panic!("unexpected reference {tag:?}"),
+        }
+    }
+    assert_eq!((fields, vars), (2, 2));
+}
+
+#[test]
+fn test_address_of_whole_array() {
+    let source = r#"
+    struct S {
+        arr: [i32; 3],
+        m: [[i32; 2]; 2],
+    }
+    fn first(p: *mut i32) -> i32 {
+        return *p;
+    }
+    fn main() -> i32 {
+        let a: [i32; 4] = {1, 2, 3, 4};
+        let p: *mut [i32; 4] = &a;
+        let q: *mut i32 = &a[0];
+        let x: struct S;
+        let ps: *mut [i32; 3] = &x.arr;
+        let pm: *mut [i32; 2] = &x.m[1];
+        // 解引用数组指针后 decay 成元素指针
+        return first(*p) + *q;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    fn main() -> i32 {
+        let a: [i32; 4];
+        let r: *mut i32 = &a;
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert_eq!(module.semantic_errors.len(), 1);
+    match &module.semantic_errors[0] {
+        AnalyzeError::TypeMismatch {
+            expected, found, ..
+        } => {
+            assert_eq!(expected.to_string(), "*mut i32");
+            assert_eq!(found.to_string(), "*mut [i32; 4]");
+        }
+        e => panic!("Expected TypeMismatch, found {e:?}"),
+    }
+}
+
+#[test]
+fn test_node_info() {
+    use syntax::ast::{AstNode, BinaryExpr, IndexVal};
+
+    let source = r#"
+    fn main() -> i32 {
+        let a: [i32; 4] = {1, 2, 3, 4};
+        let i: i32 = 1;
+        let s: i32 = a[i] + 1;
+        return s;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(module.semantic_errors.is_empty());
+    let root = syntax::SyntaxNode::new_root(module.green_tree.clone());
+
+    let index = root
+        .descendants()
+        .filter_map(IndexVal::cast)
+        .find(|v| v.indices().next().is_some())
+        .unwrap();
+    let info = module.node_info(index.text_range());
+    assert_eq!(info.ty, Some(&Ty::I32));
+    assert!(info.value.is_none());
+    assert!(info.is_lvalue);
+    let Some(ReferenceTag::VarRead(var_id)) = info.reference else {
+        panic!("expected variable reference, found {:?}", info.reference);
+    };
+    assert_eq!(module.variables[*var_id].name, "a");
+
+    let binary = root.descendants().find_map(BinaryExpr::cast).unwrap();
+    let info = module.node_info(binary.text_range());
+    assert_eq!(info.ty, Some(&Ty::I32));
+    assert!(info.reference.is_none());
+    assert!(!info.is_lvalue);
+
+    let literal = binary.rhs().unwrap();
+    let info = module.node_info(literal.text_range());
+    assert_eq!(info.value, Some(&Value::I32(1)));
+    assert!(!info.is_lvalue);
+}
+
+#[test]
+fn test_void_in_binary_expr() {
+    for expr in [
+        "x + foo()",
+        "foo() * x",
+        "foo() < x",
+        "x == foo()",
+        "foo() && x",
+    ] {
+        let source = format!(
+            "fn foo() {{}}\nfn main() -> i32 {{ let x: i32 = 1; let y: i32 = 0; if ({expr}) {{ y = 1; }} return y; }}"
+        );
+        let module = analyze(&source);
+        assert_eq!(
+            module.semantic_errors.len(),
+            1,
+            "{expr}: {:?}",
+            module.semantic_errors
+        );
+        match &module.semantic_errors[0] {
+            AnalyzeError::VoidInExpression { range } => {
+                let start = usize::from(range.start());
+                assert_eq!(&source[start..usize::from(range.end())], "foo()", "{expr}");
+            }
+            e => panic!("Expected VoidInExpression for {expr}, found {e:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_multi_var_def() {
+    let source = r#"
+    let A: const i32 = 3, B: const i64 = A * 2;
+    fn main() -> i32 {
+        let x: i32 = 1, y: [i32; 2] = {2, 3};
+        for (let i: i32 = 0, j: i32 = 9; i < j; i = i + 1) {
+            x = x + y[1];
+        }
+        return x;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("A"), Some(Value::I32(3)));
+    assert_eq!(value_of("B"), Some(Value::I64(6)));
+    let ty_of = |name: &str| {
+        module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == name)
+            .map(|(_, v)| v.ty.to_string())
+    };
+    assert_eq!(ty_of("y").as_deref(), Some("[i32; 2]"));
+    assert_eq!(ty_of("j").as_deref(), Some("i32"));
+
+    let source = r#"
+    fn main() {
+        let a: i32 = 1, a: i32 = 2;
+    }
+    "#;
+    let module = analyze(source);
+    assert_eq!(module.semantic_errors.len(), 1);
+    match &module.semantic_errors[0] {
+        AnalyzeError::VariableDefined { name, range } => {
+            assert_eq!(name, "a");
+            // 报在第二个 a 上
+            assert_eq!(
+                usize::from(range.start()),
+                source.find("a: i32 = 2").unwrap()
+            );
+        }
+        e => panic!("Expected VariableDefined, found {e:?}"),
+    }
+}
+
+#[test]
+fn test_error_codes() {
+    let cases = [
+        (
+            "fn main() -> i32 { let a: i32 = 1; let a: i32 = 2; return a; }",
+            "E0201",
+        ),
+        ("fn main() -> i32 { return b; }", "E0205"),
+        (
+            "fn main() -> i32 { let p: *mut i32 = 1; return 0; }",
+            "E0301",
+        ),
+        (
+            "fn main() -> i32 { let a: const i32 = 1; a = 2; return a; }",
+            "E0404",
+        ),
+        ("fn main() -> i32 { break; return 0; }", "E0501"),
+        (
+            "fn main() -> i32 { static_assert(1 == 2); return 0; }",
+            "E0407",
+        ),
+    ];
+    for (source, code) in cases {
+        let module = analyze(source);
+        assert_eq!(
+            module.semantic_errors.len(),
+            1,
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+        assert_eq!(module.semantic_errors[0].error_code(), code, "{source}");
+    }
+}
+
+#[test]
+fn test_error_codes_unique() {
+    // 每个变体构造一个实例，检查错误码互不相同
+    use AnalyzeError::*;
+
+    use crate::array::ArrayInitError;
+    use crate::error::ArgumentTypeMismatchData;
+
+    let range = tools::TextRange::default();
+    let s = String::new;
+    let errors = vec![
+        ParserError(Box::new(parser::parse::ParserError::Expected {
+            expected: Vec::new(),
+            range,
+        })),
+        TypeMismatch {
+            expected: Ty::I32,
+            found: Ty::I32,
+            range,
+        },
+        ConstantExprExpected { range },
+        IntegerLiteralOverflow {
+            literal: s(),
+            ty: s(),
+            range,
+        },
+        InvalidCharLiteral {
+            literal: s(),
+            reason: s(),
+            range,
+        },
+        ConstArithmeticOverflow {
+            message: s(),
+            range,
+        },
+        VariableDefined { name: s(), range },
+        FunctionDefined { name: s(), range },
+        FunctionImplemented { name: s(), range },
+        ImplementExternalFunction { name: s(), range },
+        VariableUndefined { name: s(), range },
+        ExpectInitialVal { name: s(), range },
+        ArrayError {
+            message: Box::new(ArrayInitError::AssignArrayToNumber),
+            range,
+        },
+        StructDefined { name: s(), range },
+        StructUndefined { name: s(), range },
+        TypeAliasDefined { name: s(), range },
+        TypeUndefined { name: s(), range },
+        InvalidEnumBackingType { ty: Ty::I32, range },
+        EnumVariantUndefined {
+            enum_name: s(),
+            variant: s(),
+            range,
+        },
+        FieldNotFound {
+            struct_name: s(),
+            field_name: s(),
+            range,
+        },
+        NotAStruct { ty: Ty::I32, range },
+        NotAStructPointer { ty: Ty::I32, range },
+        StructInitFieldCountMismatch {
+            expected: 0,
+            found: 0,
+            range,
+        },
+        ApplyOpOnType {
+            ty: Ty::I32,
+            op: s(),
+            range,
+        },
+        InvalidVoidUsage { range },
+        VoidInExpression { range },
+        VoidPointerDeref { range },
+        UseBeforeInit { name: s(), range },
+        FunctionUndefined { name: s(), range },
+        UnusedFunction { name: s(), range },
+        UnusedVariable { name: s(), range },
+        NotCallable {
+            name: s(),
+            ty: Ty::I32,
+            range,
+        },
+        UnexpectedTypeArgument { name: s(), range },
+        MissingTypeArgument { name: s(), range },
+        ArgumentCountMismatch {
+            function_name: s(),
+            expected: 0,
+            at_least: false,
+            found: 0,
+            range,
+        },
+        ArgumentTypeMismatch(Box::new(ArgumentTypeMismatchData {
+            function_name: s(),
+            param_name: s(),
+            arg_index: 0,
+            expected: Ty::I32,
+            found: Ty::I32,
+            range,
+        })),
+        AssignToConst { name: s(), range },
+        BreakOutsideLoop { range },
+        SwitchConditionNotInteger { ty: Ty::I32, range },
+        MisplacedRestrict { range },
+        ArrayInScalarContext { range },
+        DuplicateCaseLabel { label: s(), range },
+        ContinueOutsideLoop { range },
+        UnsupportedFeature {
+            feature: s(),
+            range,
+        },
+        ReturnTypeMismatch {
+            expected: Ty::I32,
+            found: Ty::I32,
+            range,
+        },
+        NotALValue { range },
+        AddressOfRight { range },
+        ImportPathNotFound { path: s(), range },
+        ImportSymbolNotFound {
+            symbol: s(),
+            module_path: s(),
+            range,
+        },
+        ImportSymbolConflict { symbol: s(), range },
+        RecursiveType {
+            struct_name: s(),
+            cycle: Vec::new(),
+            range,
+        },
+        CyclicTypeAlias {
+            name: s(),
+            cycle: Vec::new(),
+            range,
+        },
+        LenOnNonArray { ty: Ty::I32, range },
+        NotIndexable { ty: Ty::I32, range },
+        IncompleteStruct { name: s(), range },
+        InitializerMismatch {
+            expected: s(),
+            found: s(),
+            range,
+        },
+        BinaryOpTypeMismatch {
+            op: s(),
+            lhs: Ty::I32,
+            rhs: Ty::I32,
+            range,
+        },
+        CyclicConstInit { name: s(), range },
+        UnsupportedAbi { abi: s(), range },
+        UnknownAttribute { name: s(), range },
+        NonConstFnBody { range },
+        MissingReturn { name: s(), range },
+        UnconditionalRecursion { name: s(), range },
+        ConstantCondition { value: true, range },
+        InvalidMainSignature { ty: Ty::I32, range },
+        MissingMain { range },
+        UnsupportedMainParams { range },
+        StaticAssertFailed { range },
+        InvalidCfgArgument { range },
+        InvalidShiftCount {
+            count: 0,
+            width: 0,
+            range,
+        },
+        UnescapeError {
+            err: Box::new(snailquote::unescape("\"\\q\"").unwrap_err()),
+            range,
+        },
+    ];
+
+    let codes: Vec<_> = errors.iter().map(|e| e.error_code()).collect();
+    let unique: std::collections::HashSet<_> = codes.iter().collect();
+    assert_eq!(
+        unique.len(),
+        codes.len(),
+        "duplicate error codes: {codes:?}"
+    );
+}
+
+#[test]
+fn test_alloc() {
+    let source = r#"
+    struct P { x: i32 }
+    fn main() -> i32 {
+        let n: i32 = 2;
+        let a: *mut i32 = alloc<i32>(n);
+        let b: *mut *mut struct P = alloc<*mut struct P>(4);
+        let c: *mut [i32; 3] = alloc<[i32; 3]>(1);
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let ty_of = |call: &str| {
+        let start = source.find(call).unwrap() as u32;
+        // 表达式范围包含前导空格
+        let range = tools::TextRange::new(start - 1, start + call.len() as u32);
+        module.get_expr_type(range).cloned()
+    };
+    assert_eq!(
+        ty_of("alloc<i32>(n)"),
+        Some(Ty::Pointer {
+            pointee: Box::new(Ty::I32),
+            is_const: false
+        })
+    );
+    assert!(matches!(
+        ty_of("alloc<*mut struct P>(4)"),
+        Some(Ty::Pointer { pointee, is_const: false }) if pointee.is_pointer()
+    ));
+    assert!(matches!(
+        ty_of("alloc<[i32; 3]>(1)"),
+        Some(Ty::Pointer { pointee, is_const: false }) if pointee.is_array()
+    ));
+
+    let cases = [
+        (
+            "let p: *mut i32 = alloc<i32>(null);",
+            "ArgumentTypeMismatch",
+        ),
+        (
+            "let p: *mut i32 = alloc<i32>(1, 2);",
+            "ArgumentCountMismatch",
+        ),
+        ("let p: *mut i32 = alloc(1);", "MissingTypeArgument"),
+        ("let p: *mut i32 = alloc<void>(1);", "InvalidVoidUsage"),
+        ("let p: *mut i8 = alloc<i32>(1);", "TypeMismatch"),
+        ("let x: i32 = id<i32>(1);", "UnexpectedTypeArgument"),
+    ];
+    for (stmt, expected) in cases {
+        let source = format!(
+            "fn id(x: i32) -> i32 {{ return x; }}\nfn main() -> i32 {{ {stmt} return 0; }}"
+        );
+        let module = analyze(&source);
+        assert_eq!(
+            module.semantic_errors.len(),
+            1,
+            "{stmt}: {:?}",
+            module.semantic_errors
+        );
+        let found = format!("{:?}", module.semantic_errors[0]);
+        assert!(found.starts_with(expected), "{stmt}: {found}");
+    }
+}
+
+#[test]
+fn test_nested_for_shadowed_loop_var() {
+    let source = r#"
+    fn main() -> i32 {
+        let count: i32 = 0;
+        for (let i: i32 = 0; i < 4; i = i + 1) {
+            for (let i: i32 = 0; i < 10; i = i + 1) {
+                if (i == 3) {
+                    break;
+                }
+                count = count + 1;
+            }
+            count = count + i;
+        }
+        return count;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 两个 i 各自在 for 的作用域中定义
+    let mut defs: Vec<_> = module
+        .variables
+        .iter()
+        .filter(|(_, v)| v.name == "i")
+        .map(|(_, v)| v.range)
+        .collect();
+    defs.sort_by_key(|r| r.start());
+    let [outer, inner] = defs.as_slice() else {
+        panic!("expected two loop variables, found {defs:?}");
+    };
+    let outer_id = module.variable_map[outer];
+    let inner_id = module.variable_map[inner];
+    assert_ne!(outer_id, inner_id);
+
+    // 内层 for 中的 i 都解析到内层变量，其余解析到外层变量
+    let inner_start = source.find("for (let i: i32 = 0; i < 10").unwrap();
+    let inner_end = source.find("count = count + i;").unwrap();
+    let mut reads = 0;
+    for reference in module.reference.iter().map(|(_, r)| r) {
+        let start = usize::from(reference.range.start());
+        if &source[start..usize::from(reference.range.end())] != "i" {
+            continue;
+        }
+        let ReferenceTag::VarRead(var_id) = reference.tag else {
+            panic!("unexpected reference {:?}", reference.tag);
+        };
+        let expected = if (inner_start..inner_end).contains(&start) {
+            inner_id
+        } else {
+            outer_id
+        };
+        assert_eq!(var_id, expected, "reference at {start}");
+        reads += 1;
+    }
+    // 每层 4 次：条件、步进两次，外层循环体或内层 if 条件一次
+    assert_eq!(reads, 8);
+}
+
+#[test]
+fn test_on_error_callback() {
+    use std::sync::{Arc, Mutex};
+
+    let source = r#"
+    fn main() -> i32 {
+        let a: i32 = b;
+        let c: *mut i32 = 1;
+        break;
+        return d;
+    }
+    fn f(x: struct Missing) {}
+    "#;
+    let (tree, errors) = Parser::new(source).parse();
+    assert!(errors.is_empty(), "{errors:?}");
+    let vfs = Vfs::default();
+    let mut module = Module::new(tree);
+    module.file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&streamed);
+    module.set_on_error(move |e| sink.lock().unwrap().push(e.to_string()));
+    Project::allocate_module_symbols(&mut module);
+    // 填充定义时的错误同样经过回调
+    Project::fill_definitions(&mut module);
+    module.analyze();
+
+    let collected: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(collected[0], "struct 'Missing' undefined", "{collected:?}");
+    assert!(collected.contains(&"break statement outside loop".to_string()));
+    assert_eq!(*streamed.lock().unwrap(), collected);
+}
+
+#[test]
+fn test_cfg() {
+    let source = r#"
+    let DEBUG: const bool = cfg("debug");
+    fn main() -> i32 {
+        static_assert(cfg("debug") || !cfg("debug"));
+        // nanoc:allow(constant_condition)
+        if (cfg("trace")) {
+            return 1;
+        }
+        return 0;
+    }
+    "#;
+    let var_value = |module: &Module, name: &str| {
+        let (_, var) = module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == name)
+            .unwrap();
+        module.get_value_by_range(var.range).cloned()
+    };
+    let cond_range = |source: &str| {
+        let start = source.find("cfg(\"trace\")").unwrap() as u32;
+        tools::TextRange::new(start, start + "cfg(\"trace\")".len() as u32)
+    };
+
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(var_value(&module, "DEBUG"), Some(Value::Bool(false)));
+    assert_eq!(
+        module.get_value_by_range(cond_range(source)),
+        Some(&Value::Bool(false))
+    );
+
+    let config = AnalyzeConfig {
+        features: ["debug".to_string(), "trace".to_string()].into(),
+        ..Default::default()
+    };
+    let module = analyze_with_config(source, config);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(var_value(&module, "DEBUG"), Some(Value::Bool(true)));
+    assert_eq!(
+        module.get_value_by_range(cond_range(source)),
+        Some(&Value::Bool(true))
+    );
+
+    for call in ["cfg(1)", "cfg(name)", "cfg()"] {
+        let source = format!(
+            "fn main() -> i32 {{ let name: i32 = 1; if ({call}) {{ return name; }} return 0; }}"
+        );
+        let module = analyze(&source);
+        assert_eq!(
+            module.semantic_errors.len(),
+            1,
+            "{call}: {:?}",
+            module.semantic_errors
+        );
+        assert!(
+            matches!(
+                module.semantic_errors[0],
+                AnalyzeError::InvalidCfgArgument { .. }
+                    | AnalyzeError::ArgumentCountMismatch { .. }
+            ),
+            "{call}: {:?}",
+            module.semantic_errors
+        );
+    }
+}
+
+#[test]
+fn test_missing_return() {
+    let module = analyze("fn f() -> i32 {}\nfn main() -> i32 { return f(); }");
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::MissingReturn { name, .. }] if name == "f"
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 空的 void 函数和空的 main 都没有问题
+    for source in [
+        "fn f() {}\nfn main() { f(); }",
+        "fn main() {}",
+        "fn main() -> i32 {}",
+    ] {
+        let module = analyze(source);
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+    }
+
+    let returns = [
+        "if (x > 0) { return 1; } else { return 2; }",
+        "while (true) { if (x > 0) { return x; } }",
+        "for (;;) { x = x + 1; }",
+        "switch (x) { case 1: x = 2; default: return x; }",
+        "{ { return x; } }",
+    ];
+    let falls_through = [
+        "if (x > 0) { return 1; }",
+        "while (x > 0) { return x; }",
+        "while (true) { if (x > 0) { break; } }",
+        "switch (x) { case 1: return 1; }",
+        "switch (x) { case 1: break; default: return x; }",
+    ];
+    for (body, ok) in returns
+        .iter()
+        .map(|b| (b, true))
+        .chain(falls_through.iter().map(|b| (b, false)))
+    {
+        let source =
+            format!("fn f(x: i32) -> i32 {{ {body} }}\nfn main() -> i32 {{ return f(1); }}");
+        let module = analyze(&source);
+        let missing = module
+            .semantic_errors
+            .iter()
+            .any(|e| matches!(e, AnalyzeError::MissingReturn { .. }));
+        assert_eq!(missing, !ok, "{body}: {:?}", module.semantic_errors);
+    }
+}
+
+#[test]
+fn test_nested_initializer_error_range() {
+    let cases = [
+        // 二维数组中的单个元素
+        "fn main() -> i32 { let a: [[i32; 2]; 2] = {{1, 2}, {3, null}}; return 0; }",
+        // 数组中 struct 的字段
+        "struct P { x: i32, y: i32 }\nfn main() -> i32 { let a: [struct P; 2] = {{1, 2}, {3, null}}; return 0; }",
+        // struct 中数组字段的元素
+        "struct Q { v: [i32; 2] }\nfn main() -> i32 { let q: struct Q = {{3, null}}; return 0; }",
+    ];
+    for source in cases {
+        let module = analyze(source);
+        let [AnalyzeError::TypeMismatch { range, .. }] = module.semantic_errors.as_slice() else {
+            panic!("{source}: {:?}", module.semantic_errors);
+        };
+        let start = usize::from(range.start());
+        assert_eq!(&source[start..usize::from(range.end())], "null", "{source}");
+    }
+}
+
+#[test]
+fn test_extern_function() {
+    let module = analyze(
+        r#"
+    extern "C" fn printf(format: *const u8, ...) -> i32;
+    fn local() -> i32;
+    attach local { return 0; }
+    fn main() -> i32 { printf("%d\n", local()); return 0; }
+    "#,
+    );
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let is_extern = |name: &str| {
+        let id = module.get_function_id_by_name(name).unwrap();
+        module.get_function_by_id(id).unwrap().is_extern
+    };
+    assert!(is_extern("printf"));
+    assert!(!is_extern("local"));
+
+    let cases = [
+        r#"extern fn f() -> i32 { return 0; }"#,
+        r#"extern fn f() -> i32; attach f { return 0; }"#,
+    ];
+    for source in cases {
+        let module = analyze(source);
+        assert!(
+            matches!(
+                module.semantic_errors.as_slice(),
+                [AnalyzeError::ImplementExternalFunction { name, .. }] if name == "f"
+            ),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+    }
+
+    let module = analyze(r#"extern "Rust" fn f();"#);
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::UnsupportedAbi { abi, .. }] if abi == "\"Rust\""
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_2d_array_decay_argument() {
+    // 二维数组只 decay 最外层，得到指向第一行的指针
+    for (param, ok) in [
+        ("*mut [i32; 3]", true),
+        ("*const [i32; 3]", true),
+        ("*mut [i32; 4]", false),
+        ("*mut i32", false),
+        ("*mut [[i32; 3]; 2]", false),
+    ] {
+        let source = format!(
+            "fn f(p: {param}) -> i32 {{ return 0; }}\n\
+             fn main() -> i32 {{ let a: [[i32; 3]; 2]; return f(a); }}"
+        );
+        let module = analyze(&source);
+        let mismatch = matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::ArgumentTypeMismatch(_)]
+        );
+        assert_eq!(
+            module.semantic_errors.is_empty(),
+            ok,
+            "{param}: {:?}",
+            module.semantic_errors
+        );
+        assert_eq!(mismatch, !ok, "{param}: {:?}", module.semantic_errors);
+    }
+
+    let module = analyze(
+        r#"
+    fn f(rows: *mut [i32; 3]) -> i32 { return rows[1][2]; }
+    fn main() -> i32 { let a: [[i32; 3]; 2]; return f(a); }
+    "#,
+    );
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_array_size_overflow() {
+    for source in [
+        "let a: [i32; 100000 * 100000];",
+        "fn main() -> i32 { let a: [i32; (100000 * 100000) + 1]; return 0; }",
+        "struct S { a: [i32; 100000 * 100000] }",
+    ] {
+        let module = analyze(source);
+        let [AnalyzeError::ConstArithmeticOverflow { range, .. }] =
+            module.semantic_errors.as_slice()
+        else {
+            panic!("{source}: {:?}", module.semantic_errors);
+        };
+        let text = &source[usize::from(range.start())..usize::from(range.end())];
+        assert_eq!(text.trim(), "100000 * 100000", "{source}");
+    }
+}
+
+#[test]
+fn test_resolve_cross_module() {
+    // import 路径按真实文件解析，需要写到磁盘上
+    let dir = std::env::temp_dir().join(format!("airyc_cross_module_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let a_source = "struct P { x: i32 }\nfn add(a: i32, b: i32) -> i32 { return a + b; }";
+    let b_source = "import \"a.airy\"\nfn main() -> i32 { return add(1, 2); }";
+    std::fs::write(dir.join("a.airy"), a_source).unwrap();
+    std::fs::write(dir.join("b.airy"), b_source).unwrap();
+
+    let vfs = Vfs::default();
+    let dir = dir.canonicalize().unwrap();
+    let a_id = vfs.new_file(dir.join("a.airy"), a_source.to_string());
+    let b_id = vfs.new_file(dir.join("b.airy"), b_source.to_string());
+    let mut project = Project::new();
+    project.full_initialize(&vfs);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let b = project.module_of(b_id).unwrap();
+    assert!(b.semantic_errors.is_empty(), "{:?}", b.semantic_errors);
+
+    let Some((file_id, SymbolRef::Function(func_id))) = project.resolve_cross_module(b_id, "add")
+    else {
+        panic!("add should resolve to a function");
+    };
+    assert_eq!(file_id, a_id);
+    let a = project.module_of(a_id).unwrap();
+    assert_eq!(a.get_function_by_id(func_id).unwrap().name, "add");
+
+    assert!(matches!(
+        project.resolve_cross_module(b_id, "P"),
+        Some((file_id, SymbolRef::Struct(_))) if file_id == a_id
+    ));
+    assert_eq!(project.resolve_cross_module(b_id, "missing"), None);
+    assert_eq!(project.resolve_cross_module(a_id, "main"), None);
+}
+
+#[test]
+fn test_array_in_scalar_context() {
+    for body in [
+        "if (arr) { return 1; }",
+        "while (arr) { break; }",
+        "for (; arr; ) { break; }",
+        "let x: i32 = arr + 1;",
+        "let b: bool = arr == arr;",
+    ] {
+        let source =
+            format!("fn main() -> i32 {{ let arr: [i32; 3] = {{1, 2, 3}}; {body} return 0; }}");
+        let module = analyze(&source);
+        assert!(
+            module
+                .semantic_errors
+                .iter()
+                .any(|e| matches!(e, AnalyzeError::ArrayInScalarContext { .. })),
+            "{body}: {:?}",
+            module.semantic_errors
+        );
+    }
+
+    // 需要指针的地方仍然 decay
+    let source = r#"
+        fn sum(p: *const i32, n: i32) -> i32 { return p[0] + n; }
+        fn main() -> i32 {
+            let arr: [i32; 3] = {1, 2, 3};
+            if (arr[0]) { return sum(arr, 3); }
+            return 0;
+        }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_eval_expr_at() {
+    let source = "type Word = u64;\n\
+                  let N: const i32 = 4;\n\
+                  const fn square(x: i32) -> i32 { return x * x; }\n\
+                  fn main() -> i32 { let M: const i32 = 10; let v: i32 = 1; return v; }";
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let in_main = source.find("return v").unwrap() as u32;
+
+    let eval = |expr: &str, offset: u32| module.eval_expr_at(expr, offset);
+    assert_eq!(eval("2 + 3 * 4", 0).unwrap(), Some(Value::I32(14)));
+    assert_eq!(
+        eval("N * M + square(3)", in_main).unwrap(),
+        Some(Value::I32(49))
+    );
+    // 类型别名随分析结果一起复制
+    assert_eq!(eval("sizeof(Word)", 0).unwrap(), Some(Value::U64(8)));
+    // 局部常量在函数外不可见
+    assert!(matches!(
+        eval("M", 0).unwrap_err().as_slice(),
+        [AnalyzeError::VariableUndefined { .. }]
+    ));
+    // 普通变量不是常量
+    assert_eq!(eval("v + 1", in_main).unwrap(), None);
+    assert!(matches!(
+        eval("1 +", 0).unwrap_err().as_slice(),
+        [AnalyzeError::ParserError(_), ..]
+    ));
+}
+
+#[test]
+fn test_restrict_pointer_param() {
+    let source = r#"
+        fn copy(dst: *restrict mut i32, n: i32, src: *restrict const i32) {
+            *dst = *src + n;
+        }
+        fn main() -> i32 {
+            let a: i32 = 1;
+            let b: i32 = 2;
+            copy(&a, 1, &b);
+            return a;
+        }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // restrict 不改变参数类型
+    let func_id = module.get_function_id_by_name("copy").unwrap();
+    let func = module.get_function_by_id(func_id).unwrap();
+    assert_eq!(func.restrict_params, vec![0, 2]);
+    assert_eq!(
+        func.meta_types[0].1,
+        Ty::Pointer {
+            pointee: Box::new(Ty::I32),
+            is_const: false
+        }
+    );
+    assert_eq!(
+        func.meta_types[2].1,
+        Ty::Pointer {
+            pointee: Box::new(Ty::I32),
+            is_const: true
+        }
+    );
+
+    // 只能修饰参数最外层的指针
+    for source in [
+        "fn main() -> i32 { let p: *restrict mut i32 = null; return 0; }",
+        "struct S { p: *restrict mut i32 }",
+        "fn f(p: *mut i32) -> *restrict mut i32 { return p; }",
+        "fn f(p: *mut *restrict mut i32) {}",
+    ] {
+        let module = analyze(source);
+        assert!(
+            module
+                .semantic_errors
+                .iter()
+                .any(|e| matches!(e, AnalyzeError::MisplacedRestrict { .. })),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+    }
+}
+
+#[test]
+fn test_sizeof_comparison_folds() {
+    let source = r#"
+        struct P { a: i8, b: i32, c: i8 }
+        fn main() -> i32 {
+            static_assert(sizeof(i64) == 8);
+            static_assert(sizeof(i64) > sizeof(i32));
+            static_assert(sizeof(*mut i32) == sizeof(u64));
+            static_assert(sizeof([i32; 3]) == 12u64);
+            static_assert(sizeof(struct P) == 12);
+            static_assert(sizeof<bool>() == 1);
+            return 0;
+        }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let module =
+        analyze("fn main() -> i32 { static_assert(sizeof(i8) == sizeof(i32)); return 0; }");
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::StaticAssertFailed { .. }]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 结果是 u64 常量
+    let module = analyze("let S: const u64 = sizeof(u32);");
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    assert!(module.value_table.values().any(|v| *v == Value::U64(4)));
+
+    let module = analyze("fn main() -> i32 { let n: u64 = sizeof(void); return 0; }");
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::InvalidVoidUsage { .. }]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_goto_unsupported() {
+    // goto 和标号完整解析，不产生语法错误，由分析阶段给出明确的错误
+    for (body, feature) in [
+        ("goto done;", "goto"),
+        ("done: return 1;", "labeled statement"),
+    ] {
+        let source = format!("fn main() -> i32 {{ let x: i32 = 1; {body} return x; }}");
+        let module = analyze(&source);
+        assert!(
+            matches!(
+                module.semantic_errors.as_slice(),
+                [AnalyzeError::UnsupportedFeature { feature: f, .. }] if f == feature
+            ),
+            "{body}: {:?}",
+            module.semantic_errors
+        );
+    }
+}
+
+#[test]
+fn test_common_type() {
+    let ptr = |pointee: Ty| Ty::Pointer {
+        pointee: Box::new(pointee),
+        is_const: false,
+    };
+    let null = ptr(Ty::Void);
+
+    // 相同类型，const 被去掉
+    assert_eq!(Ty::common_type(&Ty::I32, &Ty::I32), Some(Ty::I32));
+    assert_eq!(
+        Ty::common_type(&Ty::Const(Box::new(Ty::U8)), &Ty::U8),
+        Some(Ty::U8)
+    );
+    // 不同宽度的整数按整数提升
+    assert_eq!(Ty::common_type(&Ty::I8, &Ty::I64), Some(Ty::I64));
+    assert_eq!(Ty::common_type(&Ty::U32, &Ty::U8), Some(Ty::U32));
+    // 指针与 null
+    assert_eq!(Ty::common_type(&ptr(Ty::I32), &null), Some(ptr(Ty::I32)));
+    assert_eq!(Ty::common_type(&null, &ptr(Ty::I32)), Some(ptr(Ty::I32)));
+    // 不兼容
+    assert_eq!(Ty::common_type(&ptr(Ty::I32), &Ty::I32), None);
+    assert_eq!(Ty::common_type(&ptr(Ty::I32), &ptr(Ty::I64)), None);
+    assert_eq!(Ty::common_type(&Ty::I32, &Ty::U32), None);
+}
+
+#[test]
+fn test_designated_array_init() {
+    let source = r#"
+    let N: const i32 = 6;
+    let A: [const i32; N] = { [0] = 1, [N - 1] = 9, [2] = 4, 5 };
+    let B: [[const i32; 2]; 3] = { [2] = {7, 8} };
+    let x: const i32 = A[5];
+    let y: const i32 = A[1];
+    let z: const i32 = A[3];
+    let w: const i32 = B[2][1];
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("x"), Some(Value::I32(9)));
+    assert_eq!(value_of("y"), Some(Value::I32(0)));
+    assert_eq!(value_of("z"), Some(Value::I32(5)));
+    assert_eq!(value_of("w"), Some(Value::I32(8)));
+}
+
+#[test]
+fn test_designated_array_init_errors() {
+    use crate::array::ArrayInitError;
+
+    let array_error = |init: &str| {
+        let source =
+            format!("fn main() -> i32 {{ let i: i32 = 1; let a: [i32; 4] = {init}; return 0; }}");
+        let module = analyze(&source);
+        match module.semantic_errors.as_slice() {
+            [AnalyzeError::ArrayError { message, .. }] => message.to_string(),
+            errors => panic!("{init}: {errors:?}"),
+        }
+    };
+
+    assert_eq!(
+        array_error("{ [1] = 1, [1] = 2 }"),
+        ArrayInitError::DuplicateIndex {
+            index: 1,
+            range: Default::default()
+        }
+        .to_string()
+    );
+    // 指示符之后的元素顺序排列，与后面的指示符重复
+    assert_eq!(
+        array_error("{ [2] = 1, 2, [3] = 3 }"),
+        ArrayInitError::DuplicateIndex {
+            index: 3,
+            range: Default::default()
+        }
+        .to_string()
+    );
+    for (init, index) in [("{ [4] = 1 }", 4), ("{ [0 - 1] = 1 }", -1)] {
+        assert_eq!(
+            array_error(init),
+            ArrayInitError::DesignatorOutOfRange {
+                index,
+                len: 4,
+                range: Default::default()
+            }
+            .to_string()
+        );
+    }
+    assert_eq!(
+        array_error("{ [i] = 1 }"),
+        ArrayInitError::NonConstDesignator {
+            range: Default::default()
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn test_shift_count() {
+    let source = r#"
+    let A: const i32 = 1 << 3;
+    let B: const i32 = -16 >> 2;
+    let C: const u8 = 255u8 >> 7u64;
+    fn main() -> i32 {
+        let x: i32 = 5;
+        let n: u32 = 2u32;
+        let y: i64 = 1i64 << 40;
+        return (x << 3) + (x >> n);
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("A"), Some(Value::I32(8)));
+    assert_eq!(value_of("B"), Some(Value::I32(-4)));
+    assert_eq!(value_of("C"), Some(Value::U8(1)));
+
+    for (ty, expr, count, width) in [
+        ("i32", "x << 40", 40, 32),
+        ("i32", "x >> -1", -1, 32),
+        ("u8", "1u8 << 8", 8, 8),
+    ] {
+        let source =
+            format!("fn main() -> i32 {{ let x: i32 = 1; let y: {ty} = {expr}; return 0; }}");
+        let module = analyze(&source);
+        let [
+            AnalyzeError::InvalidShiftCount {
+                count: c,
+                width: w,
+                range,
+            },
+        ] = module.semantic_errors.as_slice()
+        else {
+            panic!("{expr}: {:?}", module.semantic_errors);
+        };
+        assert_eq!((*c, *w), (count, width), "{expr}");
+        // 报告在移位数上
+        let text = &source[usize::from(range.start())..usize::from(range.end())];
+        assert!(expr.ends_with(&format!(" {text}")), "{expr}: {text}");
+    }
+}
+
+#[test]
+fn test_project_diagnostics() {
+    // 类型错误在前，词法错误（`$`）和语法错误（缺少初始值）在后
+    let source = "fn main() -> i32 {\n    let p: *mut i32 = 1;\n    return 0;\n}\nlet a: i32 = $;\nlet b: i32 = ;\n";
+    let vfs = Vfs::default();
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+    let mut project = Project::new();
+    project.full_initialize(&vfs);
+
+    let diagnostics = project.diagnostics(file_id);
+    let let_b = source.find("let b").unwrap();
+    let text = |d: &crate::diagnostic::Diagnostic| {
+        &source[usize::from(d.range.start())..usize::from(d.range.end())]
+    };
+    assert!(diagnostics.is_sorted_by_key(|d| d.range), "{diagnostics:?}");
+    assert_eq!(diagnostics[0].code, "E0301", "{diagnostics:?}");
+    assert_eq!(text(&diagnostics[0]), "1");
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.message == "unknown lexer error" && text(d) == "$"),
+        "{diagnostics:?}"
+    );
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.message.starts_with("Syntax") && usize::from(d.range.start()) > let_b),
+        "{diagnostics:?}"
+    );
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.severity == miette::Severity::Error)
+    );
+}
+
+#[test]
+fn test_type_alias() {
+    let source = r#"
+    type Int = i32;
+    let N: const i32 = 3;
+    type Vec3 = [Int; N];
+    struct S { x: Int }
+    type P = *mut struct S;
+    type CInt = const Int;
+    let V: Vec3 = { 1, 2, 3 };
+    fn main() -> Int {
+        let s: struct S = { 1 };
+        let p: P = &s;
+        let c: CInt = 2;
+        return p->x + c;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let ty_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        Some(var.ty.clone())
+    };
+    assert_eq!(ty_of("V"), Some(Ty::Array(Box::new(Ty::I32), Some(3))));
+    assert_eq!(ty_of("c"), Some(Ty::Const(Box::new(Ty::I32))));
+    assert!(matches!(
+        ty_of("p"),
+        Some(Ty::Pointer { pointee, is_const: false }) if matches!(*pointee, Ty::Struct { .. })
+    ));
+
+    let module = analyze("type A = B; type B = A; fn main() -> i32 { let x: A = 1; return 0; }");
+    let [AnalyzeError::CyclicTypeAlias { name, cycle, .. }] = module.semantic_errors.as_slice()
+    else {
+        panic!("{:?}", module.semantic_errors);
+    };
+    assert_eq!(name, "A");
+    assert_eq!(cycle, &["A", "B", "A"]);
+
+    let module = analyze("type A = i32; type A = u8; fn main() -> Foo { return 0; }");
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [
+            AnalyzeError::TypeAliasDefined { .. },
+            AnalyzeError::TypeUndefined { .. }
+        ]
+    ));
+}
+
+#[test]
+fn test_sizeof_type_alias() {
+    let source = r#"
+    type Int = i64;
+    type Vec3 = [Int; 3];
+    let N: const u64 = sizeof(Vec3);
+    fn main() -> i32 {
+        static_assert(sizeof(Int) == 8);
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let (_, n) = module
+        .variables
+        .iter()
+        .find(|(_, v)| v.name == "N")
+        .unwrap();
+    assert_eq!(module.value_table.get(&n.range), Some(&Value::U64(24)));
+
+    // 不是别名的名字仍然要求类型参数
+    let module = analyze("fn main() -> i32 { let x: i32 = 1; let n: u64 = sizeof(x); return 0; }");
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [AnalyzeError::MissingTypeArgument { .. }]
+    ));
+}
+
+#[test]
+fn test_local_const_array_index_folding() {
+    let source = r#"
+    fn main() -> i32 {
+        let a: [const i32; 3] = { 10, 20, 30 };
+        let m: [[const i32; 2]; 2] = { { 1, 2 }, { 3 } };
+        let x: const i32 = a[2];
+        let y: const i32 = m[1][0] + m[1][1];
+        let i: i32 = 1;
+        return a[i];
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert!(matches!(value_of("a"), Some(Value::Array(_))));
+    assert_eq!(value_of("x"), Some(Value::I32(30)));
+    // 未初始化的元素为 0
+    assert_eq!(value_of("y"), Some(Value::I32(3)));
+
+    // 下标不是常量时不折叠
+    let pos = source.find("a[i]").unwrap();
+    let index_range = module
+        .value_table
+        .keys()
+        .find(|r| usize::from(r.start()) == pos);
+    assert_eq!(index_range, None);
+}
+
+#[test]
+fn test_module_compact() {
+    let source = r#"
+    struct Point { x: i32 }
+    let g: i32 = 1;
+    fn unused() -> i32 {
+        let t0: i32 = 0; let t1: i32 = 1; let t2: i32 = 2; let t3: i32 = 3;
+        let t4: i32 = 4; let t5: i32 = 5; let t6: i32 = 6; let t7: i32 = 7;
+        return t0 + t1 + t2 + t3 + t4 + t5 + t6 + t7;
+    }
+    fn get(p: struct Point) -> i32 { return p.x + g; }
+    fn main() -> i32 {
+        let p: struct Point = { 1 };
+        return get(p);
+    }
+    "#;
+    let mut module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 模拟删除一部分符号：移除 `unused` 及其局部变量
+    let removed: Vec<_> = module
+        .variables
+        .iter()
+        .filter(|(_, v)| v.name.starts_with('t'))
+        .map(|(idx, v)| (idx, v.range))
+        .collect();
+    for (idx, range) in removed {
+        module.variables.remove(idx);
+        module.variable_map.remove(&range);
+        module
+            .reference
+            .retain(|_, r| r.tag != ReferenceTag::VarRead(VariableID(idx)));
+    }
+    for (_, scope) in module.scopes.iter_mut() {
+        scope.variables.retain(|name, _| !name.starts_with('t'));
+    }
+    let unused = module.function_map.remove("unused").unwrap();
+    module.functions.remove(unused.index);
+    module
+        .reference_map
+        .retain(|_, id| module.reference.contains(**id));
+
+    let capacity = module.variables.capacity();
+    module.compact();
+    assert!(module.variables.capacity() < capacity);
+    assert_eq!(module.variables.capacity(), module.variables.len());
+    assert_eq!(module.functions.len(), 2);
+
+    // 所有符号仍能解析
+    for (range, id) in &module.variable_map {
+        assert_eq!(module.get_variable_by_id(*id).unwrap().range, *range);
+    }
+    for (name, id) in &module.function_map {
+        assert_eq!(&module.get_function_by_id(*id).unwrap().name, name);
+    }
+    for (_, reference) in module.reference.iter() {
+        match reference.tag {
+            ReferenceTag::VarRead(id) => assert!(module.get_variable_by_id(id).is_some()),
+            ReferenceTag::FuncCall(id) => assert!(module.get_function_by_id(id).is_some()),
+            ReferenceTag::FieldRead(id) => assert!(module.get_field_by_id(id).is_some()),
+        }
+    }
+    let get = module.get_function_id_by_name("get").unwrap();
+    let get = module.get_function_by_id(get).unwrap();
+    let p = module.get_variable_by_id(get.params[0]).unwrap();
+    assert_eq!(p.name, "p");
+    let Ty::Struct { id, .. } = &p.ty else {
+        panic!("{:?}", p.ty);
+    };
+    assert_eq!(module.get_struct_by_id(*id).unwrap().name, "Point");
+}
+
+#[test]
+fn test_streq_folding() {
+    let source = r#"
+    fn is_a(s: *const u8) -> bool { return streq(s, "a"); }
+    fn main() -> i32 {
+        static_assert(streq("ok", "ok"));
+        let a: bool = streq("a\n", "a\n");
+        let b: bool = streq("a", "b");
+        let c: bool = streq("a\u{41}", "aA");
+        return 0;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let call_value = |call: &str| {
+        let start = source.find(call).unwrap() as u32;
+        // 表达式范围包含前导空格
+        let range = tools::TextRange::new(start - 1, start + call.len() as u32);
+        assert_eq!(module.get_expr_type(range), Some(&Ty::Bool), "{call}");
+        module.get_value_by_range(range).cloned()
+    };
+    assert_eq!(
+        call_value(r#"streq("a\n", "a\n")"#),
+        Some(Value::Bool(true))
+    );
+    assert_eq!(call_value(r#"streq("a", "b")"#), Some(Value::Bool(false)));
+    // 按转义后的内容比较
+    assert_eq!(
+        call_value(r#"streq("a\u{41}", "aA")"#),
+        Some(Value::Bool(true))
+    );
+    // 有一边不是常量时留到运行期
+    assert_eq!(call_value(r#"streq(s, "a")"#), None);
+
+    for (call, expected) in [
+        ("streq(\"a\")", "ArgumentCountMismatch"),
+        ("streq(1, \"a\")", "ArgumentTypeMismatch"),
+    ] {
+        let source = format!("fn main() -> i32 {{ let x: bool = {call}; return 0; }}");
+        let module = analyze(&source);
+        assert_eq!(module.semantic_errors.len(), 1, "{call}");
+        assert!(
+            format!("{:?}", module.semantic_errors[0]).starts_with(expected),
+            "{call}: {:?}",
+            module.semantic_errors
+        );
+    }
+}
+
+#[test]
+fn test_zero_initializer() {
+    let source = r#"
+    struct P { x: i32, y: [i32; 2] }
+    struct Q { p: struct P, n: i32 }
+    let G: struct P = {};
+    fn main() -> i32 {
+        let n: i32 = 3;
+        let a: [i32; 3] = {};
+        let p: struct P = {};
+        let q: struct Q = { {}, n };
+        let ps: [struct P; 2] = { {}, { 1, { 2, 3 } } };
+        return a[0] + p.x + q.n;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    // 按变量定义找到其后的 `{}`，初始值范围包含前导空格
+    let init_value = |def: &str| {
+        let start = (source.find(def).unwrap() + def.len()) as u32;
+        module
+            .get_value_by_range(tools::TextRange::new(start, start + 3))
+            .cloned()
+    };
+    assert!(matches!(
+        init_value("let a: [i32; 3] ="),
+        Some(Value::Array(crate::array::ArrayTree::Val(
+            crate::array::ArrayTreeValue::Empty
+        )))
+    ));
+    assert!(matches!(
+        init_value("let p: struct P ="),
+        Some(Value::StructZero(_))
+    ));
+    assert!(matches!(
+        init_value("let G: struct P ="),
+        Some(Value::StructZero(_))
+    ));
+    assert!(matches!(
+        init_value("let q: struct Q = {"),
+        Some(Value::StructZero(_))
+    ));
+
+    for (ty, found) in [
+        ("i32", "zero initializer `{}`"),
+        ("*mut i32", "zero initializer `{}`"),
+        ("bool", "zero initializer `{}`"),
+    ] {
+        let source = format!("fn main() -> i32 {{ let x: {ty} = {{}}; return 0; }}");
+        let module = analyze(&source);
+        let [AnalyzeError::InitializerMismatch { found: f, .. }] =
+            module.semantic_errors.as_slice()
+        else {
+            panic!("{ty}: {:?}", module.semantic_errors);
+        };
+        assert_eq!(f, found);
+    }
+}
+
+#[test]
+fn test_unconditional_recursion() {
+    let warned = |source: &str| {
+        let module = analyze(source);
+        module
+            .semantic_errors
+            .iter()
+            .filter_map(|e| match e {
+                AnalyzeError::UnconditionalRecursion { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let source = r#"
+    fn forever(n: i32) -> i32 {
+        let m: i32 = n + 1;
+        return forever(m);
+    }
+    fn in_cond(n: i32) -> i32 {
+        if (in_cond(n - 1) > 0) { return 1; }
+        return 0;
+    }
+    fn both(n: i32) -> i32 {
+        if (n > 0) { return both(n - 1); } else { return both(n + 1); }
+    }
+    fn main() -> i32 { return 0; }
+    "#;
+    assert_eq!(warned(source), ["forever", "in_cond", "both"]);
+
+    // 有不递归的分支、递归在短路运算右边或者在可能的 return 之后，都不警告
+    let source = r#"
+    fn fact(n: i32) -> i32 {
+        if (n <= 1) { return 1; }
+        return n * fact(n - 1);
+    }
+    fn ternary(n: i32) -> i32 {
+        if (n == 0) { return 0; } else { return ternary(n - 1); }
+    }
+    fn short(n: i32) -> bool {
+        return n > 0 && short(n - 1);
+    }
+    fn in_loop(n: i32) -> i32 {
+        while (n > 0) { in_loop(n - 1); }
+        return 0;
+    }
+    fn main() -> i32 { return fact(5) + ternary(1) + in_loop(1); }
+    "#;
+    assert!(warned(source).is_empty());
+
+    // 只是警告
+    let module = analyze("fn f() -> i32 { return f(); } fn main() -> i32 { return 0; }");
+    assert_eq!(
+        miette::Diagnostic::severity(&module.semantic_errors[0]),
+        Some(miette::Severity::Warning)
+    );
+}
+
+#[test]
+fn test_mutually_recursive_structs() {
+    let source = r#"
+    struct B;
+    struct A { b: *mut struct B, v: i32 }
+    struct B { a: *mut struct A }
+    struct C;
+    struct C;
+    fn main() -> i32 {
+        let a: struct A = { null, 1 };
+        let b: struct B = { &a };
+        a.b = &b;
+        let c: *mut struct C = null;
+        let cc: *mut *mut struct C = &c;
+        return b.a->b->a->v;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    // 前向声明不会产生新的 struct，定义中的字段保持不变
+    let b = module.get_struct_id_by_name("B").unwrap();
+    let b = module.get_struct_by_id(b).unwrap();
+    assert_eq!(b.fields.len(), 1);
+    assert_eq!(module.structs.len(), 3);
+
+    // 没有定义的 struct 只能通过指针使用
+    let module = analyze(
+        r#"
+    struct C;
+    struct D { c: struct C }
+    fn f(c: struct C) {}
+    fn main() -> i32 {
+        let c: struct C;
+        let arr: [struct C; 2];
+        let p: *mut struct C = null;
+        let n: u64 = sizeof(struct C);
+        return p->x;
+    }
+    "#,
+    );
+    let incomplete = module
+        .semantic_errors
+        .iter()
+        .filter(|e| matches!(e, AnalyzeError::IncompleteStruct { name, .. } if name == "C"))
+        .count();
+    assert_eq!(incomplete, 6, "{:?}", module.semantic_errors);
+    assert!(
+        !module
+            .semantic_errors
+            .iter()
+            .any(|e| matches!(e, AnalyzeError::FieldNotFound { .. })),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    // 不用前向声明同样可以互相引用，重复定义仍然报错
+    let module = analyze(
+        "struct A { b: *mut struct B } struct B { a: *mut struct A } struct A; struct B { x: i32 }",
+    );
+    assert!(matches!(
+        module.semantic_errors.as_slice(),
+        [AnalyzeError::StructDefined { name, .. }] if name == "B"
+    ));
+}
+
+#[test]
+fn test_pointer_const_assignment() {
+    // `*const T` 是常量指针，`*mut const T` 才是指向常量的指针
+    let cases = [
+        // 指针自身的 const 不影响赋值
+        ("*mut i32", "*const i32", true),
+        ("*const i32", "*mut i32", true),
+        // 指向的类型可以加 const，不能去掉
+        ("*mut const i32", "*mut i32", true),
+        ("*mut i32", "*mut const i32", false),
+        ("*mut i32", "*const const i32", false),
+        // 内层的 const 必须一致，只有指向的那一层可以加 const
+        ("*mut *const i32", "*mut *mut i32", true),
+        ("*mut *mut i32", "*mut *const i32", false),
+        ("*mut *mut const i32", "*mut *mut i32", false),
+        ("*mut *mut i32", "*mut *mut const i32", false),
+        ("*const *const const i32", "*mut *const const i32", true),
+        ("*mut *mut *const i32", "*mut *mut *mut i32", false),
+        // void 指针不受限制
+        ("*mut void", "*mut const i32", true),
+        ("*mut *mut i32", "*mut void", true),
+    ];
+    for (dst, src, ok) in cases {
+        let source = format!("fn f(src: {src}) {{ let dst: {dst} = src; }}");
+        let module = analyze(&source);
+        if ok {
+            assert!(
+                module.semantic_errors.is_empty(),
+                "{dst} = {src}: {:?}",
+                module.semantic_errors
+            );
+        } else {
+            assert!(
+                matches!(
+                    module.semantic_errors.as_slice(),
+                    [AnalyzeError::TypeMismatch { expected, found, .. }]
+                        if expected.to_string() == dst && found.to_string() == src
+                ),
+                "{dst} = {src}: {:?}",
+                module.semantic_errors
+            );
+        }
+    }
+}
+
+#[test]
+fn test_deny_warnings() {
+    // 未使用的函数只是提示，不影响编译
+    let source = r#"
+    fn unused() -> i32 { return 2; }
+    fn main() -> i32 { return 0; }
+    "#;
+    let build = |deny_warnings| {
+        let vfs = Vfs::default();
+        let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+        let mut project = Project::new()
+            .with_config(AnalyzeConfig {
+                deny_warnings,
+                ..Default::default()
+            })
+            .with_checker::<UnusedFunctionChecker>();
+        project.full_initialize(&vfs);
+        (project, file_id)
+    };
+
+    let (project, file_id) = build(false);
+    assert!(!project.modules[&file_id].has_errors());
+    let diagnostics = project.diagnostics(file_id);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, miette::Severity::Advice);
+
+    let (project, file_id) = build(true);
+    let module = &project.modules[&file_id];
+    assert!(module.has_errors());
+    // 错误本身的严重程度不变，只在诊断中提升
+    assert_eq!(
+        miette::Diagnostic::severity(&module.semantic_errors[0]),
+        Some(miette::Severity::Advice)
+    );
+    let diagnostics = project.diagnostics(file_id);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "E0212");
+    assert_eq!(diagnostics[0].severity, miette::Severity::Error);
+
+    // 没有诊断时不受影响
+    let module = analyze_with_config(
+        "fn main() -> i32 { return 0; }",
+        AnalyzeConfig {
+            deny_warnings: true,
+            ..Default::default()
+        },
+    );
+    assert!(!module.has_errors());
+}
+
+#[test]
+fn test_array_len() {
+    let source = r#"
+    struct S { len: i32, data: [i32; 4] }
+    fn main() -> i32 {
+        let a: [i32; 5];
+        let m: [[i32; 3]; 2];
+        let s: struct S = { 1, {} };
+        let n: u64 = a.len;
+        static_assert(m.len == 2u64 && m[0].len == 3u64);
+        static_assert(s.data.len == 4u64);
+        let b: [i32; a.len];
+        return s.len;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let start = source.find("a.len").unwrap() as u32;
+    let range = tools::TextRange::new(start - 1, start + 5);
+    assert_eq!(module.get_value_by_range(range), Some(&Value::U64(5)));
+
+    let source = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        let p: *mut i32 = &x;
+        let a: [i32; 5];
+        a.len = 3u64;
+        return x.len + p.len;
+    }
+    "#;
+    let module = analyze(source);
+    let errors: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| (e.error_code(), e.to_string()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                "E0404",
+                "can't assign to const variable 'field'".to_string()
+            ),
+            (
+                "E0323",
+                "`.len` is only available on arrays with a known size, found i32".to_string()
+            ),
+            (
+                "E0323",
+                "`.len` is only available on arrays with a known size, found *mut i32".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_constant_condition() {
+    let source = r#"
+    let DEBUG: const bool = false;
+    fn main() -> i32 {
+        let x: i32 = 0;
+        if (0) { x = 1; }
+        if (1 + 1 == 2) { x = 2; } else { x = 3; }
+        while (false) { x = 4; }
+        for (; 0; ) { x = 5; }
+        while (1) { break; }
+        for (; true; ) { break; }
+        if (x > 0) { x = 6; }
+        if (DEBUG) { x = 7; }
+        if (cfg("debug")) { x = 8; }
+        return x;
+    }
+    "#;
+    let module = analyze(source);
+    let found: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| match e {
+            AnalyzeError::ConstantCondition { value, range } => {
+                (*value, &source[range.start().into()..range.end().into()])
+            }
+            e => panic!("unexpected error {e:?}"),
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (false, "0"),
+            (true, "1 + 1 == 2"),
+            (false, "false"),
+            (false, "0"),
+            // const 变量和 cfg(...) 折叠成常量，同样提示
+            (false, "DEBUG"),
+            (false, "cfg(\"debug\")")
+        ]
+    );
+    assert_eq!(
+        miette::Diagnostic::severity(&module.semantic_errors[0]),
+        Some(miette::Severity::Advice)
+    );
+}
+
+#[test]
+fn test_repeat_array_init() {
+    let source = r#"
+    let N: const i32 = 3;
+    struct P { x: i32, y: i32 }
+    let A: [const i32; 3] = { 7; N };
+    let B: [[const i32; 2]; 3] = { { 4; 2 }; 2 };
+    let C: [const i32; 4] = { 1 + 1; 2 };
+    let D: [struct P; 2] = { { 1, 2 }; 2 };
+    let a0: const i32 = A[0];
+    let a2: const i32 = A[2];
+    let b11: const i32 = B[1][1];
+    let b21: const i32 = B[2][1];
+    let c1: const i32 = C[1];
+    let c3: const i32 = C[3];
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let (_, var) = module.variables.iter().find(|(_, v)| v.name == name)?;
+        module.value_table.get(&var.range).cloned()
+    };
+    assert_eq!(value_of("a0"), Some(Value::I32(7)));
+    assert_eq!(value_of("a2"), Some(Value::I32(7)));
+    assert_eq!(value_of("b11"), Some(Value::I32(4)));
+    // 重复次数少于长度时其余元素为零
+    assert_eq!(value_of("b21"), Some(Value::I32(0)));
+    assert_eq!(value_of("c1"), Some(Value::I32(2)));
+    assert_eq!(value_of("c3"), Some(Value::I32(0)));
+
+    let Some(Value::Array(ArrayTree::Children(children))) = value_of("A") else {
+        panic!("A is not a constant array");
+    };
+    assert_eq!(children.len(), 3);
+    for child in &children {
+        let ArrayTree::Val(leaf) = child else {
+            panic!("{child:?}");
+        };
+        assert_eq!(
+            leaf.get_const_value(&module.value_table),
+            Some(&Value::I32(7))
+        );
+    }
+}
+
+#[test]
+fn test_repeat_array_init_errors() {
+    use crate::array::ArrayInitError;
+
+    let array_error = |init: &str| {
+        let source =
+            format!("fn main() -> i32 {{ let i: i32 = 1; let a: [i32; 3] = {init}; return 0; }}");
+        let module = analyze(&source);
+        match module.semantic_errors.as_slice() {
+            [AnalyzeError::ArrayError { message, .. }] => message.to_string(),
+            errors => panic!("{init}: {errors:?}"),
+        }
+    };
+
+    for (init, count) in [("{ 0; 4 }", 4), ("{ 0; 0 - 1 }", -1)] {
+        assert_eq!(
+            array_error(init),
+            ArrayInitError::RepeatCountOutOfRange {
+                count,
+                len: 3,
+                range: Default::default()
+            }
+            .to_string()
+        );
+    }
+    assert_eq!(
+        array_error("{ 0; i }"),
+        ArrayInitError::NonConstRepeatCount {
+            range: Default::default()
+        }
+        .to_string()
+    );
+    for init in ["{ i; 3 }", "{ [1] = 0; 3 }"] {
+        assert_eq!(
+            array_error(init),
+            ArrayInitError::InvalidRepeatValue {
+                range: Default::default()
+            }
+            .to_string()
+        );
+    }
+
+    // 重复初始化只能用于数组
+    let module =
+        analyze("struct P { x: i32 } fn main() -> i32 { let p: struct P = { 0; 1 }; return 0; }");
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::InitializerMismatch { found, .. }] if found == "array repeat count"
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_not_indexable() {
+    let source = r#"
+    struct S { x: i32, data: [i32; 2] }
+    fn main() -> i32 {
+        let a: [i32; 3] = {1, 2, 3};
+        let p: *mut i32 = a;
+        let s: struct S = { 1, {} };
+        return a[0] + p[1] + s.data[1];
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    struct S { x: i32 }
+    fn main() -> i32 {
+        let x: i32 = 1;
+        let s: struct S = { 1 };
+        return x[0] + s.x[1];
+    }
+    "#;
+    let module = analyze(source);
+    let errors: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .filter_map(|e| match e {
+            AnalyzeError::NotIndexable { ty, range } => Some((
+                ty.to_string(),
+                &source[std::ops::Range::<usize>::from(*range)],
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![("i32".to_string(), "x"), ("i32".to_string(), "x")],
+        "{:?}",
+        module.semantic_errors
+    );
+    assert_eq!(module.semantic_errors[0].error_code(), "E0324");
+}
+
+#[test]
+fn test_global_address_init() {
+    let source = r#"
+    let g: i32 = 3;
+    let arr: [i32; 4];
+    let p: *mut i32 = &g;
+    let q: *const i32 = (&g);
+    let pa: *mut [i32; 4] = &arr;
+    fn main() -> i32 {
+        return *p + *q;
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+
+    let source = r#"
+    let g: i32 = 3;
+    let p: *mut i32 = &g + 1;
+    let x: i32 = *(&g);
+    "#;
+    let module = analyze(source);
+    let codes: Vec<_> = module
+        .semantic_errors
+        .iter()
+        .map(|e| e.error_code())
+        .collect();
+    assert_eq!(
+        codes,
+        vec!["E0401", "E0401"],
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_analyze_profile() {
+    use crate::profile::ProfilePhase;
+
+    let mut source = String::from(
+        "const fn square(x: i32) -> i32 { return x * x; }\nlet N: const i32 = 4 + 4;\n",
+    );
+    for i in 0..50 {
+        source.push_str(&format!(
+            "fn f{i}(a: i32) -> i32 {{\n    let arr: [i32; N];\n    let s: i32 = square(3) + -{i};\n    \
+             while (s < a) {{ s = s + arr[0]; }}\n    return s;\n}}\n"
+        ));
+    }
+    let (tree, errors) = Parser::new(&source).parse();
+    assert!(errors.is_empty(), "{errors:?}");
+    let build = |profile| {
+        let mut module = Module::new(tree.clone());
+        module.config = std::sync::Arc::new(AnalyzeConfig {
+            profile,
+            ..Default::default()
+        });
+        Project::allocate_module_symbols(&mut module);
+        let result = module.analyze();
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{:?}",
+            module.semantic_errors
+        );
+        result
+    };
+
+    assert!(build(false).is_none());
+    let profile = build(true).unwrap();
+    for (phase, stat) in profile.iter() {
+        assert!(stat.count > 0, "{phase:?} not recorded\n{profile}");
+    }
+    assert!(profile.get(ProfilePhase::TypeInference).count > 50);
+    assert!(profile.total() > std::time::Duration::ZERO);
+}
+
+#[test]
+fn test_mixed_width_const_folding() {
+    // 没有显式转换语法，不同宽度的常量按隐式扩展后的类型折叠
+    let source = r#"
+    let A: const i64 = 5 + 3i64;
+    let B: const i64 = 5i8 + 3i64;
+    let C: const u64 = 250u8 + 10u64;
+    let D: const i32 = 127i8 + 1;
+    "#;
+    let module = analyze(source);
+    assert!(
+        module.semantic_errors.is_empty(),
+        "{:?}",
+        module.semantic_errors
+    );
+    let value_of = |name: &str| {
+        let var = module
+            .variables
+            .iter()
+            .find(|(_, v)| v.name == name)
+            .unwrap()
+            .1;
+        module.get_value_by_range(var.range).cloned()
+    };
+    assert_eq!(value_of("A"), Some(Value::I64(8)));
+    assert_eq!(value_of("B"), Some(Value::I64(8)));
+    assert_eq!(value_of("C"), Some(Value::U64(260)));
+    assert_eq!(value_of("D"), Some(Value::I32(128)));
+}
+
+#[test]
+fn test_empty_source() {
+    for source in [
+        "",
+        " \t\r\n\n",
+        "// only a comment",
+        "/* block */\n// line\n",
+    ] {
+        let module = analyze(source);
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{source:?}: {:?}",
+            module.semantic_errors
+        );
+
+        let vfs = Vfs::default();
+        let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+        let mut project = Project::default();
+        project.full_initialize(&vfs);
+        assert!(project.diagnostics(file_id).is_empty(), "{source:?}");
+    }
+}
+
+#[test]
+fn test_allow_unused_variable() {
+    let source = r#"
+    let GLOBAL: i32 = 0;
+    fn f(param: i32) -> i32 {
+        let used: i32 = 1;
+        let _ignored: i32 = 2;
+        let a: i32 = 3; // nanoc:allow(unused_variable)
+        // nanoc:allow(unused_variable)
+        let b: i32 = 4;
+        let c: i32 = 5; // nanoc:allow(unused_function)
+        let d: i32 = 6;
+        /* nanoc:allow(constant_condition, unused_variable) */ let e: i32 = 7;
+        return used;
+    }
+    "#;
+    let vfs = Vfs::default();
+    let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+    let mut project = Project::new().with_checker::<UnusedVariableChecker>();
+    project.full_initialize(&vfs);
+
+    // 无关的 allow 不起作用；单独成行的注释作用于下一行
+    let unused: Vec<_> = project.modules[&file_id]
+        .semantic_errors
+        .iter()
+        .map(|e| match e {
+            AnalyzeError::UnusedVariable { name, .. } => name.as_str(),
+            e => panic!("unexpected {e:?}"),
+        })
+        .collect();
+    assert_eq!(unused, vec!["c", "d"]);
+    assert_eq!(
+        project.modules[&file_id].semantic_errors[0].error_code(),
+        "E0216"
+    );
+}
+
+#[test]
+fn test_allow_does_not_suppress_errors() {
+    let source = r#"
+    fn main() -> i32 {
+        if (1) {} // nanoc:allow(constant_condition)
+        return x; // nanoc:allow(variable_undefined)
+    }
+    "#;
+    let module = analyze(source);
+    assert!(
+        matches!(
+            module.semantic_errors.as_slice(),
+            [AnalyzeError::VariableUndefined { .. }]
+        ),
+        "{:?}",
+        module.semantic_errors
+    );
+}
+
+#[test]
+fn test_main_signature() {
+    for source in [
+        "fn main() -> i32 { return 0; }",
+        "fn main() { }",
+        "fn main() -> i64 { return 0i64; }",
+        "fn main() -> u8 { return 0u8; }",
+    ] {
+        let module = analyze(source);
+        assert!(
+            module.semantic_errors.is_empty(),
+            "{source}: {:?}",
+            module.semantic_errors
+        );
+        assert!(module.has_main());
+    }
+
+    let source = "fn main() -> *mut i32 { return null; }";
+    let module = analyze(source);
+    let [AnalyzeError::InvalidMainSignature { ty, range }] = module.semantic_errors.as_slice()
+    else {
+        panic!("{:?}", module.semantic_errors);
+    };
+    assert_eq!(ty.to_string(), "*mut i32");
+    assert_eq!(&source[std::ops::Range::<usize>::from(*range)], "*mut i32");
+    assert_eq!(module.semantic_errors[0].error_code(), "E0510");
+
+    let module = analyze("fn main() -> bool { return true; }");
+    let [err @ AnalyzeError::InvalidMainSignature { ty: Ty::Bool, .. }] =
+        module.semantic_errors.as_slice()
+    else {
+        panic!("{:?}", module.semantic_errors);
+    };
+    assert_eq!(
+        err.to_string(),
+        "'main' must return an integer type or void, found bool"
+    );
+
+    // 只有声明的 main 不算入口
+    assert!(!analyze("fn main() -> i32;").has_main());
+}
+
+#[test]
+fn test_missing_main() {
+    let build = |source: &str, executable| {
+        let vfs = Vfs::default();
+        let file_id = vfs.new_file(PathBuf::from("test.airy"), source.to_string());
+        let mut project = Project::new().with_config(AnalyzeConfig {
+            executable,
+            ..Default::default()
+        });
+        project.full_initialize(&vfs);
+        project.modules.remove(&file_id).unwrap().semantic_errors
+    };
+
+    let source = "fn helper() -> i32 { return 1; }";
+    let errors = build(source, true);
+    assert!(
+        matches!(errors.as_slice(), [AnalyzeError::MissingMain { .. }]),
+        "{errors:?}"
+    );
+    assert_eq!(
+        miette::Diagnostic::severity(&errors[0]),
+        Some(miette::Severity::Advice)
+    );
+    // 针对整个文件，不指向具体位置
+    assert!(miette::Diagnostic::labels(&errors[0]).is_none());
+    // 编译库时不需要 main
+    assert!(build(source, false).is_empty());
+    assert!(build("fn main() -> i32 { return 0; }", true).is_empty());
+}
@@ -1,4 +1,7 @@
-use crate::{module::StructID, value::Value};
+use crate::{
+    module::{Module, StructID},
+    value::Value,
+};
 use std::fmt::{self};
 use syntax::SyntaxKind;
 
@@ -11,7 +14,7 @@ pub enum UnaryOpError {
     InvalidOp,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Ty {
     I32,
     I8,
@@ -146,14 +149,18 @@ impl Ty {
         }
     }
 
-    /// *mut *const i8 == *const *mut const i8
-    // FIXME: inner 是数组可能出错
-    pub fn is_same_pointer_without_const(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Pointer { pointee: p0, .. }, Self::Pointer { pointee: p1, .. }) => {
-                p0.is_same_pointer_without_const(p1)
-            }
-            _ => self.unwrap_const() == other.unwrap_const(),
+    /// 去掉自身的 const（`const` 包装、指针的 `*const`、数组元素的 const），内层保持不变
+    ///
+    /// `*const *mut const i8` => `*mut *mut const i8`
+    pub fn without_own_const(&self) -> Ty {
+        match self {
+            Self::Const(inner) => inner.without_own_const(),
+            Self::Pointer { pointee, .. } => Self::Pointer {
+                pointee: pointee.clone(),
+                is_const: false,
+            },
+            Self::Array(inner, size) => Self::Array(Box::new(inner.without_own_const()), *size),
+            _ => self.clone(),
         }
     }
 
@@ -179,11 +186,14 @@ impl Ty {
             (Ty::U32, Ty::U8) => true,
             (Ty::U64, Ty::U8 | Ty::U32) => true,
 
-            // 指针类型：*void 可以与任何指针互转
+            // 指针类型：*void 可以与任何指针互转；
+            // 指针自身的 const 不影响赋值，指向的类型只能加 const，更内层的 const 必须一致，
+            // 否则 `*mut *mut i32` 当作 `*mut *mut const i32` 就能把 const 对象的地址写进 `*mut i32`
             (Ty::Pointer { pointee: p1, .. }, Ty::Pointer { pointee: p2, .. }) => {
                 matches!(p1.as_ref(), Ty::Void)
                     || matches!(p2.as_ref(), Ty::Void)
-                    || p1.is_same_pointer_without_const(p2)
+                    || ((p1.is_const() || !p2.is_const())
+                        && p1.without_own_const() == p2.without_own_const())
             }
 
             (Ty::Struct { id: id1, .. }, Ty::Struct { id: id2, .. }) => id1 == id2,
@@ -197,6 +207,9 @@ impl Ty {
     /// 计算二元表达式的结果类型  
     /// 支持整数类型之间的隐式类型转换（Rust 风格：只允许无损扩展）
     /// 结果总是非 const
+    ///
+    /// codegen 按这里的结果生成指令，返回 `Some` 的组合都必须能编译，
+    /// 返回 `None` 时 analyzer 报 `BinaryOpTypeMismatch`（有 void 操作数时报 `VoidInExpression`）
     pub fn compute_binary_result_type(lhs: &Ty, rhs: &Ty, op: SyntaxKind) -> Option<Ty> {
         use SyntaxKind::*;
 
@@ -204,6 +217,11 @@ impl Ty {
         let lhs_unwrapped = lhs.unwrap_const();
         let rhs_unwrapped = rhs.unwrap_const();
 
+        // void 没有值，不能参与任何运算
+        if lhs_unwrapped == Ty::Void || rhs_unwrapped == Ty::Void {
+            return None;
+        }
+
         match op {
             // 算术运算符: +, -, *, /, %
             PLUS | MINUS | STAR | SLASH | PERCENT => {
@@ -247,22 +265,29 @@ impl Ty {
             }
 
             // 比较运算符: <, >, <=, >=, ==, !=
-            LT | GT | LTEQ | GTEQ | EQEQ | NEQ => match (&lhs_unwrapped, &rhs_unwrapped) {
-                // 整数/bool 比较：允许混合（但有符号和无符号不能混合）
-                (Ty::I64 | Ty::I32 | Ty::I8 | Ty::Bool, Ty::I64 | Ty::I32 | Ty::I8 | Ty::Bool) => {
-                    Some(Ty::Bool)
-                }
-                (Ty::U64 | Ty::U8 | Ty::U32 | Ty::Bool, Ty::U64 | Ty::U8 | Ty::U32 | Ty::Bool) => {
-                    Some(Ty::Bool)
-                }
+            op if op.is_comparison_op() => match (&lhs_unwrapped, &rhs_unwrapped) {
                 // 指针比较
                 (l, r) if l.is_pointer() && r.is_pointer() => Some(Ty::Bool),
+                // 整数/bool 比较：codegen 先把两边提升到同一类型再比较，
+                // 无法提升的组合（有符号和无符号混合）在这里直接拒绝
+                _ => Ty::compute_promotion_type(lhs, rhs).map(|_| Ty::Bool),
+            },
 
+            // 移位运算符: <<, >>，结果类型只由左操作数决定（bool 提升到 i32），
+            // 移位数可以是任意整数类型，不参与类型提升
+            op if op.is_shift_op() => match (&lhs_unwrapped, &rhs_unwrapped) {
+                (
+                    l @ (Ty::I32 | Ty::I8 | Ty::U8 | Ty::U32 | Ty::I64 | Ty::U64),
+                    Ty::I32 | Ty::I8 | Ty::U8 | Ty::U32 | Ty::I64 | Ty::U64 | Ty::Bool,
+                ) => Some(l.clone()),
+                (Ty::Bool, Ty::I32 | Ty::I8 | Ty::U8 | Ty::U32 | Ty::I64 | Ty::U64 | Ty::Bool) => {
+                    Some(Ty::I32)
+                }
                 _ => None,
             },
 
             // 逻辑运算符: &&, ||
-            AMPAMP | PIPEPIPE => match (&lhs_unwrapped, &rhs_unwrapped) {
+            op if op.is_logical_op() => match (&lhs_unwrapped, &rhs_unwrapped) {
                 // 接受整数类型，返回 bool
                 (
                     Ty::I32 | Ty::I8 | Ty::U8 | Ty::U32 | Ty::I64 | Ty::U64 | Ty::Bool,
@@ -310,6 +335,22 @@ impl Ty {
         }
     }
 
+    /// 两个分支结果的共同类型：相同类型、整数提升，或者指针与 null（`*void`）
+    ///
+    /// 分支的值不带 const，结果也去掉 const；不兼容时返回 None
+    pub fn common_type(a: &Ty, b: &Ty) -> Option<Ty> {
+        let a = a.unwrap_const();
+        let b = b.unwrap_const();
+        if a == b {
+            return (a != Ty::Void).then_some(a);
+        }
+        match (&a, &b) {
+            (Ty::Pointer { .. }, Ty::Pointer { .. }) if b.is_void_pointer() => Some(a),
+            (Ty::Pointer { .. }, Ty::Pointer { .. }) if a.is_void_pointer() => Some(b),
+            _ => Self::compute_promotion_type(&a, &b),
+        }
+    }
+
     /// 验证一元操作符并计算结果类型        
     /// 结果总是非 const
     ///
@@ -350,6 +391,12 @@ impl Ty {
                 // 检查是否为 void 指针
                 if matches!(pointee.as_ref(), Ty::Void) {
                     Err(UnaryOpError::VoidPointerDeref)
+                } else if let Ty::Array(inner, _) = pointee.unwrap_const() {
+                    // 解引用数组指针得到数组，与数组变量一样 decay 成指向元素的指针
+                    Ok(Ty::Pointer {
+                        pointee: inner,
+                        is_const: true,
+                    })
                 } else {
                     Ok((**pointee).clone())
                 }
@@ -380,4 +427,45 @@ impl Ty {
             _ => false,
         }
     }
+
+    /// 整数类型的位宽，bool 和非整数类型返回 None
+    pub fn int_bits(&self) -> Option<u32> {
+        match self.unwrap_const() {
+            Ty::I8 | Ty::U8 => Some(8),
+            Ty::I32 | Ty::U32 => Some(32),
+            Ty::I64 | Ty::U64 => Some(64),
+            _ => None,
+        }
+    }
+
+    /// 类型占用的字节数和对齐，与 codegen 生成的 LLVM 类型布局一致（struct 不压缩，按 C 规则填充）
+    ///
+    /// void、大小未知的数组和找不到定义的 struct 返回 None
+    pub fn size_and_align(&self, module: &Module) -> Option<(u64, u64)> {
+        match self {
+            Ty::I8 | Ty::U8 | Ty::Bool => Some((1, 1)),
+            Ty::I32 | Ty::U32 => Some((4, 4)),
+            Ty::I64 | Ty::U64 | Ty::Pointer { .. } => Some((8, 8)),
+            Ty::Void => None,
+            Ty::Array(elem, size) => {
+                let (elem_size, align) = elem.size_and_align(module)?;
+                Some((elem_size * u64::try_from((*size)?).ok()?, align))
+            }
+            Ty::Struct { id, .. } => {
+                let def = module.get_struct_by_id(*id)?;
+                let mut size = 0u64;
+                let mut max_align = 1;
+                for field_id in def.fields {
+                    let (field_size, align) = module
+                        .get_field_by_id(field_id)?
+                        .ty
+                        .size_and_align(module)?;
+                    size = size.next_multiple_of(align) + field_size;
+                    max_align = max_align.max(align);
+                }
+                Some((size.next_multiple_of(max_align), max_align))
+            }
+            Ty::Const(inner) => inner.size_and_align(module),
+        }
+    }
 }
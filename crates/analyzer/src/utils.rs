@@ -7,7 +7,7 @@ use tools::TextRange;
 use crate::{
     array::ArrayTree,
     error::AnalyzeError,
-    module::{Module, StructID},
+    module::{Module, ReferenceTag, StructID},
     r#type::Ty,
     value::Value,
 };
@@ -24,6 +24,16 @@ pub fn parse_type_node(
     module: &Module,
     ty_node: &Type,
     value_table: Option<&HashMap<TextRange, Value>>,
+) -> Result<Option<Ty>, AnalyzeError> {
+    parse_type(module, ty_node, value_table, false)
+}
+
+/// `behind_pointer` 为 true 时类型是指针指向的类型，允许只有前向声明的 struct
+fn parse_type(
+    module: &Module,
+    ty_node: &Type,
+    value_table: Option<&HashMap<TextRange, Value>>,
+    behind_pointer: bool,
 ) -> Result<Option<Ty>, AnalyzeError> {
     if ty_node.l_brack_token().is_some() {
         // 数组类型: [Type; Expr]
@@ -31,7 +41,7 @@ pub fn parse_type_node(
             return Ok(None);
         };
 
-        let Some(inner) = parse_type_node(module, &inner_node, value_table)? else {
+        let Some(inner) = parse_type(module, &inner_node, value_table, false)? else {
             return Ok(None);
         };
 
@@ -55,6 +65,10 @@ pub fn parse_type_node(
                                 range: utils::trim_node_text_range(&expr_node),
                             }
                         })?),
+                        // 没有类型说明表达式本身已经报错（例如变量未定义），不再重复报告
+                        None if module.get_expr_type(expr_range).is_none() => return Ok(None),
+                        // 常量折叠溢出已经报告 ConstArithmeticOverflow，不再构造数组类型
+                        None if module.has_const_overflow_in(expr_range) => return Ok(None),
                         None => {
                             return Err(AnalyzeError::ConstantExprExpected {
                                 range: utils::trim_node_text_range(&expr_node),
@@ -73,7 +87,7 @@ pub fn parse_type_node(
             return Ok(None);
         };
 
-        let Some(inner) = parse_type_node(module, &inner_node, value_table)? else {
+        let Some(inner) = parse_type(module, &inner_node, value_table, true)? else {
             return Ok(None);
         };
 
@@ -114,16 +128,39 @@ pub fn parse_type_node(
                     range: utils::trim_node_text_range(ty_node),
                 });
             };
+            if !behind_pointer
+                && module
+                    .get_struct_by_id(sid)
+                    .is_some_and(|s| s.is_incomplete)
+            {
+                return Err(AnalyzeError::IncompleteStruct {
+                    name,
+                    range: utils::trim_node_text_range(ty_node),
+                });
+            }
 
             Ty::Struct {
                 id: sid,
                 name: name.clone(),
             }
+        } else if let Some(name) = pt_node.name().and_then(|n| n.var_name()) {
+            // 类型别名，无法解析的别名已经报过错
+            match module.type_aliases.get(&name) {
+                Some(Some(ty)) => ty.clone(),
+                Some(None) => return Ok(None),
+                None => {
+                    return Err(AnalyzeError::TypeUndefined {
+                        name,
+                        range: utils::trim_node_text_range(ty_node),
+                    });
+                }
+            }
         } else {
             return Ok(None);
         };
 
-        if ty_node.const_token().is_some() {
+        // 别名本身可能已经是 const
+        if ty_node.const_token().is_some() && !matches!(ntype, Ty::Const(_)) {
             Ok(Some(Ty::Const(Box::new(ntype))))
         } else {
             Ok(Some(ntype))
@@ -132,19 +169,45 @@ pub fn parse_type_node(
 }
 
 impl Module {
+    /// `range` 内的常量折叠是否已经报告过溢出，用于避免再报告"需要常量表达式"
+    pub(crate) fn has_const_overflow_in(&self, range: TextRange) -> bool {
+        self.semantic_errors.iter().any(|e| {
+            matches!(e, AnalyzeError::ConstArithmeticOverflow { range: r, .. }
+                if range.contains_range(**r))
+        })
+    }
+
     /// 计算索引后的类型：去掉 index_count 层数组/指针
     /// 如果结果是数组类型，自动 decay 成指向元素的指针
     pub(crate) fn compute_indexed_type(
         ty: &Ty,
         index_count: usize,
         range: TextRange,
+    ) -> Result<Ty, AnalyzeError> {
+        let current = Self::strip_indexed_type(ty, index_count, range)?;
+        // 如果结果是数组类型，decay 成指向元素的指针
+        if let Ty::Array(inner, _) = current.unwrap_const() {
+            Ok(Ty::Pointer {
+                pointee: inner,
+                is_const: true,
+            })
+        } else {
+            Ok(current)
+        }
+    }
+
+    /// 去掉 index_count 层数组/指针，不做 decay
+    fn strip_indexed_type(
+        ty: &Ty,
+        index_count: usize,
+        range: TextRange,
     ) -> Result<Ty, AnalyzeError> {
         let mut current = ty.clone();
         for _ in 0..index_count {
             current = match current {
                 Ty::Array(inner, _) => *inner,
                 Ty::Pointer { pointee, .. } => *pointee,
-                Ty::Const(inner) => Self::compute_indexed_type(&inner, 1, range)?,
+                Ty::Const(inner) => Self::strip_indexed_type(&inner, 1, range)?,
                 _ => {
                     return Err(AnalyzeError::ApplyOpOnType {
                         ty: current,
@@ -154,15 +217,7 @@ impl Module {
                 }
             };
         }
-        // 如果结果是数组类型，decay 成指向元素的指针
-        if let Ty::Array(inner, _) = current.unwrap_const() {
-            Ok(Ty::Pointer {
-                pointee: inner,
-                is_const: true,
-            })
-        } else {
-            Ok(current)
-        }
+        Ok(current)
     }
 
     /// 解析 struct 初始化列表，返回 Value::Struct
@@ -185,6 +240,29 @@ impl Module {
         // 否则是初始化列表 { init1, init2, ... }
         let inits: Vec<_> = init_val_node.inits().collect();
 
+        // `{}` 显式零初始化所有字段
+        if inits.is_empty() {
+            return Ok(Some(Value::StructZero(struct_id)));
+        }
+
+        // 重复初始化只能用于数组
+        if let Some(repeat) = init_val_node.repeat() {
+            return Err(AnalyzeError::InitializerMismatch {
+                expected: "field initializer".to_string(),
+                found: "array repeat count".to_string(),
+                range: utils::trim_node_text_range(&repeat),
+            });
+        }
+
+        // 下标指示符只能用于数组
+        if let Some(designator) = inits.iter().find_map(InitVal::designator) {
+            return Err(AnalyzeError::InitializerMismatch {
+                expected: "field initializer".to_string(),
+                found: "array designator".to_string(),
+                range: utils::trim_node_text_range(&designator),
+            });
+        }
+
         // 检查初始化列表长度是否与字段数匹配
         if inits.len() != struct_def.fields.len() {
             return Err(AnalyzeError::StructInitFieldCountMismatch {
@@ -269,11 +347,9 @@ impl Module {
             // 数组类型：使用 ArrayTree 解析
             Ty::Array(_, _) => {
                 let range = init_val_node.text_range();
+                let range_trimmed = utils::trim_node_text_range(&init_val_node);
                 let (array_tree, is_const) = ArrayTree::new(self, field_ty, init_val_node)
-                    .map_err(|e| AnalyzeError::ArrayError {
-                        message: Box::new(e),
-                        range,
-                    })?;
+                    .map_err(|e| e.into_analyze_error(range_trimmed))?;
 
                 if !is_const {
                     self.expand_array.insert(range, array_tree);
@@ -302,6 +378,39 @@ impl Module {
         }
     }
 
+    /// 左值本身是数组时返回 decay 之前的数组类型，用于 `&arr` 得到 `*mut [T; N]`
+    ///
+    /// 其他左值返回 None，沿用表达式记录的类型
+    pub(crate) fn array_lvalue_type(&self, expr: &Expr) -> Option<Ty> {
+        let (ty, indices, range) = match expr {
+            Expr::IndexVal(index_val) => {
+                let var_range = index_val.name()?.var_range()?;
+                let ReferenceTag::VarRead(var_id) = self.reference_at(var_range)? else {
+                    return None;
+                };
+                let ty = self.variables.get(*var_id)?.ty.clone();
+                (ty, index_val.indices().count(), index_val.text_range())
+            }
+            Expr::PostfixExpr(postfix) => {
+                let field_access = postfix.field()?;
+                let member_range = field_access.name()?.var_range()?;
+                let ReferenceTag::FieldRead(field_id) = self.reference_at(member_range)? else {
+                    return None;
+                };
+                let ty = self.get_field_by_id(field_id)?.ty.clone();
+                (ty, field_access.indices().count(), postfix.text_range())
+            }
+            _ => return None,
+        };
+        let ty = Self::strip_indexed_type(&ty, indices, range).ok()?;
+        ty.unwrap_const().is_array().then_some(ty)
+    }
+
+    fn reference_at(&self, range: TextRange) -> Option<ReferenceTag> {
+        let ref_id = self.reference_map.get(&range)?;
+        Some(self.get_reference_by_id(*ref_id)?.tag)
+    }
+
     /// 返回 true 如果是有效的左值
     pub(crate) fn is_lvalue_expr(&self, expr: &Expr) -> bool {
         match expr {
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use syntax::SyntaxKind;
 
 use crate::{
@@ -22,6 +24,35 @@ pub enum Value {
     Null,
 }
 
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::I32(v) => write!(f, "{}", v),
+            Value::I8(v) => write!(f, "{}", v),
+            Value::U8(v) => write!(f, "{}", v),
+            Value::U32(v) => write!(f, "{}", v),
+            Value::I64(v) => write!(f, "{}", v),
+            Value::U64(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::String(s) => write!(f, "{:?}", s),
+            // 数组叶子只记录了表达式范围，不展开
+            Value::Array(_) => write!(f, "{{...}}"),
+            Value::Struct(_, fields) => {
+                write!(f, "{{")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", field)?;
+                }
+                write!(f, "}}")
+            }
+            Value::StructZero(_) => write!(f, "{{}}"),
+            Value::Null => write!(f, "null"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum EvalError {
     TypeMismatch,
@@ -95,12 +126,33 @@ macro_rules! impl_binary_ops {
     };
 }
 
+/// 宏：为整数类型生成移位运算的实现，移位数不小于位宽时报错
+macro_rules! impl_shift_ops {
+    ($l:expr, $count:expr, $op:expr, $val_variant:ident) => {
+        match $op {
+            LTLT => $l.checked_shl($count),
+            GTGT => $l.checked_shr($count),
+            _ => unreachable!(),
+        }
+        .map(Value::$val_variant)
+        .ok_or_else(|| {
+            EvalError::UnsupportedOperation(format!("shift count {} out of range", $count))
+        })
+    };
+}
+
 /// 宏：为整数类型生成一元运算的实现
 macro_rules! impl_unary_ops {
-    ($v:expr, $op:expr, $val_variant:ident) => {
+    ($v:expr, $op:expr, $val_variant:ident, $type_name:expr) => {
         match $op {
             PLUS => Ok(Value::$val_variant($v)),
-            MINUS => Ok(Value::$val_variant($v.wrapping_neg())),
+            MINUS => match $v.checked_neg() {
+                Some(result) => Ok(Value::$val_variant(result)),
+                None => Err(EvalError::Overflow(format!(
+                    "-({}) overflows {}",
+                    $v, $type_name
+                ))),
+            },
             _ => unreachable!(),
         }
     };
@@ -268,7 +320,7 @@ impl Value {
         let rhs_ty = rhs.get_type(module);
 
         // 对于逻辑运算，转换为 bool
-        if matches!(op, AMPAMP | PIPEPIPE) {
+        if op.is_logical_op() {
             let l = lhs.cast_to_bool()?;
             let r = rhs.cast_to_bool()?;
             return match op {
@@ -290,8 +342,32 @@ impl Value {
             };
         }
 
+        // 对于移位运算，左操作数提升到结果类型，移位数单独取出，不参与类型提升
+        if op.is_shift_op() {
+            let target_ty = Ty::compute_binary_result_type(&lhs_ty, &rhs_ty, op)
+                .ok_or(EvalError::TypeMismatch)?;
+            let count = match rhs {
+                Value::Bool(v) => u32::from(*v),
+                v => v
+                    .as_i64()
+                    .and_then(|c| u32::try_from(c).ok())
+                    .ok_or_else(|| {
+                        EvalError::UnsupportedOperation("shift count out of range".to_string())
+                    })?,
+            };
+            return match lhs.cast_to_type(&target_ty)? {
+                Value::I32(l) => impl_shift_ops!(l, count, op, I32),
+                Value::I8(l) => impl_shift_ops!(l, count, op, I8),
+                Value::U8(l) => impl_shift_ops!(l, count, op, U8),
+                Value::U32(l) => impl_shift_ops!(l, count, op, U32),
+                Value::I64(l) => impl_shift_ops!(l, count, op, I64),
+                Value::U64(l) => impl_shift_ops!(l, count, op, U64),
+                _ => Err(EvalError::TypeMismatch),
+            };
+        }
+
         // 对于比较运算，需要先提升到共同类型再比较
-        if matches!(op, LT | GT | LTEQ | GTEQ | EQEQ | NEQ) {
+        if op.is_comparison_op() {
             let promoted_ty =
                 Ty::compute_promotion_type(&lhs_ty, &rhs_ty).ok_or(EvalError::TypeMismatch)?;
 
@@ -362,12 +438,12 @@ impl Value {
             }
             // 算术运算：bool 提升到 i32
             PLUS | MINUS => match val {
-                Value::I32(v) => impl_unary_ops!(v, op, I32),
-                Value::I8(v) => impl_unary_ops!(v, op, I8),
-                Value::U8(v) => impl_unary_ops!(v, op, U8),
-                Value::U32(v) => impl_unary_ops!(v, op, U32),
-                Value::I64(v) => impl_unary_ops!(v, op, I64),
-                Value::U64(v) => impl_unary_ops!(v, op, U64),
+                Value::I32(v) => impl_unary_ops!(v, op, I32, "i32"),
+                Value::I8(v) => impl_unary_ops!(v, op, I8, "i8"),
+                Value::U8(v) => impl_unary_ops!(v, op, U8, "u8"),
+                Value::U32(v) => impl_unary_ops!(v, op, U32, "u32"),
+                Value::I64(v) => impl_unary_ops!(v, op, I64, "i64"),
+                Value::U64(v) => impl_unary_ops!(v, op, U64, "u64"),
                 Value::Bool(_) => {
                     let i32_val = val.cast_to_i32()?;
                     Self::eval_unary(i32_val, op)
@@ -378,6 +454,53 @@ impl Value {
         }
     }
 
+    /// 两个整数常量按数值比较，类型可以不同；不是整数或不是比较运算时返回 None
+    pub fn compare_integers(lhs: &Value, rhs: &Value, op: SyntaxKind) -> Option<Value> {
+        use SyntaxKind::*;
+
+        let as_i128 = |v: &Value| match v {
+            Value::U64(v) => Some(i128::from(*v)),
+            v => v.as_i64().map(i128::from),
+        };
+        let (l, r) = (as_i128(lhs)?, as_i128(rhs)?);
+        let result = match op {
+            LT => l < r,
+            GT => l > r,
+            LTEQ => l <= r,
+            GTEQ => l >= r,
+            EQEQ => l == r,
+            NEQ => l != r,
+            _ => return None,
+        };
+        Some(Value::Bool(result))
+    }
+
+    /// 取出整数值（bool 不算整数）
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::I32(v) => Some(*v as i64),
+            Value::I8(v) => Some(*v as i64),
+            Value::U8(v) => Some(*v as i64),
+            Value::U32(v) => Some(*v as i64),
+            Value::I64(v) => Some(*v),
+            Value::U64(v) => i64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// 按整数类型构造值，超出范围或不是整数类型时返回 None
+    pub fn from_i64(v: i64, ty: &Ty) -> Option<Value> {
+        match ty.unwrap_const() {
+            Ty::I32 => i32::try_from(v).ok().map(Value::I32),
+            Ty::I8 => i8::try_from(v).ok().map(Value::I8),
+            Ty::U8 => u8::try_from(v).ok().map(Value::U8),
+            Ty::U32 => u32::try_from(v).ok().map(Value::U32),
+            Ty::I64 => Some(Value::I64(v)),
+            Ty::U64 => u64::try_from(v).ok().map(Value::U64),
+            _ => None,
+        }
+    }
+
     pub fn get_array_size(&self) -> Option<i32> {
         match self {
             Value::I32(v) => Some(*v),
@@ -1,11 +1,12 @@
 use thiserror::Error;
+use tools::TextRange;
 
 pub type Result<T> = std::result::Result<T, CodegenError>;
 
 #[derive(Debug, Error)]
 pub enum CodegenError {
     #[error("missing {0}")]
-    Missing(&'static str),
+    Missing(&'static str, Option<TextRange>),
 
     #[error("LLVM build failed: {0}")]
     LlvmBuild(&'static str),
@@ -17,10 +18,10 @@ pub enum CodegenError {
     UndefinedFunc(String),
 
     #[error("type mismatch: {0}")]
-    TypeMismatch(String),
+    TypeMismatch(String, Option<TextRange>),
 
     #[error("unsupported: {0}")]
-    Unsupported(String),
+    Unsupported(String, Option<TextRange>),
 
     #[error("not implemented: {0}")]
     NotImplemented(&'static str),
@@ -40,3 +41,26 @@ pub enum CodegenError {
     #[error("root node is not CompUnit")]
     InvalidRoot,
 }
+
+impl CodegenError {
+    /// 获取出错的源码位置（如果有）
+    pub fn range(&self) -> Option<TextRange> {
+        match self {
+            Self::Missing(_, range)
+            | Self::TypeMismatch(_, range)
+            | Self::Unsupported(_, range) => *range,
+            _ => None,
+        }
+    }
+
+    /// 为尚未携带位置的错误补上位置，已有的（更内层节点的）位置保持不变
+    pub fn with_range(mut self, node_range: TextRange) -> Self {
+        if let Self::Missing(_, range) | Self::TypeMismatch(_, range) | Self::Unsupported(_, range) =
+            &mut self
+            && range.is_none()
+        {
+            *range = Some(node_range);
+        }
+        self
+    }
+}
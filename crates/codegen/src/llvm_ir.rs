@@ -1,6 +1,9 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 
+use analyzer::r#type::Ty;
 use inkwell::basic_block::BasicBlock;
+use inkwell::types::BasicTypeEnum;
 use inkwell::values::{FunctionValue, GlobalValue, PointerValue};
 use inkwell::{builder::Builder, context::Context};
 use syntax::ast::*;
@@ -11,6 +14,7 @@ mod decl;
 mod expr;
 mod func;
 mod stmt;
+mod target;
 
 /// 变量和函数的符号表
 #[derive(Default)]
@@ -20,6 +24,8 @@ pub struct SymbolTable<'a, 'ctx> {
     pub functions: HashMap<String, FunctionValue<'ctx>>,
     pub globals: HashMap<String, Symbol<'a, 'ctx>>,
     pub loop_stack: Vec<LoopContext<'ctx>>,
+    /// 当前函数内 `Ty` 到 LLVM 类型的转换缓存，进入函数体时清空
+    pub type_cache: RefCell<HashMap<Ty, BasicTypeEnum<'ctx>>>,
 }
 
 pub struct Program<'a, 'ctx> {
@@ -29,6 +35,8 @@ pub struct Program<'a, 'ctx> {
     pub analyzer: &'a analyzer::module::Module,
     pub symbols: SymbolTable<'a, 'ctx>,
     pub string_constants: HashMap<String, GlobalValue<'ctx>>,
+    /// 运行期数组下标越界时调用 `llvm.trap`
+    pub bounds_check: bool,
 }
 
 #[derive(Clone, Copy)]
@@ -61,14 +69,28 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             }
         }
 
+        // 先声明本模块的所有函数，函数体中可以调用定义在后面的函数（包括互相递归）
         for global in node.global_decls() {
+            if let GlobalDecl::FuncDef(func) = &global {
+                let node_range = utils::trim_node_text_range(&global);
+                self.declare_func_def(func)
+                    .map_err(|e| e.with_range(node_range))?;
+            }
+        }
+
+        for global in node.global_decls() {
+            let node_range = utils::trim_node_text_range(&global);
             match global {
-                GlobalDecl::VarDef(decl) => self.compile_var_def(decl)?,
-                GlobalDecl::FuncDef(func) => self.compile_func_def(func)?,
+                GlobalDecl::VarDef(decl) => self
+                    .compile_var_def(decl)
+                    .map_err(|e| e.with_range(node_range))?,
+                GlobalDecl::FuncDef(func) => self
+                    .compile_func_def(func)
+                    .map_err(|e| e.with_range(node_range))?,
                 GlobalDecl::FuncAttach(attach) => {
                     self.compile_func_attach(attach.name(), attach.block())?
                 }
-                GlobalDecl::StructDef(_) => {}
+                GlobalDecl::StructDef(_) | GlobalDecl::EnumDef(_) | GlobalDecl::TypeAlias(_) => {}
             }
         }
         Ok(())
@@ -10,18 +10,20 @@ use crate::llvm_ir::Program;
 
 impl<'a, 'ctx> Program<'a, 'ctx> {
     pub(crate) fn compile_var_def(&mut self, def: VarDef) -> Result<()> {
-        let name_node = def.name().ok_or(CodegenError::Missing("variable name"))?;
+        let name_node = def
+            .name()
+            .ok_or(CodegenError::Missing("variable name", None))?;
         let name = name_node
             .var_name()
-            .ok_or(CodegenError::Missing("variable name"))?;
+            .ok_or(CodegenError::Missing("variable name", None))?;
         let name_range = name_node
             .var_range()
-            .ok_or(CodegenError::Missing("variable range"))?;
+            .ok_or(CodegenError::Missing("variable range", None))?;
 
         let var = self
             .analyzer
             .get_varaible_by_range(name_range)
-            .ok_or(CodegenError::Missing("variable info"))?;
+            .ok_or(CodegenError::Missing("variable info", None))?;
         let var_ty = &var.ty;
         let llvm_ty = self.convert_ntype_to_type(var_ty)?;
 
@@ -32,14 +34,17 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             let is_const = var_ty.is_const();
             let init_val = if is_const {
                 // const 变量必须有初始值
-                let init_node = def.init().ok_or(CodegenError::Missing("initial value"))?;
-                self.get_const_var_value(&init_node, Some(llvm_ty))?
+                let init_node = def
+                    .init()
+                    .ok_or(CodegenError::Missing("initial value", None))?;
+                self.const_init_or_zero(Some(init_node), var_ty, llvm_ty)?
             } else {
-                self.const_init_or_zero(def.init(), llvm_ty)?
+                self.const_init_or_zero(def.init(), var_ty, llvm_ty)?
             };
 
             let global = self.module.add_global(llvm_ty, None, &name);
             global.set_initializer(&init_val);
+            global.set_alignment(Self::natural_alignment(llvm_ty));
             if is_const {
                 global.set_constant(true);
             }
@@ -52,7 +57,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             let func = self
                 .symbols
                 .current_function
-                .ok_or(CodegenError::Missing("current function"))?;
+                .ok_or(CodegenError::Missing("current function", None))?;
             let alloca = self.create_entry_alloca(func, llvm_ty, &name)?;
 
             // 判断变量类型
@@ -69,31 +74,25 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     let expr_ty = self
                         .analyzer
                         .get_expr_type(expr.text_range())
-                        .ok_or(CodegenError::Missing("expr type"))?;
+                        .ok_or(CodegenError::Missing("expr type", None))?;
                     let init_val_casted = self.cast_value(init_val, expr_ty, var_ty)?;
 
-                    self.builder
-                        .build_store(alloca, init_val_casted)
-                        .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                    self.build_aligned_store(alloca, init_val_casted, "store failed")?;
                 } else if ty.is_array() {
                     // 数组初始化列表
                     let array_tree = self
                         .analyzer
                         .expand_array
                         .get(&range)
-                        .ok_or(CodegenError::Missing("array init info"))?;
+                        .ok_or(CodegenError::Missing("array init info", None))?;
 
                     if self.analyzer.is_compile_time_constant(range) {
                         let init_val =
                             self.convert_array_tree_to_global_init(array_tree, llvm_ty)?;
-                        self.builder
-                            .build_store(alloca, init_val)
-                            .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                        self.build_aligned_store(alloca, init_val, "store failed")?;
                     } else {
                         // 非常量数组：先 zero init，再逐个 store
-                        self.builder
-                            .build_store(alloca, llvm_ty.const_zero())
-                            .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                        self.build_aligned_store(alloca, llvm_ty.const_zero(), "store failed")?;
                         let mut indices = vec![self.context.i32_type().const_zero()];
                         // 提取数组元素类型
                         let element_ty = match &ty {
@@ -101,6 +100,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                             _ => {
                                 return Err(CodegenError::TypeMismatch(
                                     "expected array type".into(),
+                                    None,
                                 ));
                             }
                         };
@@ -117,21 +117,17 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     if self.analyzer.is_compile_time_constant(range) {
                         // 常量 struct：直接 store
                         let init_val = self.get_const_var_value(&init_node, Some(llvm_ty))?;
-                        self.builder
-                            .build_store(alloca, init_val)
-                            .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                        self.build_aligned_store(alloca, init_val, "store failed")?;
                     } else {
                         // 非常量 struct：逐字段 store（struct 初始化要求完全覆盖，不需要 zero init）
                         self.store_struct_init(var_ty, init_node, alloca, llvm_ty)?;
                     }
                 } else {
-                    return Err(CodegenError::Unsupported("init list type".into()));
+                    return Err(CodegenError::Unsupported("init list type".into(), None));
                 }
             } else {
                 // 无初始值，zero init
-                self.builder
-                    .build_store(alloca, llvm_ty.const_zero())
-                    .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                self.build_aligned_store(alloca, llvm_ty.const_zero(), "store failed")?;
             }
 
             self.symbols.insert_var(name.to_string(), alloca, var_ty);
@@ -147,9 +143,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         ptr: PointerValue<'ctx>,
         llvm_ty: BasicTypeEnum<'ctx>,
     ) -> Result<()> {
-        let struct_id = struct_ty
-            .as_struct_id()
-            .ok_or(CodegenError::TypeMismatch("expected struct type".into()))?;
+        let struct_id = struct_ty.as_struct_id().ok_or(CodegenError::TypeMismatch(
+            "expected struct type".into(),
+            None,
+        ))?;
         let struct_def = self
             .analyzer
             .get_struct_by_id(struct_id)
@@ -191,24 +188,22 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let expr_ty = self
                     .analyzer
                     .get_expr_type(expr.text_range())
-                    .ok_or(CodegenError::Missing("expr type"))?;
+                    .ok_or(CodegenError::Missing("expr type", None))?;
                 let value_casted = self.cast_value(value, expr_ty, &field.ty)?;
 
-                self.builder
-                    .build_store(field_ptr, value_casted)
-                    .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                self.build_aligned_store(field_ptr, value_casted, "store failed")?;
             } else if inner_field_ty.is_array() {
                 // 数组字段：使用 ArrayTree 解析
                 if self.analyzer.is_compile_time_constant(init.text_range()) {
                     let init_val = self.get_const_var_value(&init, Some(field_llvm_ty))?;
-                    self.builder
-                        .build_store(field_ptr, init_val)
-                        .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                    self.build_aligned_store(field_ptr, init_val, "store failed")?;
                 } else {
                     // 非常量数组需要先 zero init
-                    self.builder
-                        .build_store(field_ptr, field_llvm_ty.const_zero())
-                        .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                    self.build_aligned_store(
+                        field_ptr,
+                        field_llvm_ty.const_zero(),
+                        "store failed",
+                    )?;
                     let mut indices = vec![self.context.i32_type().const_zero()];
                     let array_tree = self.analyzer.expand_array.get(&init.text_range()).unwrap();
                     // 提取数组元素类型
@@ -219,10 +214,16 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                             _ => {
                                 return Err(CodegenError::TypeMismatch(
                                     "expected array type".into(),
+                                    None,
                                 ));
                             }
                         },
-                        _ => return Err(CodegenError::TypeMismatch("expected array type".into())),
+                        _ => {
+                            return Err(CodegenError::TypeMismatch(
+                                "expected array type".into(),
+                                None,
+                            ));
+                        }
                     };
                     self.store_on_array_tree(
                         array_tree,
@@ -236,15 +237,14 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 // 嵌套 struct 字段：递归处理（不需要 zero init）
                 if self.analyzer.is_compile_time_constant(init.text_range()) {
                     let init_val = self.get_const_var_value(&init, Some(field_llvm_ty))?;
-                    self.builder
-                        .build_store(field_ptr, init_val)
-                        .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                    self.build_aligned_store(field_ptr, init_val, "store failed")?;
                 } else {
                     self.store_struct_init(&field.ty, init, field_ptr, field_llvm_ty)?;
                 }
             } else {
                 return Err(CodegenError::Unsupported(
                     "unsupported field init type".into(),
+                    None,
                 ));
             }
         }
@@ -265,14 +265,14 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             ArrayTree::Val(ArrayTreeValue::Expr(expr_range)) => {
                 let syntax_tree = SyntaxNode::new_root(self.analyzer.get_green_tree());
                 let expr = find_node_by_range::<Expr>(&syntax_tree, *expr_range)
-                    .ok_or(CodegenError::Missing("expr node not found"))?;
+                    .ok_or(CodegenError::Missing("expr node not found", None))?;
                 let value = self.compile_expr(expr.clone())?;
 
                 // 获取表达式类型并进行隐式类型转换
                 let expr_ty = self
                     .analyzer
                     .get_expr_type(expr.text_range())
-                    .ok_or(CodegenError::Missing("expr type"))?;
+                    .ok_or(CodegenError::Missing("expr type", None))?;
                 let value_casted = self.cast_value(value, expr_ty, element_ty)?;
 
                 let gep = unsafe {
@@ -280,9 +280,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                         .build_gep(llvm_ty, ptr, indices, "idx.gep")
                         .map_err(|_| CodegenError::LlvmBuild("gep failed"))?
                 };
-                self.builder
-                    .build_store(gep, value_casted)
-                    .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                self.build_aligned_store(gep, value_casted, "store failed")?;
             }
             ArrayTree::Val(ArrayTreeValue::Struct {
                 struct_id: id,
@@ -297,22 +295,22 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     id: *id,
                     name: struct_name,
                 };
-                let llvm_ty = self.convert_ntype_to_type(&struct_ty)?;
+                let struct_llvm_ty = self.convert_ntype_to_type(&struct_ty)?;
+                // 元素指针要按整个数组的类型计算，indices 是从数组起点开始的下标
                 let gep = unsafe {
                     self.builder
                         .build_gep(llvm_ty, ptr, indices, "idx.gep")
                         .map_err(|_| CodegenError::LlvmBuild("gep failed"))?
                 };
-                if let Ok(const_val) = self.get_const_var_value_by_range(*list_range, Some(llvm_ty))
+                if let Ok(const_val) =
+                    self.get_const_var_value_by_range(*list_range, Some(struct_llvm_ty))
                 {
-                    self.builder
-                        .build_store(gep, const_val)
-                        .map_err(|_| CodegenError::LlvmBuild("store failed"))?;
+                    self.build_aligned_store(gep, const_val, "store failed")?;
                 } else {
                     let syntax_tree = SyntaxNode::new_root(self.analyzer.get_green_tree());
                     let list = find_node_by_range::<InitVal>(&syntax_tree, *list_range)
-                        .ok_or(CodegenError::Missing("init_val node not found"))?;
-                    self.store_struct_init(&struct_ty, list, gep, llvm_ty)?;
+                        .ok_or(CodegenError::Missing("init_val node not found", None))?;
+                    self.store_struct_init(&struct_ty, list, gep, struct_llvm_ty)?;
                 }
             }
             ArrayTree::Children(children) => {
@@ -341,9 +339,13 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     }
 
     /// Global variable initialization (default 0)
+    ///
+    /// 初始值先按变量类型做隐式转换，保证常量的位宽与全局变量一致，
+    /// 例如 bool 全局变量统一存为 `i1`，用 bool 初始化的整数变量存为对应宽度的整数
     fn const_init_or_zero(
         &mut self,
         init: Option<InitVal>,
+        var_ty: &Ty,
         ty: BasicTypeEnum<'ctx>,
     ) -> Result<BasicValueEnum<'ctx>> {
         let Some(init) = init else {
@@ -351,8 +353,23 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         };
         let range = init.text_range();
         if let Some(value) = self.analyzer.get_value_by_range(range) {
-            return self.convert_value(value, Some(ty));
+            let value = value
+                .convert_to(var_ty, self.analyzer)
+                .map_err(|e| CodegenError::TypeMismatch(format!("{e:?}"), None))?;
+            return self.convert_value(&value, Some(ty));
+        }
+        // `&g`：直接用全局变量的地址作为常量
+        if let Some(var) = init
+            .expr()
+            .and_then(|expr| self.analyzer.global_address_of(&expr))
+        {
+            let global = self
+                .symbols
+                .globals
+                .get(&var.name)
+                .ok_or(CodegenError::Missing("global variable", None))?;
+            return Ok(global.ptr.into());
         }
-        Err(CodegenError::Missing("init value"))
+        Err(CodegenError::Missing("init value", None))
     }
 }
@@ -1,6 +1,7 @@
 use analyzer::r#type::Ty;
-use inkwell::types::{BasicType, BasicTypeEnum};
-use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, PointerValue};
+use inkwell::AddressSpace;
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, IntValue, PointerValue};
 use syntax::ast::*;
 use syntax::syntax_kind::SyntaxKind;
 
@@ -20,6 +21,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             return self.get_const_var_value_by_range(range, ty);
         }
 
+        let node_range = utils::trim_node_text_range(&expr);
         match expr {
             Expr::BinaryExpr(e) => self.compile_binary_expr(e),
             Expr::UnaryExpr(e) => self.compile_unary_expr(e),
@@ -28,22 +30,32 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             Expr::IndexVal(e) => self.compile_index_val(e),
             Expr::Literal(e) => self.compile_literal(e),
             Expr::PostfixExpr(e) => self.compile_postfix_expr(e),
+            // 枚举成员总是编译期常量，走到这里说明分析阶段没有求出值
+            Expr::EnumVariantExpr(_) => Err(CodegenError::Missing("enum variant value", None)),
         }
+        .map_err(|e| e.with_range(node_range))
     }
 
     fn compile_deref_expr(&mut self, expr: &UnaryExpr) -> Result<BasicValueEnum<'ctx>> {
         // 获取整个解引用表达式的类型（即解引用后的结果类型）
         let range = expr.text_range();
-        let operand = expr.expr().ok_or(CodegenError::Missing("* operand"))?;
+        let operand = expr
+            .expr()
+            .ok_or(CodegenError::Missing("* operand", None))?;
+        let operand_range = operand.text_range();
         let ptr = self.compile_expr(operand)?.into_pointer_value();
+        // 数组指针解引用后 decay 成首元素指针，地址不变，不需要 load
+        if let Some(Ty::Pointer { pointee, .. }) = self.analyzer.get_expr_type(operand_range)
+            && pointee.is_array()
+        {
+            return Ok(ptr.into());
+        }
         let result_ty = self
             .analyzer
             .get_expr_type(range)
-            .ok_or(CodegenError::Missing("deref type"))?;
+            .ok_or(CodegenError::Missing("deref type", None))?;
         let llvm_ty = self.convert_ntype_to_type(result_ty)?;
-        self.builder
-            .build_load(llvm_ty, ptr, "deref")
-            .map_err(|_| CodegenError::LlvmBuild("deref load"))
+        self.build_aligned_load(llvm_ty, ptr, "deref", "deref load")
     }
 
     fn compile_binary_expr(&mut self, expr: BinaryExpr) -> Result<BasicValueEnum<'ctx>> {
@@ -51,18 +63,20 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
 
         let op_token = expr
             .op()
-            .ok_or(CodegenError::Missing("binary operator"))?
+            .ok_or(CodegenError::Missing("binary operator", None))?
             .op();
 
         if let Some(func) = self.symbols.current_function
-            && matches!(op_token.kind(), SyntaxKind::AMPAMP | SyntaxKind::PIPEPIPE)
+            && op_token.kind().is_logical_op()
         {
             let bool_false = self.context.bool_type().const_zero();
             let rhs_bb = self.context.append_basic_block(func, "land.rhs");
             let merge_bb = self.context.append_basic_block(func, "land.phi");
 
-            let lhs =
-                self.compile_expr(expr.lhs().ok_or(CodegenError::Missing("left operand"))?)?;
+            let lhs = self.compile_expr(
+                expr.lhs()
+                    .ok_or(CodegenError::Missing("left operand", None))?,
+            )?;
             let lhs = lhs.into_int_value();
 
             let lhs_bb = self
@@ -91,8 +105,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             };
 
             self.builder.position_at_end(rhs_bb);
-            let rhs =
-                self.compile_expr(expr.rhs().ok_or(CodegenError::Missing("right operand"))?)?;
+            let rhs = self.compile_expr(
+                expr.rhs()
+                    .ok_or(CodegenError::Missing("right operand", None))?,
+            )?;
             let rhs_val = self.as_bool(rhs)?;
             let rhs_end_bb = self
                 .builder
@@ -110,8 +126,12 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             return Ok(merge.as_basic_value());
         }
 
-        let lhs_node = expr.lhs().ok_or(CodegenError::Missing("left operand"))?;
-        let rhs_node = expr.rhs().ok_or(CodegenError::Missing("right operand"))?;
+        let lhs_node = expr
+            .lhs()
+            .ok_or(CodegenError::Missing("left operand", None))?;
+        let rhs_node = expr
+            .rhs()
+            .ok_or(CodegenError::Missing("right operand", None))?;
         let lhs = self.compile_expr(lhs_node.clone())?;
         let rhs = self.compile_expr(rhs_node.clone())?;
 
@@ -121,11 +141,12 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let lhs_ty = self
                     .analyzer
                     .get_expr_type(lhs_node.text_range())
-                    .ok_or(CodegenError::Missing("lhs type"))?;
+                    .ok_or(CodegenError::Missing("lhs type", None))?;
                 let pointee = lhs_ty
                     .pointer_inner()
-                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into()))?;
+                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into(), None))?;
                 let llvm_ty = self.convert_ntype_to_type(pointee)?;
+                let i = self.ptr_offset_index(i, &rhs_node)?;
                 match op_token.kind() {
                     SyntaxKind::PLUS => {
                         let gep = unsafe {
@@ -147,7 +168,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                         };
                         Ok(gep.into())
                     }
-                    _ => Err(CodegenError::Unsupported("ptr binary op".into())),
+                    _ => Err(CodegenError::Unsupported("ptr binary op".into(), None)),
                 }
             }
             // 整数 + 指针
@@ -155,11 +176,12 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let rhs_ty = self
                     .analyzer
                     .get_expr_type(rhs_node.text_range())
-                    .ok_or(CodegenError::Missing("rhs type"))?;
+                    .ok_or(CodegenError::Missing("rhs type", None))?;
                 let pointee = rhs_ty
                     .pointer_inner()
-                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into()))?;
+                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into(), None))?;
                 let llvm_ty = self.convert_ntype_to_type(pointee)?;
+                let i = self.ptr_offset_index(i, &lhs_node)?;
                 if op_token.kind() == SyntaxKind::PLUS {
                     let gep = unsafe {
                         self.builder
@@ -168,7 +190,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     };
                     Ok(gep.into())
                 } else {
-                    Err(CodegenError::Unsupported("int - ptr".into()))
+                    Err(CodegenError::Unsupported("int - ptr".into(), None))
                 }
             }
             // 指针 - 指针 / 指针比较
@@ -180,31 +202,33 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let lhs_ty = self
                     .analyzer
                     .get_expr_type(lhs_node.text_range())
-                    .ok_or(CodegenError::Missing("lhs type"))?;
+                    .ok_or(CodegenError::Missing("lhs type", None))?;
                 let rhs_ty = self
                     .analyzer
                     .get_expr_type(rhs_node.text_range())
-                    .ok_or(CodegenError::Missing("rhs type"))?;
+                    .ok_or(CodegenError::Missing("rhs type", None))?;
 
                 self.compile_int_binary_op(op_token.kind(), l, r, lhs_ty, rhs_ty)
             }
-            _ => Err(CodegenError::TypeMismatch(format!(
-                "binary op lhs: {:?} rhs: {:?}",
-                lhs, rhs
-            ))),
+            _ => Err(CodegenError::TypeMismatch(
+                format!("binary op lhs: {:?} rhs: {:?}", lhs, rhs),
+                None,
+            )),
         }
     }
 
     fn compile_unary_expr(&mut self, expr: UnaryExpr) -> Result<BasicValueEnum<'ctx>> {
         let op_token = expr
             .op()
-            .ok_or(CodegenError::Missing("unary operator"))?
+            .ok_or(CodegenError::Missing("unary operator", None))?
             .op();
 
         // 取地址需要特殊处理，不能先编译操作数
         let op_kind = op_token.kind();
         if op_kind == SyntaxKind::AMP {
-            let operand = expr.expr().ok_or(CodegenError::Missing("& operand"))?;
+            let operand = expr
+                .expr()
+                .ok_or(CodegenError::Missing("& operand", None))?;
             return match operand {
                 Expr::IndexVal(iv) => {
                     let (_, ptr, _) = self.get_index_val_ptr(&iv)?;
@@ -212,22 +236,28 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 }
                 Expr::UnaryExpr(de) if de.op().map(|x| x.op().kind()) == Some(SyntaxKind::STAR) => {
                     // &*ptr == ptr
-                    self.compile_expr(de.expr().ok_or(CodegenError::Missing("deref operand"))?)
+                    self.compile_expr(
+                        de.expr()
+                            .ok_or(CodegenError::Missing("deref operand", None))?,
+                    )
                 }
                 Expr::PostfixExpr(pe) => {
                     let (ptr, _) = self.get_postfix_expr_ptr(pe)?;
                     Ok(ptr.into())
                 }
-                _ => Err(CodegenError::Unsupported(format!(
-                    "cannot take address {:?}",
-                    operand.syntax().text()
-                ))),
+                _ => Err(CodegenError::Unsupported(
+                    format!("cannot take address {:?}", operand.syntax().text()),
+                    None,
+                )),
             };
         } else if op_kind == SyntaxKind::STAR {
             return self.compile_deref_expr(&expr);
         }
 
-        let val = self.compile_expr(expr.expr().ok_or(CodegenError::Missing("unary operand"))?)?;
+        let val = self.compile_expr(
+            expr.expr()
+                .ok_or(CodegenError::Missing("unary operand", None))?,
+        )?;
 
         match val {
             BasicValueEnum::IntValue(i) => match op_token.kind() {
@@ -238,16 +268,17 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     .map_err(|_| CodegenError::LlvmBuild("int neg"))?
                     .into()),
                 SyntaxKind::BANG => {
+                    // 结果类型为 bool，保持 i1，参与算术时再由 cast_int_to_type 扩展
                     let b = self.as_bool(val)?;
                     let nb = self
                         .builder
                         .build_not(b, "lnot")
                         .map_err(|_| CodegenError::LlvmBuild("not"))?;
-                    Ok(self.bool_to_i32(nb)?.into())
+                    Ok(nb.into())
                 }
-                _ => Err(CodegenError::Unsupported("int unary op".into())),
+                _ => Err(CodegenError::Unsupported("int unary op".into(), None)),
             },
-            _ => Err(CodegenError::Unsupported("operand type".into())),
+            _ => Err(CodegenError::Unsupported("operand type".into(), None)),
         }
     }
 
@@ -255,7 +286,24 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let name = expr
             .name()
             .and_then(|n| n.var_name())
-            .ok_or(CodegenError::Missing("function name"))?;
+            .ok_or(CodegenError::Missing("function name", None))?;
+        // 编译期断言已在 analyzer 中检查，不生成代码
+        if name == analyzer::analyze::STATIC_ASSERT
+            && self.analyzer.get_function_id_by_name(&name).is_none()
+        {
+            return Ok(self.context.i32_type().const_zero().into());
+        }
+        if name == analyzer::analyze::ALLOC
+            && self.analyzer.get_function_id_by_name(&name).is_none()
+        {
+            return self.compile_alloc(&expr);
+        }
+        // 两边都是字符串常量时已经折叠，这里只处理运行期比较
+        if name == analyzer::analyze::STREQ
+            && self.analyzer.get_function_id_by_name(&name).is_none()
+        {
+            return self.compile_streq(&expr);
+        }
         let func = self
             .module
             .get_function(&name)
@@ -268,7 +316,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         {
             func_info.meta_types.into_iter().map(|(_, ty)| ty).collect()
         } else {
-            return Err(CodegenError::Missing("function info"));
+            return Err(CodegenError::Missing("function info", None));
         };
 
         let args: Vec<BasicMetadataValueEnum<'ctx>> = if let Some(rps) = expr.args() {
@@ -282,7 +330,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                         let arg_ty = self
                             .analyzer
                             .get_expr_type(arg_expr.text_range())
-                            .ok_or(CodegenError::Missing("arg type"))?;
+                            .ok_or(CodegenError::Missing("arg type", None))?;
                         let casted = self.cast_value(val, arg_ty, &param_types[i])?;
                         Ok(casted.into())
                     } else {
@@ -305,10 +353,92 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         }
     }
 
+    /// `alloc<T>(n)` 降级为 `malloc(sizeof(T) * n)`
+    fn compile_alloc(&mut self, expr: &CallExpr) -> Result<BasicValueEnum<'ctx>> {
+        let pointee = self
+            .analyzer
+            .get_expr_type(expr.text_range())
+            .and_then(|ty| ty.pointer_inner())
+            .ok_or(CodegenError::Missing("alloc type", None))?;
+        let elem_size = self
+            .convert_ntype_to_type(pointee)?
+            .size_of()
+            .ok_or(CodegenError::LlvmBuild("failed to get type size"))?;
+
+        let count_expr = expr
+            .args()
+            .and_then(|args| args.args().next())
+            .ok_or(CodegenError::Missing("alloc count", None))?;
+        let count_ty = self
+            .analyzer
+            .get_expr_type(count_expr.text_range())
+            .ok_or(CodegenError::Missing("alloc count type", None))?;
+        let count = self.compile_expr(count_expr)?;
+        let count = self.cast_value(count, count_ty, &Ty::I64)?.into_int_value();
+        let size = self
+            .builder
+            .build_int_mul(elem_size, count, "alloc.size")
+            .map_err(|_| CodegenError::LlvmBuild("mul"))?;
+
+        // 复用用户声明的 malloc，按其参数类型传入大小
+        let malloc = self.module.get_function("malloc").unwrap_or_else(|| {
+            let ptr_ty = self.context.ptr_type(AddressSpace::default());
+            let fn_ty = ptr_ty.fn_type(&[self.context.i64_type().into()], false);
+            self.module.add_function("malloc", fn_ty, None)
+        });
+        let size = match malloc.get_type().get_param_types().first() {
+            Some(BasicMetadataTypeEnum::IntType(param_ty)) => self
+                .builder
+                .build_int_cast(size, *param_ty, "alloc.size")
+                .map_err(|_| CodegenError::LlvmBuild("int cast"))?,
+            _ => size,
+        };
+        let call = self
+            .builder
+            .build_call(malloc, &[size.into()], "alloc")
+            .map_err(|_| CodegenError::LlvmBuild("function call"))?;
+        Ok(call.try_as_basic_value().unwrap_basic())
+    }
+
+    /// `streq(a, b)` 降级为 `strcmp(a, b) == 0`
+    fn compile_streq(&mut self, expr: &CallExpr) -> Result<BasicValueEnum<'ctx>> {
+        use inkwell::IntPredicate;
+
+        let args = expr
+            .args()
+            .map(|args| args.args().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let [a, b] = args.as_slice() else {
+            return Err(CodegenError::Missing("streq arguments", None));
+        };
+        let a = self.compile_expr(a.clone())?;
+        let b = self.compile_expr(b.clone())?;
+
+        let strcmp = self.module.get_function("strcmp").unwrap_or_else(|| {
+            let ptr_ty = self.context.ptr_type(AddressSpace::default());
+            let fn_ty = self
+                .context
+                .i32_type()
+                .fn_type(&[ptr_ty.into(), ptr_ty.into()], false);
+            self.module.add_function("strcmp", fn_ty, None)
+        });
+        let cmp = self
+            .builder
+            .build_call(strcmp, &[a.into(), b.into()], "streq")
+            .map_err(|_| CodegenError::LlvmBuild("function call"))?
+            .try_as_basic_value()
+            .unwrap_basic()
+            .into_int_value();
+        self.builder
+            .build_int_compare(IntPredicate::EQ, cmp, cmp.get_type().const_zero(), "streq")
+            .map(Into::into)
+            .map_err(|_| CodegenError::LlvmBuild("int compare"))
+    }
+
     fn compile_paren_expr(&mut self, expr: ParenExpr) -> Result<BasicValueEnum<'ctx>> {
         self.compile_expr(
             expr.expr()
-                .ok_or(CodegenError::Missing("paren expression"))?,
+                .ok_or(CodegenError::Missing("paren expression", None))?,
         )
     }
 
@@ -322,9 +452,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         }
 
         // 否则 load 值
-        self.builder
-            .build_load(ty, ptr, &name)
-            .map_err(|_| CodegenError::LlvmBuild("load"))
+        self.build_aligned_load(ty, ptr, &name, "load")
     }
 
     fn compile_literal(&mut self, expr: Literal) -> Result<BasicValueEnum<'ctx>> {
@@ -390,9 +518,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         }
 
         // 否则 load 值
-        self.builder
-            .build_load(field_ty, field_ptr, "field")
-            .map_err(|_| CodegenError::LlvmBuild("load field failed"))
+        self.build_aligned_load(field_ty, field_ptr, "field", "load field failed")
     }
 
     /// 编译 PostfixExpr 为左值，返回 (指针, 字段类型)
@@ -402,26 +528,26 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     ) -> Result<(PointerValue<'ctx>, BasicTypeEnum<'ctx>)> {
         let op = postfix
             .op()
-            .ok_or(CodegenError::Missing("postfix operator"))?;
+            .ok_or(CodegenError::Missing("postfix operator", None))?;
         let op_kind = op.op().kind();
 
         // 获取字段 FieldAccess（包含字段名和可能的数组索引）
         let field_access = postfix
             .field()
-            .ok_or(CodegenError::Missing("field access"))?;
+            .ok_or(CodegenError::Missing("field access", None))?;
         let member_name = field_access
             .name()
             .and_then(|n| n.var_name())
-            .ok_or(CodegenError::Missing("member name"))?;
+            .ok_or(CodegenError::Missing("member name", None))?;
 
         let base_expr = postfix
             .expr()
-            .ok_or(CodegenError::Missing("base expression"))?;
+            .ok_or(CodegenError::Missing("base expression", None))?;
         let base_range = base_expr.text_range();
         let base_ty = self
             .analyzer
             .get_expr_type(base_range)
-            .ok_or(CodegenError::Missing("base type"))?
+            .ok_or(CodegenError::Missing("base type", None))?
             .clone();
 
         // 根据操作符类型获取基础指针
@@ -436,10 +562,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             self.get_struct_field_ptr(base_ptr, &base_ty, &member_name, is_pointer_access)?;
 
         // 处理数组索引（如 arr[0] 或 arr[0][1]）
-        let indices: Vec<_> = field_access
-            .indices()
-            .map(|e| self.compile_expr(e).map(|v| v.into_int_value()))
-            .collect::<Result<Vec<_>>>()?;
+        let indices = self.compile_indices(field_access.indices())?;
 
         (field_llvm_ty, field_ptr) =
             self.calculate_index_op(field_ty, field_llvm_ty, field_ptr, indices)?;
@@ -459,9 +582,13 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             }
             Expr::UnaryExpr(unary) => {
                 // 解引用表达式可以作为左值
-                let op = unary.op().ok_or(CodegenError::Missing("unary operator"))?;
+                let op = unary
+                    .op()
+                    .ok_or(CodegenError::Missing("unary operator", None))?;
                 if op.op().kind() == SyntaxKind::STAR {
-                    let operand = unary.expr().ok_or(CodegenError::Missing("* operand"))?;
+                    let operand = unary
+                        .expr()
+                        .ok_or(CodegenError::Missing("* operand", None))?;
                     Ok(self.compile_expr(operand)?.into_pointer_value())
                 } else {
                     Err(CodegenError::NotImplemented("not an lvalue"))
@@ -471,6 +598,13 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let (ptr, _) = self.get_postfix_expr_ptr(postfix)?;
                 Ok(ptr)
             }
+            Expr::ParenExpr(paren) => {
+                // 括号作为链式访问的基址，如 (*p).inner.values[1]
+                let inner = paren
+                    .expr()
+                    .ok_or(CodegenError::Missing("paren expression", None))?;
+                self.get_expr_ptr(inner)
+            }
             _ => Err(CodegenError::NotImplemented("not an lvalue")),
         }
     }
@@ -483,7 +617,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let name = index_val
             .name()
             .and_then(|n| n.var_name())
-            .ok_or(CodegenError::Missing("function name"))?;
+            .ok_or(CodegenError::Missing("function name", None))?;
         let symbol = self
             .symbols
             .lookup_var(&name)
@@ -491,16 +625,30 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let (mut ptr, cur_ntype) = (symbol.ptr, symbol.ty.clone());
         let mut cur_llvm_type = self.convert_ntype_to_type(&cur_ntype)?;
 
-        let indices: Vec<_> = index_val
-            .indices()
-            .map(|e| self.compile_expr(e).map(|v| v.into_int_value()))
-            .collect::<Result<Vec<_>>>()?;
+        let indices = self.compile_indices(index_val.indices())?;
 
         (cur_llvm_type, ptr) = self.calculate_index_op(cur_ntype, cur_llvm_type, ptr, indices)?;
         Ok((cur_llvm_type, ptr, name))
     }
 
-    /// 编译整数二元运算（算术、比较、逻辑）
+    /// 编译下标表达式，同时取出分析器推导的下标类型
+    fn compile_indices(
+        &mut self,
+        exprs: impl Iterator<Item = Expr>,
+    ) -> Result<Vec<(IntValue<'ctx>, Ty)>> {
+        exprs
+            .map(|e| {
+                let ty = self
+                    .analyzer
+                    .get_expr_type(e.text_range())
+                    .cloned()
+                    .ok_or(CodegenError::Missing("index type", Some(e.text_range())))?;
+                Ok((self.compile_expr(e)?.into_int_value(), ty))
+            })
+            .collect()
+    }
+
+    /// 编译整数二元运算（算术、移位、比较、逻辑）
     /// 统一处理类型提升和运算逻辑
     fn compile_int_binary_op(
         &mut self,
@@ -522,21 +670,18 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             return self.compile_int_arithmetic(op, l, r, lhs_ty, rhs_ty);
         }
 
+        // 移位运算：结果类型由左操作数决定
+        if op.is_shift_op() {
+            return self.compile_int_shift(op, l, r, lhs_ty, rhs_ty);
+        }
+
         // 比较运算：返回 bool (i1)
-        if matches!(
-            op,
-            SyntaxKind::LT
-                | SyntaxKind::GT
-                | SyntaxKind::LTEQ
-                | SyntaxKind::GTEQ
-                | SyntaxKind::EQEQ
-                | SyntaxKind::NEQ
-        ) {
+        if op.is_comparison_op() {
             return self.compile_int_comparison(op, l, r, lhs_ty, rhs_ty);
         }
 
         // 逻辑运算：返回 bool (i1)
-        if matches!(op, SyntaxKind::AMPAMP | SyntaxKind::PIPEPIPE) {
+        if op.is_logical_op() {
             let lb = self.cast_int_to_type(l, lhs_ty, &Ty::Bool)?;
             let rb = self.cast_int_to_type(r, rhs_ty, &Ty::Bool)?;
             let res = match op {
@@ -553,7 +698,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             return Ok(res.into());
         }
 
-        Err(CodegenError::Unsupported(format!("int binary op {:?}", op)))
+        Err(CodegenError::Unsupported(
+            format!("int binary op {:?}", op),
+            None,
+        ))
     }
 
     /// 编译整数算术运算
@@ -567,7 +715,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     ) -> Result<BasicValueEnum<'ctx>> {
         // 确定结果类型（使用 analyzer 的类型提升规则）
         let result_ty = Ty::compute_binary_result_type(lhs_ty, rhs_ty, op)
-            .ok_or_else(|| CodegenError::TypeMismatch("incompatible types".into()))?;
+            .ok_or_else(|| CodegenError::TypeMismatch("incompatible types".into(), None))?;
 
         // 将操作数转换到结果类型
         let l_casted = self.cast_int_to_type(l, lhs_ty, &result_ty)?;
@@ -577,6 +725,38 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         self.build_int_arithmetic_op(op, l_casted, r_casted, &result_ty)
     }
 
+    /// 编译整数移位运算，右移按左操作数的符号选择算术或逻辑右移
+    fn compile_int_shift(
+        &mut self,
+        op: SyntaxKind,
+        l: inkwell::values::IntValue<'ctx>,
+        r: inkwell::values::IntValue<'ctx>,
+        lhs_ty: &Ty,
+        rhs_ty: &Ty,
+    ) -> Result<BasicValueEnum<'ctx>> {
+        let result_ty = Ty::compute_binary_result_type(lhs_ty, rhs_ty, op)
+            .ok_or_else(|| CodegenError::TypeMismatch("incompatible types".into(), None))?;
+
+        // LLVM 要求两个操作数位宽相同，移位数也转换到结果类型
+        let l_casted = self.cast_int_to_type(l, lhs_ty, &result_ty)?;
+        let r_casted = self.cast_int_to_type(r, rhs_ty, &result_ty)?;
+
+        let res = match op {
+            SyntaxKind::LTLT => self
+                .builder
+                .build_left_shift(l_casted, r_casted, "shl")
+                .map_err(|_| CodegenError::LlvmBuild("int shl"))?,
+            SyntaxKind::GTGT => {
+                let is_signed = matches!(result_ty, Ty::I8 | Ty::I32 | Ty::I64);
+                self.builder
+                    .build_right_shift(l_casted, r_casted, is_signed, "shr")
+                    .map_err(|_| CodegenError::LlvmBuild("int shr"))?
+            }
+            _ => unreachable!(),
+        };
+        Ok(res.into())
+    }
+
     /// 编译整数比较运算
     fn compile_int_comparison(
         &mut self,
@@ -588,7 +768,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     ) -> Result<BasicValueEnum<'ctx>> {
         // 使用新方法计算提升类型
         let promoted_ty = Ty::compute_promotion_type(lhs_ty, rhs_ty).ok_or_else(|| {
-            CodegenError::TypeMismatch("incompatible types for comparison".into())
+            CodegenError::TypeMismatch("incompatible types for comparison".into(), None)
         })?;
 
         // 将操作数转换到提升类型
@@ -699,6 +879,22 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         self.build_int_cmp(predicate, l, r, "cmp")
     }
 
+    /// 指针偏移量先扩展为 i64 再参与 GEP
+    ///
+    /// GEP 把下标当作有符号数，`p + 200u8` 不能直接用 i8 的下标，
+    /// 否则会被解释成 -56；扩展之后 `p - n` 取负也不会溢出
+    fn ptr_offset_index(
+        &self,
+        i: inkwell::values::IntValue<'ctx>,
+        node: &Expr,
+    ) -> Result<inkwell::values::IntValue<'ctx>> {
+        let ty = self
+            .analyzer
+            .get_expr_type(node.text_range())
+            .ok_or(CodegenError::Missing("offset type", None))?;
+        self.cast_int_to_type(i, ty, &Ty::I64)
+    }
+
     /// 将两个指针转换为整数用于比较
     /// 返回 (i1, i2)
     fn ptr_to_int_pair(
@@ -739,10 +935,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 let lhs_ty = self
                     .analyzer
                     .get_expr_type(lhs_node.text_range())
-                    .ok_or(CodegenError::Missing("lhs type"))?;
+                    .ok_or(CodegenError::Missing("lhs type", None))?;
                 let pointee = lhs_ty
                     .pointer_inner()
-                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into()))?;
+                    .ok_or_else(|| CodegenError::TypeMismatch("expected pointer".into(), None))?;
 
                 // 获取元素大小
                 let llvm_ty = self.convert_ntype_to_type(pointee)?;
@@ -785,10 +981,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     .map_err(|_| CodegenError::LlvmBuild("ptr compare"))?;
                 Ok(cmp.into())
             }
-            _ => Err(CodegenError::Unsupported(format!(
-                "unsupported pointer operation: {:?}",
-                op
-            ))),
+            _ => Err(CodegenError::Unsupported(
+                format!("unsupported pointer operation: {:?}", op),
+                None,
+            )),
         }
     }
 }
@@ -1,16 +1,53 @@
 use analyzer::module::Function;
 use analyzer::r#type::Ty;
+use inkwell::attributes::AttributeLoc;
+use inkwell::module::Linkage;
 use inkwell::types::BasicType;
+use inkwell::values::FunctionValue;
 use syntax::ast::*;
 
 use crate::error::{CodegenError, Result};
 use crate::llvm_ir::Program;
 
 impl<'a, 'ctx> Program<'a, 'ctx> {
-    /// 编译函数定义
+    /// 声明函数定义的签名并设置属性，函数体由 `compile_func_def` 生成
+    pub(super) fn declare_func_def(&mut self, func: &FuncDef) -> Result<()> {
+        let sign = func
+            .sign()
+            .ok_or(CodegenError::Missing("function signature", None))?;
+        self.compile_func_signature(sign)?;
+        self.apply_func_attributes(func)
+    }
+
+    /// 编译函数定义的函数体，签名已经在 `declare_func_def` 中声明
     pub(super) fn compile_func_def(&mut self, func: FuncDef) -> Result<()> {
-        self.compile_func_signature(func.sign().unwrap())?;
-        self.compile_func_attach(func.sign().and_then(|n| n.name()), func.block())?;
+        self.compile_func_attach(func.sign().and_then(|n| n.name()), func.block())
+    }
+
+    /// 将 `@inline` / `@noinline` 映射为 LLVM 函数属性
+    fn apply_func_attributes(&mut self, func: &FuncDef) -> Result<()> {
+        let name = func
+            .sign()
+            .and_then(|s| s.name())
+            .and_then(|n| n.var_name())
+            .ok_or(CodegenError::Missing("function name", None))?;
+        let function = self
+            .symbols
+            .functions
+            .get(&name)
+            .copied()
+            .ok_or_else(|| CodegenError::UndefinedFunc(name.clone()))?;
+
+        for attr in func.attributes() {
+            let llvm_attr = match attr.name().and_then(|n| n.var_name()).as_deref() {
+                Some("inline") => "alwaysinline",
+                Some("noinline") => "noinline",
+                _ => continue,
+            };
+            let kind_id = inkwell::attributes::Attribute::get_named_enum_kind_id(llvm_attr);
+            let attribute = self.context.create_enum_attribute(kind_id, 0);
+            function.add_attribute(AttributeLoc::Function, attribute);
+        }
         Ok(())
     }
 
@@ -19,7 +56,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let name = func
             .name()
             .and_then(|n| n.var_name())
-            .ok_or(CodegenError::Missing("function name"))?;
+            .ok_or(CodegenError::Missing("function name", None))?;
 
         // 直接从 analyzer 获取函数信息
         let func_id = self
@@ -47,13 +84,24 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             .collect::<Result<Vec<_>>>()?;
 
         let ret_llvm_ty = self.convert_ntype_to_type(ret_ty)?;
-        let fn_type = if is_void {
+        // 返回 void 的 main 仍以 i32 作为进程退出码，函数体结束时返回 0
+        let fn_type = if is_void && name == "main" {
+            self.context.i32_type().fn_type(&basic_params, is_variadic)
+        } else if is_void {
             self.context.void_type().fn_type(&basic_params, is_variadic)
         } else {
             ret_llvm_ty.fn_type(&basic_params, is_variadic)
         };
 
-        let function = self.module.add_function(name, fn_type, None);
+        // extern 函数按原名以外部链接声明，由链接时的 C 运行时提供实现
+        let linkage = func_info.is_extern.then_some(Linkage::External);
+        let function = self.module.add_function(name, fn_type, linkage);
+        // restrict 指针参数保证不与其他指针别名
+        let noalias = inkwell::attributes::Attribute::get_named_enum_kind_id("noalias");
+        for &index in &func_info.restrict_params {
+            let attribute = self.context.create_enum_attribute(noalias, 0);
+            function.add_attribute(AttributeLoc::Param(index as u32), attribute);
+        }
         self.symbols.functions.insert(name.to_string(), function);
         Ok(())
     }
@@ -69,7 +117,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         };
         let name = name
             .and_then(|n| n.var_name())
-            .ok_or(CodegenError::Missing("function name"))?;
+            .ok_or(CodegenError::Missing("function name", None))?;
 
         // 获取已声明的函数
         let function = self
@@ -89,9 +137,6 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             .get_function_by_id(func_id)
             .ok_or_else(|| CodegenError::UndefinedFunc(name.clone()))?;
 
-        let ret_ty = &func_info.ret_type;
-        let is_void = matches!(ret_ty, Ty::Void);
-
         // 从 func_info.params 获取参数信息
         let params: Vec<(String, &'a Ty)> = func_info
             .params
@@ -107,41 +152,47 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
 
         let prev_func = self.symbols.current_function;
         self.symbols.current_function = Some(function);
+        self.symbols.type_cache.borrow_mut().clear();
         self.symbols.push_scope();
 
         for (i, (pname, param_ty)) in params.into_iter().enumerate() {
             let param_val = function
                 .get_nth_param(i as u32)
-                .ok_or(CodegenError::Missing("parameter"))?;
+                .ok_or(CodegenError::Missing("parameter", None))?;
             param_val.set_name(&pname);
 
             let alloc_ty = param_val.get_type();
             let alloca = self.create_entry_alloca(function, alloc_ty, &pname)?;
-            self.builder
-                .build_store(alloca, param_val)
-                .map_err(|_| CodegenError::LlvmBuild("parameter store failed"))?;
+            self.build_aligned_store(alloca, param_val, "parameter store failed")?;
             self.symbols.insert_var(pname, alloca, param_ty);
         }
 
         self.compile_block(block)?;
 
-        let ret_llvm_ty = self.convert_ntype_to_type(ret_ty)?;
         let has_term = self
             .builder
             .get_insert_block()
             .and_then(|bb| bb.get_terminator())
             .is_some();
         if !has_term {
-            if is_void {
-                self.builder.build_return(None).ok();
-            } else {
-                let zero = ret_llvm_ty.const_zero();
-                self.builder.build_return(Some(&zero)).ok();
-            }
+            self.build_default_return(function);
         }
 
         self.symbols.pop_scope();
         self.symbols.current_function = prev_func;
         Ok(())
     }
+
+    /// 函数末尾或不带值的 return：void 函数直接 `ret void`，其余（包括 void 的 `main`）返回 0
+    pub(super) fn build_default_return(&self, function: FunctionValue<'ctx>) {
+        match function.get_type().get_return_type() {
+            Some(ty) => {
+                let zero = ty.const_zero();
+                self.builder.build_return(Some(&zero)).ok();
+            }
+            None => {
+                self.builder.build_return(None).ok();
+            }
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use analyzer::value::Value;
 use syntax::ast::*;
 
 use crate::error::{CodegenError, Result};
@@ -8,21 +9,22 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     pub(super) fn compile_block(&mut self, block: Block) -> Result<()> {
         self.symbols.push_scope();
         for item in block.items() {
-            let is_terminal = if let BlockItem::Stmt(ref stmt) = item
-                && matches!(
-                    stmt,
-                    Stmt::BreakStmt(_) | Stmt::ContinueStmt(_) | Stmt::ReturnStmt(_)
-                ) {
-                true
-            } else {
-                false
-            };
             match item {
-                BlockItem::VarDef(decl) => self.compile_var_def(decl)?,
+                BlockItem::VarDef(decl) => {
+                    let node_range = utils::trim_node_text_range(&decl);
+                    self.compile_var_def(decl)
+                        .map_err(|e| e.with_range(node_range))?
+                }
                 BlockItem::Stmt(stmt) => self.compile_stmt(stmt)?,
             }
 
-            if is_terminal {
+            // 当前基本块已经终结（break/continue/return，包括嵌套块中的），后面的语句不可达
+            if self
+                .builder
+                .get_insert_block()
+                .and_then(|bb| bb.get_terminator())
+                .is_some()
+            {
                 break;
             }
         }
@@ -31,21 +33,33 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     }
 
     pub(super) fn compile_stmt(&mut self, stmt: Stmt) -> Result<()> {
+        let node_range = utils::trim_node_text_range(&stmt);
         match stmt {
             Stmt::AssignStmt(s) => self.compile_assign_stmt(s),
             Stmt::ExprStmt(s) => self.compile_expr_stmt(s),
             Stmt::Block(s) => self.compile_block(s),
             Stmt::IfStmt(s) => self.compile_if_stmt(s),
             Stmt::WhileStmt(s) => self.compile_while_stmt(s),
+            Stmt::ForStmt(s) => self.compile_for_stmt(s),
             Stmt::BreakStmt(s) => self.compile_break_stmt(s),
             Stmt::ContinueStmt(s) => self.compile_continue_stmt(s),
             Stmt::ReturnStmt(s) => self.compile_return_stmt(s),
+            Stmt::SwitchStmt(s) => self.compile_switch_stmt(s),
+            // 分析阶段已经报告不支持
+            Stmt::GotoStmt(_) | Stmt::LabelStmt(_) => {
+                Err(CodegenError::NotImplemented("goto and labeled statements"))
+            }
         }
+        .map_err(|e| e.with_range(node_range))
     }
 
     fn compile_assign_stmt(&mut self, stmt: AssignStmt) -> Result<()> {
-        let rhs_node = stmt.rhs().ok_or(CodegenError::Missing("assign rhs"))?;
-        let lhs_node = stmt.lhs().ok_or(CodegenError::Missing("assign lhs"))?;
+        let rhs_node = stmt
+            .rhs()
+            .ok_or(CodegenError::Missing("assign rhs", None))?;
+        let lhs_node = stmt
+            .lhs()
+            .ok_or(CodegenError::Missing("assign lhs", None))?;
 
         let rhs = self.compile_expr(rhs_node.clone())?;
         let lhs_ptr = self.get_expr_ptr(lhs_node.clone())?;
@@ -54,18 +68,16 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let lhs_ty = self
             .analyzer
             .get_expr_type(lhs_node.text_range())
-            .ok_or(CodegenError::Missing("lhs type"))?;
+            .ok_or(CodegenError::Missing("lhs type", None))?;
         let rhs_ty = self
             .analyzer
             .get_expr_type(rhs_node.text_range())
-            .ok_or(CodegenError::Missing("rhs type"))?;
+            .ok_or(CodegenError::Missing("rhs type", None))?;
 
         // 如果类型不同，插入转换
         let rhs_casted = self.cast_value(rhs, rhs_ty, lhs_ty)?;
 
-        self.builder
-            .build_store(lhs_ptr, rhs_casted)
-            .map_err(|_| CodegenError::LlvmBuild("assign store failed"))?;
+        self.build_aligned_store(lhs_ptr, rhs_casted, "assign store failed")?;
         Ok(())
     }
 
@@ -77,14 +89,30 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     }
 
     fn compile_if_stmt(&mut self, stmt: IfStmt) -> Result<()> {
-        let cond_val = self.compile_expr(
-            stmt.condition()
-                .ok_or(CodegenError::Missing("if condition"))?,
-        )?;
+        let cond = stmt
+            .condition()
+            .ok_or(CodegenError::Missing("if condition", None))?;
+        // 条件是编译期常量（如 `cfg("debug")`）时只生成会执行的分支
+        if let Some(Ok(Value::Bool(taken))) = self
+            .analyzer
+            .get_value_by_range(cond.text_range())
+            .map(|v| v.cast_to_bool())
+        {
+            let branch = if taken {
+                stmt.then_branch()
+            } else {
+                stmt.else_branch()
+            };
+            if let Some(branch) = branch {
+                self.compile_stmt(branch)?;
+            }
+            return Ok(());
+        }
+        let cond_val = self.compile_expr(cond)?;
         let func = self
             .symbols
             .current_function
-            .ok_or(CodegenError::Missing("current function"))?;
+            .ok_or(CodegenError::Missing("current function", None))?;
 
         let then_bb = self.context.append_basic_block(func, "then");
         let else_bb = self.context.append_basic_block(func, "else");
@@ -112,10 +140,19 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
     }
 
     fn compile_while_stmt(&mut self, stmt: WhileStmt) -> Result<()> {
+        // 条件恒为假时循环体不会执行，不生成代码
+        if let Some(cond) = stmt.condition()
+            && let Some(Ok(Value::Bool(false))) = self
+                .analyzer
+                .get_value_by_range(cond.text_range())
+                .map(|v| v.cast_to_bool())
+        {
+            return Ok(());
+        }
         let func = self
             .symbols
             .current_function
-            .ok_or(CodegenError::Missing("current function"))?;
+            .ok_or(CodegenError::Missing("current function", None))?;
         let cond_bb = self.context.append_basic_block(func, "while.cond");
         let body_bb = self.context.append_basic_block(func, "while.body");
         let end_bb = self.context.append_basic_block(func, "while.end");
@@ -129,7 +166,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         self.builder.position_at_end(cond_bb);
         let cond_val = self.compile_expr(
             stmt.condition()
-                .ok_or(CodegenError::Missing("while condition"))?,
+                .ok_or(CodegenError::Missing("while condition", None))?,
         )?;
         let bool_val = self.as_bool(cond_val)?;
         self.builder
@@ -146,12 +183,153 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         Ok(())
     }
 
+    /// 编译 for 语句
+    ///
+    /// 每次迭代先求值条件再执行循环体，循环体之后执行步进；
+    /// 省略条件时无条件进入循环体，continue 跳到步进
+    fn compile_for_stmt(&mut self, stmt: ForStmt) -> Result<()> {
+        let func = self
+            .symbols
+            .current_function
+            .ok_or(CodegenError::Missing("current function", None))?;
+
+        // init 中声明的变量只在 for 内可见
+        self.symbols.push_scope();
+        if let Some(init) = stmt.init() {
+            for def in init.var_defs() {
+                let node_range = utils::trim_node_text_range(&def);
+                self.compile_var_def(def)
+                    .map_err(|e| e.with_range(node_range))?;
+            }
+            if let Some(s) = init.stmt() {
+                self.compile_stmt(s)?;
+            }
+        }
+
+        let cond_bb = self.context.append_basic_block(func, "for.cond");
+        let body_bb = self.context.append_basic_block(func, "for.body");
+        let step_bb = self.context.append_basic_block(func, "for.step");
+        let end_bb = self.context.append_basic_block(func, "for.end");
+
+        self.symbols.push_loop(step_bb, end_bb);
+
+        self.builder
+            .build_unconditional_branch(cond_bb)
+            .map_err(|_| CodegenError::LlvmBuild("for entry branch failed"))?;
+
+        self.builder.position_at_end(cond_bb);
+        if let Some(cond) = stmt.condition() {
+            let cond_val = self.compile_expr(cond)?;
+            let bool_val = self.as_bool(cond_val)?;
+            self.builder
+                .build_conditional_branch(bool_val, body_bb, end_bb)
+                .map_err(|_| CodegenError::LlvmBuild("for cond branch failed"))?;
+        } else {
+            self.builder
+                .build_unconditional_branch(body_bb)
+                .map_err(|_| CodegenError::LlvmBuild("for cond branch failed"))?;
+        }
+
+        self.builder.position_at_end(body_bb);
+        if let Some(body) = stmt.body() {
+            self.compile_stmt(body)?;
+        }
+        self.branch_if_no_terminator(step_bb)?;
+
+        self.builder.position_at_end(step_bb);
+        if let Some(step) = stmt.step().and_then(|s| s.stmt()) {
+            self.compile_stmt(step)?;
+        }
+        self.branch_if_no_terminator(cond_bb)?;
+
+        self.symbols.pop_loop();
+        self.symbols.pop_scope();
+        self.builder.position_at_end(end_bb);
+        Ok(())
+    }
+
+    /// 编译 switch 语句
+    ///
+    /// 与 C 一致，分支末尾没有 break 时贯穿到下一个分支；
+    /// switch 内的 continue 仍作用于外层循环
+    fn compile_switch_stmt(&mut self, stmt: SwitchStmt) -> Result<()> {
+        let func = self
+            .symbols
+            .current_function
+            .ok_or(CodegenError::Missing("current function", None))?;
+        let cond_val = self
+            .compile_expr(
+                stmt.condition()
+                    .ok_or(CodegenError::Missing("switch condition", None))?,
+            )?
+            .into_int_value();
+        let int_ty = cond_val.get_type();
+
+        let cases: Vec<SwitchCase> = stmt.cases().collect();
+        let case_bbs: Vec<_> = cases
+            .iter()
+            .map(|case| {
+                let name = if case.is_default() {
+                    "switch.default"
+                } else {
+                    "switch.case"
+                };
+                self.context.append_basic_block(func, name)
+            })
+            .collect();
+        let end_bb = self.context.append_basic_block(func, "switch.end");
+
+        let mut default_bb = end_bb;
+        let mut targets = Vec::new();
+        for (case, bb) in cases.iter().zip(&case_bbs) {
+            if case.is_default() {
+                default_bb = *bb;
+                continue;
+            }
+            let label = case
+                .value()
+                .ok_or(CodegenError::Missing("case label", None))?;
+            let v = self
+                .analyzer
+                .get_value_by_range(label.text_range())
+                .and_then(|v| v.as_i64())
+                .ok_or(CodegenError::Missing("case label value", None))?;
+            targets.push((int_ty.const_int(v as u64, true), *bb));
+        }
+        self.builder
+            .build_switch(cond_val, default_bb, &targets)
+            .map_err(|_| CodegenError::LlvmBuild("switch build failed"))?;
+
+        let continue_bb = self.symbols.loop_stack.last().map_or(end_bb, |l| l.cond_bb);
+        self.symbols.push_loop(continue_bb, end_bb);
+        for (index, (case, bb)) in cases.into_iter().zip(&case_bbs).enumerate() {
+            self.builder.position_at_end(*bb);
+            for s in case.body() {
+                self.compile_stmt(s)?;
+                // 与 compile_block 相同，嵌套块中的 break 等也会终结当前基本块
+                if self
+                    .builder
+                    .get_insert_block()
+                    .and_then(|bb| bb.get_terminator())
+                    .is_some()
+                {
+                    break;
+                }
+            }
+            let next_bb = case_bbs.get(index + 1).copied().unwrap_or(end_bb);
+            self.branch_if_no_terminator(next_bb)?;
+        }
+        self.symbols.pop_loop();
+        self.builder.position_at_end(end_bb);
+        Ok(())
+    }
+
     fn compile_break_stmt(&mut self, _stmt: BreakStmt) -> Result<()> {
         let end_bb = self
             .symbols
             .loop_stack
             .last()
-            .ok_or(CodegenError::Unsupported("break not in loop".into()))?
+            .ok_or(CodegenError::Unsupported("break not in loop".into(), None))?
             .end_bb;
         self.builder
             .build_unconditional_branch(end_bb)
@@ -164,7 +342,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             .symbols
             .loop_stack
             .last()
-            .ok_or(CodegenError::Unsupported("continue not in loop".into()))?
+            .ok_or(CodegenError::Unsupported(
+                "continue not in loop".into(),
+                None,
+            ))?
             .cond_bb;
         self.builder
             .build_unconditional_branch(cond_bb)
@@ -180,30 +361,34 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             let func = self
                 .symbols
                 .current_function
-                .ok_or(CodegenError::Missing("current function"))?;
+                .ok_or(CodegenError::Missing("current function", None))?;
             let func_name = func.get_name().to_str().unwrap();
             let func_id = self
                 .analyzer
                 .get_function_id_by_name(func_name)
-                .ok_or(CodegenError::Missing("function id"))?;
+                .ok_or(CodegenError::Missing("function id", None))?;
             let func_info = self
                 .analyzer
                 .get_function_by_id(func_id)
-                .ok_or(CodegenError::Missing("function info"))?;
+                .ok_or(CodegenError::Missing("function info", None))?;
             let func_ret_ty = &func_info.ret_type;
 
             // 获取表达式类型
             let expr_ty = self
                 .analyzer
                 .get_expr_type(expr_node.text_range())
-                .ok_or(CodegenError::Missing("expr type"))?;
+                .ok_or(CodegenError::Missing("expr type", None))?;
 
             // 如果类型不同，插入转换
             let val_casted = self.cast_value(val, expr_ty, func_ret_ty)?;
 
             self.builder.build_return(Some(&val_casted)).ok();
         } else {
-            self.builder.build_return(None).ok();
+            let func = self
+                .symbols
+                .current_function
+                .ok_or(CodegenError::Missing("current function", None))?;
+            self.build_default_return(func);
         }
         Ok(())
     }
@@ -0,0 +1,54 @@
+//! 目标机器：输出目标文件和汇编
+
+use std::path::Path;
+
+use inkwell::OptimizationLevel;
+use inkwell::targets::{
+    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
+};
+
+use super::Program;
+use crate::error::{CodegenError, Result};
+
+impl Program<'_, '_> {
+    /// 创建本机的目标机器，目标文件和汇编输出共用同一套配置
+    pub fn target_machine(opt: OptimizationLevel) -> Result<TargetMachine> {
+        Target::initialize_all(&InitializationConfig::default());
+        let triple = TargetMachine::get_default_triple();
+        let target =
+            Target::from_triple(&triple).map_err(|e| CodegenError::TargetMachine(e.to_string()))?;
+
+        target
+            .create_target_machine(
+                &triple,
+                "generic",
+                "",
+                opt,
+                RelocMode::PIC,
+                CodeModel::Default,
+            )
+            .ok_or_else(|| {
+                CodegenError::TargetMachine("failed to create target machine".to_string())
+            })
+    }
+
+    /// 输出目标文件（.o）
+    pub fn emit_object(&self, path: &Path, opt: OptimizationLevel) -> Result<()> {
+        self.emit_file(path, opt, FileType::Object)
+    }
+
+    /// 输出汇编文本（.s），便于查看生成的代码
+    pub fn emit_asm(&self, path: &Path, opt: OptimizationLevel) -> Result<()> {
+        self.emit_file(path, opt, FileType::Assembly)
+    }
+
+    fn emit_file(&self, path: &Path, opt: OptimizationLevel, file_type: FileType) -> Result<()> {
+        let machine = Self::target_machine(opt)?;
+        self.module.set_triple(&machine.get_triple());
+        self.module
+            .set_data_layout(&machine.get_target_data().get_data_layout());
+        machine
+            .write_to_file(self.module, file_type, path)
+            .map_err(|e| CodegenError::LlvmWrite(e.to_string()))
+    }
+}
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use analyzer::{module::Module, project::Project};
+use analyzer::{module::Module, project::Project, r#type::Ty};
 use inkwell::context::Context;
 use syntax::{
     SyntaxNode,
@@ -9,9 +9,35 @@ use syntax::{
 };
 use vfs::Vfs;
 
+use crate::error::{CodegenError, Result};
 use crate::llvm_ir;
 
 fn try_it(code: &str) -> String {
+    compile(code, false).unwrap()
+}
+
+fn compile(code: &str, allow_semantic_errors: bool) -> Result<String> {
+    with_program(code, allow_semantic_errors, |p| {
+        p.module.print_to_string().to_string()
+    })
+}
+
+/// 编译源码并把编译后的 `Program` 交给 `f` 检查
+fn with_program<R>(
+    code: &str,
+    allow_semantic_errors: bool,
+    f: impl FnOnce(&llvm_ir::Program) -> R,
+) -> Result<R> {
+    with_program_options(code, allow_semantic_errors, false, f)
+}
+
+/// 同 `with_program`，可以打开数组下标越界检查
+fn with_program_options<R>(
+    code: &str,
+    allow_semantic_errors: bool,
+    bounds_check: bool,
+    f: impl FnOnce(&llvm_ir::Program) -> R,
+) -> Result<R> {
     let parser = parser::parse::Parser::new(code);
     let (green_node, errors) = parser.parse();
     assert!(errors.is_empty(), "Parser errors: {:?}", errors);
@@ -23,11 +49,12 @@ fn try_it(code: &str) -> String {
     module.file_id = file_id;
 
     Project::allocate_module_symbols(&mut module);
+    Project::resolve_type_aliases(&mut module);
 
     module.analyze();
 
     assert!(
-        module.semantic_errors.is_empty(),
+        allow_semantic_errors || module.semantic_errors.is_empty(),
         "Analyzer errors: {:?}",
         module.semantic_errors
     );
@@ -46,11 +73,12 @@ fn try_it(code: &str) -> String {
         analyzer: &module,
         symbols: Default::default(),
         string_constants: HashMap::new(),
+        bounds_check,
     };
 
-    program.compile_comp_unit(comp_unit).unwrap();
+    program.compile_comp_unit(comp_unit)?;
 
-    program.module.print_to_string().to_string()
+    Ok(f(&program))
 }
 
 #[test]
@@ -106,7 +134,8 @@ fn test_if_stmt() {
         return 0;
     }
     "#;
-    insta::assert_snapshot!(try_it(code));
+    // x 在赋值前被读取（UseBeforeInit），这里只关心 if 的代码生成
+    insta::assert_snapshot!(compile(code, true).unwrap());
 }
 
 #[test]
@@ -163,3 +192,1135 @@ fn test_array_initialize() {
     "#;
     insta::assert_snapshot!(try_it(code));
 }
+
+#[test]
+fn test_error_carries_range() {
+    let code = r#"
+    fn main() -> i32 {
+        break;
+        return 0;
+    }
+    "#;
+    let err = compile(code, true).unwrap_err();
+    assert!(matches!(err, CodegenError::Unsupported(..)), "{err:?}");
+    let start = code.find("break").unwrap() as u32;
+    assert_eq!(
+        err.range(),
+        Some(tools::TextRange::new(start, start + "break;".len() as u32))
+    );
+}
+
+#[test]
+fn test_function_attributes() {
+    let code = r#"
+    @inline fn add(a: i32, b: i32) -> i32 {
+        return a + b;
+    }
+    @noinline fn main() -> i32 {
+        return add(1, 2);
+    }
+    "#;
+    let ir = try_it(code);
+    // `define ... @name(...) #N {` 引用的属性组 `attributes #N = { ... }`
+    let attributes_of = |name: &str| {
+        let define = ir
+            .lines()
+            .find(|l| l.starts_with("define") && l.contains(&format!("@{name}(")))
+            .unwrap_or_else(|| panic!("no definition of {name}: {ir}"));
+        let group = define
+            .split_whitespace()
+            .find(|w| w.starts_with('#'))
+            .unwrap_or_else(|| panic!("{name} has no attribute group: {ir}"));
+        ir.lines()
+            .find_map(|l| l.strip_prefix(&format!("attributes {group} = ")))
+            .unwrap_or_else(|| panic!("missing attributes {group}: {ir}"))
+            .to_string()
+    };
+    let add = attributes_of("add");
+    assert!(
+        add.contains("alwaysinline") && !add.contains("noinline"),
+        "{ir}"
+    );
+    let main = attributes_of("main");
+    assert!(
+        main.contains("noinline") && !main.contains("alwaysinline"),
+        "{ir}"
+    );
+}
+
+#[test]
+fn test_bool_arithmetic_widening() {
+    let code = r#"
+    fn add_one(b: bool) -> i32 {
+        return b + 1;
+    }
+    fn not_plus_one(x: i32) -> i32 {
+        return !x + 1;
+    }
+    fn main() -> i32 {
+        return add_one(true) + not_plus_one(0);
+    }
+    "#;
+    let ir = try_it(code);
+    // bool 参与算术前必须显式 zext 到 i32，不应出现 i1 宽度的加法
+    assert!(ir.contains("zext i1"), "{ir}");
+    assert!(!ir.contains("add i1"), "{ir}");
+}
+
+#[test]
+fn test_chained_field_index_lvalue() {
+    let code = r#"
+    struct Inner { values: [i32; 4] }
+    struct Outer { tag: i32, inner: struct Inner }
+    fn main() -> i32 {
+        let s: struct Outer;
+        let p: *mut struct Outer = &s;
+        s.inner.values[1] = 5;
+        (*p).inner.values[2] = 7;
+        p->inner.values[3] = 9;
+        return s.inner.values[1] + s.inner.values[2] + p->inner.values[3];
+    }
+    "#;
+    let ir = try_it(code);
+    // 三种链式写法都应落到 store，而不是报 "not an lvalue"
+    assert_eq!(ir.matches("store i32 5").count(), 1, "{ir}");
+    assert_eq!(ir.matches("store i32 7").count(), 1, "{ir}");
+    assert_eq!(ir.matches("store i32 9").count(), 1, "{ir}");
+}
+
+#[test]
+fn test_return_in_nested_loops() {
+    let code = r#"
+    fn find(n: i32) -> i32 {
+        let i: i32 = 0;
+        while (i < n) {
+            let j: i32 = 0;
+            while (j < n) {
+                if (i * j == 6) {
+                    return i + j;
+                }
+                j = j + 1;
+            }
+            i = i + 1;
+        }
+        return -1;
+    }
+    fn spin() -> i32 {
+        while (1) {
+            while (1) return 3;
+        }
+    }
+    fn main() -> i32 {
+        return find(5) + spin();
+    }
+    "#;
+    // 深层 return 之后不能再追加跳转，否则会出现终结指令之后的指令
+    let verified = with_program(code, false, |p| {
+        p.module.verify().map_err(|e| e.to_string())
+    })
+    .unwrap();
+    assert!(verified.is_ok(), "{verified:?}\n{}", try_it(code));
+}
+
+#[test]
+fn test_array_of_struct_init() {
+    let code = r#"
+    struct Point { x: i32, y: i32 }
+    let g: [struct Point; 2] = {{1, 2}, {3, 4}};
+    fn main() -> i32 {
+        let v: i32 = 4;
+        let a: [struct Point; 2] = {{1, 2}, {3, v}};
+        return a[1].y + g[1].y;
+    }
+    "#;
+    let ir = try_it(code);
+    // 全局变量直接生成常量数组
+    assert!(
+        ir.contains("[%Point { i32 1, i32 2 }, %Point { i32 3, i32 4 }]"),
+        "{ir}"
+    );
+    // 运行时初始化按数组类型计算元素指针，再逐字段 store
+    assert!(ir.contains("getelementptr [2 x %Point], ptr"), "{ir}");
+    assert!(
+        !ir.contains("getelementptr %Point, ptr %a, i32 0, i32 1"),
+        "{ir}"
+    );
+}
+
+#[test]
+fn test_type_conversion_cache() {
+    let code = r#"
+    fn main() -> i32 {
+        let a: [[i8; 4]; 4] = {};
+        let b: [[u8; 4]; 4] = {};
+        let i: i32 = 0;
+        let sum: i64 = 0i64;
+        while (i < 4) {
+            sum = sum + a[i][0] + a[i][1];
+            sum = sum + b[i][2] + b[i][3];
+            i = i + 1;
+        }
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // i8 和 u8 共用同一个 LLVM 类型，缓存后仍按各自的符号扩展，不截断
+    assert_eq!(ir.matches("sext i8").count(), 2, "{ir}");
+    assert_eq!(ir.matches("zext i8").count(), 2, "{ir}");
+    assert!(!ir.contains(" trunc "), "{ir}");
+}
+
+#[test]
+fn test_type_cache_per_function() {
+    let code = r#"
+    fn main() -> i32 {
+        let a: [[i32; 4]; 4] = {};
+        a[1][2] = a[0][1];
+        a[2][3] = a[1][2];
+        a[3][0] = a[2][3];
+        return 0;
+    }
+    "#;
+    let cached: HashSet<Ty> = with_program(code, false, |p| {
+        p.symbols.type_cache.borrow().keys().cloned().collect()
+    })
+    .unwrap();
+    // 6 次数组访问只转换 [[i32; 4]; 4]、[i32; 4] 和 i32 各一次
+    let row = Ty::Array(Box::new(Ty::I32), Some(4));
+    let expected = HashSet::from([Ty::Array(Box::new(row.clone()), Some(4)), row, Ty::I32]);
+    assert_eq!(cached, expected);
+}
+
+#[test]
+fn test_enum_variant_const() {
+    let code = r#"
+    enum Color {
+        Red,
+        Green = 5,
+        Blue,
+    }
+    fn main() -> i32 {
+        return Color::Blue;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    // 枚举成员按编译期常量直接折叠
+    assert!(ir.contains("ret i32 6"), "{ir}");
+}
+
+#[test]
+fn test_switch_three_way() {
+    let code = r#"
+    fn classify(x: i32) -> i32 {
+        let r: i32 = 0;
+        switch (x) {
+            case 1:
+                r = 10;
+                break;
+            case 2:
+                r = 20;
+            default:
+                r = r + 1;
+        }
+        return r;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    assert!(ir.contains("switch i32"), "{ir}");
+    assert!(ir.contains("i32 1, label %switch.case"), "{ir}");
+    assert!(ir.contains("i32 2, label %switch.case"), "{ir}");
+    assert!(ir.contains("label %switch.default ["), "{ir}");
+    // case 2 没有 break，贯穿到 default
+    assert!(
+        ir.contains("store i32 20, ptr %r") && ir.contains("br label %switch.default"),
+        "{ir}"
+    );
+}
+
+#[test]
+fn test_switch_nested_block_break() {
+    let code = r#"
+    fn f(v: i32) -> i32 {
+        let x: i32 = 0;
+        switch (v) {
+            case 1:
+                { break; }
+                x = 7;
+            default:
+                x = 2;
+        }
+        return x;
+    }
+    "#;
+    let ir = with_program(code, true, |p| {
+        assert!(p.module.verify().is_ok(), "{}", p.module.print_to_string());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 嵌套块中的 break 之后的语句不可达，不能生成在终结指令之后
+    assert!(!ir.contains("store i32 7"), "{ir}");
+}
+
+#[test]
+fn test_for_count_down() {
+    let code = r#"
+    fn main() -> i32 {
+        let sum: i32 = 0;
+        let i: i32 = 0;
+        for (i = 3; i > 0; i = i - 1) {
+            sum = sum + i;
+        }
+        return sum;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 条件在循环体之前求值，步进在循环体之后执行
+    assert!(ir.contains("for.cond:"), "{ir}");
+    assert!(ir.contains("icmp sgt i32"), "{ir}");
+    assert!(ir.contains("br i1 %"), "{ir}");
+    assert!(ir.contains("for.step:"), "{ir}");
+    assert!(ir.contains("sub i32"), "{ir}");
+}
+
+#[test]
+fn test_for_infinite_with_break() {
+    let code = r#"
+    fn main() -> i32 {
+        let n: i32 = 0;
+        for (;;) {
+            n = n + 1;
+            if (n == 4) {
+                break;
+            }
+        }
+        return n;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 没有条件时 for.cond 无条件跳到循环体，只能通过 break 离开
+    let cond_first_inst = ir
+        .split("for.cond:")
+        .nth(1)
+        .and_then(|rest| rest.lines().nth(1))
+        .unwrap();
+    assert_eq!(cond_first_inst.trim(), "br label %for.body", "{ir}");
+    assert!(ir.contains("br label %for.end"), "{ir}");
+}
+
+#[test]
+fn test_compare_result_as_i32() {
+    let code = r#"
+    fn one() -> i32 {
+        let x: i32 = (1 < 2);
+        return x;
+    }
+    fn lt(a: i32, b: i32) -> i32 {
+        let y: i32 = a < b;
+        return y;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 常量比较折叠为 1，运行期比较结果从 i1 零扩展到 i32
+    assert!(ir.contains("store i32 1, ptr %x"), "{ir}");
+    assert!(ir.contains("icmp slt i32"), "{ir}");
+    assert!(ir.contains("zext i1"), "{ir}");
+    assert!(!ir.contains("sext i1"), "{ir}");
+}
+
+#[test]
+fn test_compare_as_if_condition() {
+    let code = r#"
+    fn min(a: i32, b: i32) -> i32 {
+        if (a < b) {
+            return a;
+        }
+        return b;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 作为条件时直接使用 i1，不做扩展
+    assert!(ir.contains("icmp slt i32"), "{ir}");
+    assert!(ir.contains("br i1 %"), "{ir}");
+    assert!(!ir.contains("zext"), "{ir}");
+}
+
+#[test]
+fn test_bounds_check_flag() {
+    let code = r#"
+    fn get(i: i32) -> i32 {
+        let a: [i32; 4] = {1, 2, 3, 4};
+        let b: [[i32; 3]; 2];
+        b[1][2] = a[2];
+        return a[i] + b[i][1];
+    }
+    "#;
+    let emit = |bounds_check| {
+        with_program_options(code, false, bounds_check, |p| {
+            assert!(p.module.verify().is_ok());
+            p.module.print_to_string().to_string()
+        })
+        .unwrap()
+    };
+
+    let ir = emit(false);
+    assert!(!ir.contains("llvm.trap"), "{ir}");
+    assert!(!ir.contains("bounds."), "{ir}");
+
+    let ir = emit(true);
+    assert!(ir.contains("call void @llvm.trap()"), "{ir}");
+    assert!(ir.contains("sext i32"), "{ir}");
+    // 常量下标不检查，只有 a[i] 和 b[i] 两处
+    assert_eq!(ir.matches("icmp uge i64").count(), 2, "{ir}");
+}
+
+#[test]
+fn test_bounds_check_narrow_index() {
+    let code = r#"
+    fn get(j: u8) -> i32 {
+        let a: [i32; 1000];
+        let i: i8 = -1;
+        return a[i] + a[j];
+    }
+    "#;
+    let ir = with_program_options(code, false, true, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 长度超出 i8/u8 的范围也要检查：-1 符号扩展后按无符号比较越界，u8 零扩展
+    assert!(ir.contains("sext i8"), "{ir}");
+    assert!(ir.contains("zext i8"), "{ir}");
+    assert_eq!(ir.matches("icmp uge i64").count(), 2, "{ir}");
+    assert!(ir.contains("call void @llvm.trap()"), "{ir}");
+}
+
+#[test]
+fn test_void_main_exits_zero() {
+    let code = r#"
+    fn main() {
+        let x: i32 = 1;
+        if (x > 0) {
+            return;
+        }
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 入口仍返回 i32，提前 return 和函数末尾都返回 0
+    assert!(ir.contains("define i32 @main()"), "{ir}");
+    assert_eq!(ir.matches("ret i32 0").count(), 2, "{ir}");
+    assert!(!ir.contains("ret void"), "{ir}");
+}
+
+#[test]
+fn test_int_main_returns_value() {
+    let code = r#"
+    fn helper() {
+        return;
+    }
+    fn main() -> i32 {
+        helper();
+        let x: i32 = 7;
+        return x;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("define i32 @main()"), "{ir}");
+    assert!(ir.contains("ret i32 %"), "{ir}");
+    // 其他 void 函数不受影响
+    assert!(ir.contains("define void @helper()"), "{ir}");
+    assert!(ir.contains("ret void"), "{ir}");
+}
+
+#[test]
+fn test_const_globals_in_reverse_order() {
+    let code = r#"
+    let c: const i32 = b * 2;
+    let b: const i32 = a + 1;
+    let a: const i32 = 3;
+    fn main() -> i32 {
+        return c;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("@c = constant i32 8"), "{ir}");
+    assert!(ir.contains("@b = constant i32 4"), "{ir}");
+    assert!(ir.contains("@a = constant i32 3"), "{ir}");
+}
+
+#[test]
+fn test_mixed_width_arithmetic() {
+    let code = r#"
+    fn f(a: i8, b: i64, c: u8, d: u64) -> i64 {
+        let y: u64 = c * d;
+        if (a < b) {
+            return a + b;
+        }
+        return 0i64;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 有符号扩展 i8，无符号扩展 u8
+    assert!(ir.contains("sext i8"), "{ir}");
+    assert!(ir.contains("zext i8"), "{ir}");
+}
+
+#[test]
+fn test_static_assert_emits_no_code() {
+    let code = r#"
+    let N: const i32 = 4;
+    fn main() -> i32 {
+        static_assert(N * 4 == 16);
+        return N;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(!ir.contains("static_assert"), "{ir}");
+}
+
+#[test]
+fn test_address_of_whole_array() {
+    let code = r#"
+    fn first(p: *mut i32) -> i32 {
+        return *p;
+    }
+    fn main() -> i32 {
+        let a: [i32; 4] = {1, 2, 3, 4};
+        let p: *mut [i32; 4] = &a;
+        let q: *mut i32 = &a[0];
+        if (*p == q) {
+            return first(*p);
+        }
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // `*p` 直接得到首元素地址，不会把整个数组 load 出来
+    assert!(!ir.contains("load [4 x i32]"), "{ir}");
+}
+
+#[test]
+fn test_break_in_nested_blocks() {
+    let code = r#"
+    fn main() -> i32 {
+        let i: i32 = 0;
+        while (1) {
+            {
+                {
+                    break;
+                }
+                i = i + 1;
+            }
+        }
+        while (i < 10) {
+            i = i + 1;
+            {
+                if (i % 2 == 0) {
+                    {
+                        continue;
+                    }
+                }
+            }
+            i = i + 2;
+        }
+        return i;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 嵌套块中的 break 跳到外层 while 的结束块，后面不可达的语句不生成
+    assert!(ir.contains("br label %while.end\n"), "{ir}");
+    assert!(ir.contains("br label %while.cond"), "{ir}");
+}
+
+#[test]
+fn test_bool_global_initializers() {
+    let code = r#"
+    let flag: const bool = 1 < 2;
+    let off: bool = 3 == 4;
+    let n: const i32 = 1 < 2;
+    let w: i64 = 7;
+    fn main() -> i32 {
+        if (flag && !off) {
+            return n;
+        }
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // bool 全局变量存为 i1，读取时按 i1 load
+    assert!(ir.contains("@flag = constant i1 true"), "{ir}");
+    assert!(ir.contains("@off = global i1 false"), "{ir}");
+    assert!(ir.contains("load i1, ptr @off"), "{ir}");
+    // 初始值按变量类型转换
+    assert!(ir.contains("@n = constant i32 1"), "{ir}");
+    assert!(ir.contains("@w = global i64 7"), "{ir}");
+}
+
+#[test]
+fn test_multi_var_def() {
+    let code = r#"
+    let g: i32 = 4, h: const i64 = 5;
+    fn main() -> i32 {
+        let x: i32 = 1, y: i32 = 2;
+        for (let i: i32 = 0, j: i32 = 3; i < j; i = i + 1) {
+            x = x + y;
+        }
+        return x + g;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("@g = global i32 4"), "{ir}");
+    assert!(ir.contains("@h = constant i64 5"), "{ir}");
+    for name in ["x", "y", "i", "j"] {
+        assert!(ir.contains(&format!("%{name} = alloca i32")), "{ir}");
+    }
+    assert!(ir.contains("store i32 1, ptr %x"), "{ir}");
+    assert!(ir.contains("store i32 2, ptr %y"), "{ir}");
+}
+
+#[test]
+fn test_alloc_emits_malloc() {
+    let code = r#"
+    struct P { x: i32, y: i64 }
+    fn main() -> i32 {
+        let n: i32 = 3;
+        let a: *mut i32 = alloc<i32>(n);
+        let p: *mut struct P = alloc<struct P>(1);
+        *a = 1;
+        p->x = 2;
+        return *a + p->x;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("declare ptr @malloc(i64)"), "{ir}");
+    assert_eq!(ir.matches("call ptr @malloc").count(), 2, "{ir}");
+    assert!(ir.contains("sext i32"), "{ir}");
+}
+
+#[test]
+fn test_extern_fn_keeps_name() {
+    let code = r#"
+    extern "C" fn printf(format: *const u8, ...) -> i32;
+    extern fn puts(s: *const u8) -> i32;
+    fn main() -> i32 {
+        puts("hi");
+        return printf("%d\n", 1);
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("declare i32 @printf(ptr, ...)"), "{ir}");
+    assert!(ir.contains("declare i32 @puts(ptr)"), "{ir}");
+    assert!(ir.contains("call i32 (ptr, ...) @printf"), "{ir}");
+}
+
+#[test]
+fn test_restrict_param_noalias() {
+    let code = r#"
+    fn copy(dst: *restrict mut i32, n: i32, src: *restrict const i32) {
+        *dst = *src + n;
+    }
+    fn main() -> i32 {
+        let a: i32 = 1;
+        let b: i32 = 2;
+        copy(&a, 1, &b);
+        return a;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    let define = ir
+        .lines()
+        .find(|l| l.starts_with("define void @copy"))
+        .unwrap_or_else(|| panic!("{ir}"));
+    assert!(define.contains("(ptr noalias %"), "{define}");
+    assert_eq!(define.matches("noalias").count(), 2, "{define}");
+}
+
+#[test]
+fn test_sizeof_is_constant() {
+    let code = r#"
+    struct P { a: i8, b: i64 }
+    fn main() -> i32 {
+        let n: u64 = sizeof(struct P);
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("store i64 16"), "{ir}");
+    assert!(!ir.contains("@sizeof"), "{ir}");
+}
+
+#[test]
+fn test_2d_array_decay_argument() {
+    let code = r#"
+    fn get(rows: *mut [i32; 3]) -> i32 {
+        return rows[1][2];
+    }
+    fn main() -> i32 {
+        let a: [[i32; 3]; 2] = {{1, 2, 3}, {4, 5, 6}};
+        return get(a);
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 实参 decay 成第一行的地址，被调函数按行的大小步进
+    assert!(ir.contains("getelementptr [2 x [3 x i32]]"), "{ir}");
+    assert!(ir.contains("getelementptr [3 x i32], ptr"), "{ir}");
+}
+
+#[test]
+fn test_if_without_braces() {
+    let code = r#"
+    fn pick(a: i32, b: i32) -> i32 {
+        let x: i32 = 0;
+        if (a > 0)
+            if (b > 0) x = 1;
+            else x = 2;
+        else
+            x = 3;
+        return x;
+    }
+    fn main() -> i32 { return pick(1, 0); }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert_eq!(ir.matches("br i1").count(), 2, "{ir}");
+    for value in ["i32 1, ptr", "i32 2, ptr", "i32 3, ptr"] {
+        assert!(ir.contains(&format!("store {value}")), "{ir}");
+    }
+}
+
+#[test]
+fn test_field_access_natural_alignment() {
+    let code = r#"
+    struct P { a: u8, b: i64, c: *mut i32 }
+    let g: i64 = 1;
+    fn main() -> i32 {
+        let p: struct P;
+        p.a = 1u8;
+        p.b = g;
+        p.c = null;
+        let b: i64 = p.b;
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 默认 data layout 下 i64 只有 4 字节对齐，这里必须按 8 字节
+    assert!(ir.contains("@g = global i64 1, align 8"), "{ir}");
+    assert!(ir.contains("alloca %P, align 8"), "{ir}");
+    assert!(ir.contains("store i8 1, ptr %"), "{ir}");
+    assert!(ir.contains("store i64 %"), "{ir}");
+    for line in ir
+        .lines()
+        .filter(|l| l.contains("store i64") || l.contains("load i64"))
+    {
+        assert!(line.ends_with("align 8"), "{line}");
+    }
+    for line in ir.lines().filter(|l| l.contains("store i8 1")) {
+        assert!(line.ends_with("align 1"), "{line}");
+    }
+    for line in ir.lines().filter(|l| l.contains("store ptr null")) {
+        assert!(line.ends_with("align 8"), "{line}");
+    }
+}
+
+#[test]
+fn test_nested_for_shadowed_loop_var() {
+    let code = r#"
+    fn main() -> i32 {
+        let count: i32 = 0;
+        for (let i: i32 = 0; i < 4; i = i + 1) {
+            for (let i: i32 = 0; i < 10; i = i + 1) {
+                if (i == 3) {
+                    break;
+                }
+                if (i == 1) {
+                    continue;
+                }
+                count = count + 1;
+            }
+            count = count + i * 10;
+        }
+        return count;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // count 和两个 i 各有一个 alloca，内外层循环各有自己的块
+    assert_eq!(ir.matches("= alloca i32").count(), 3, "{ir}");
+    assert_eq!(ir.matches("\nfor.end").count(), 2, "{ir}");
+}
+
+#[test]
+fn test_const_if_elides_dead_branch() {
+    let code = r#"
+    fn trace() -> i32 { return 7; }
+    fn release() -> i32 { return 9; }
+    fn main() -> i32 {
+        let r: i32 = 0;
+        if (cfg("trace")) {
+            r = trace();
+        } else {
+            r = release();
+        }
+        return r;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 没有启用 trace，只生成 else 分支，也没有条件跳转
+    assert!(ir.contains("call i32 @release()"), "{ir}");
+    assert!(!ir.contains("call i32 @trace()"), "{ir}");
+    assert!(!ir.contains("br i1"), "{ir}");
+}
+
+#[test]
+fn test_designated_array_init() {
+    let code = r#"
+    let g: [i32; 6] = { [0] = 1, [5] = 9, [2] = 4, 5 };
+    fn main() -> i32 {
+        let n: i32 = 7;
+        let a: [i32; 4] = { [3] = n, [1] = 2 };
+        return g[3] + a[3] + a[0];
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 常量全局数组：未指定的元素补零
+    assert!(
+        ir.contains("[6 x i32] [i32 1, i32 0, i32 4, i32 5, i32 0, i32 9]"),
+        "{ir}"
+    );
+    // 非常量局部数组：先整体清零，再只 store 指定的元素
+    assert!(ir.contains("store [4 x i32] zeroinitializer"), "{ir}");
+    assert!(ir.contains("[4 x i32], ptr %a, i32 0, i32 3"), "{ir}");
+    assert!(ir.contains("[4 x i32], ptr %a, i32 0, i32 1"), "{ir}");
+    assert!(!ir.contains("[4 x i32], ptr %a, i32 0, i32 2"), "{ir}");
+}
+
+#[test]
+fn test_shift_ops() {
+    let code = r#"
+    fn shift(a: i32, b: u32, n: u8) -> i32 {
+        let c: u32 = b >> n;
+        return (a << n) + (a >> 1);
+    }
+    fn main() -> i32 { return shift(1, 2u32, 3u8); }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 移位数扩展到左操作数的位宽，右移按左操作数的符号选择指令
+    assert!(ir.contains("zext i8"), "{ir}");
+    assert!(ir.contains("= shl i32"), "{ir}");
+    assert!(ir.contains("= lshr i32"), "{ir}");
+    assert!(ir.contains("= ashr i32"), "{ir}");
+}
+
+#[test]
+fn test_type_alias() {
+    let code = r#"
+    type Int = i64;
+    type Arr = [Int; 2];
+    let G: Arr = { 1, 2 };
+    fn get(a: Int) -> Int { return a + G[1]; }
+    fn main() -> i32 { get(1i64); return 0; }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    assert!(ir.contains("@G = global [2 x i64]"), "{ir}");
+    assert!(ir.contains("define i64 @get(i64"), "{ir}");
+}
+
+#[test]
+fn test_local_const_array_index_folding() {
+    let code = r#"
+    fn main() -> i32 {
+        let a: [const i32; 3] = { 10, 20, 30 };
+        return a[2];
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    // 常量下标直接使用折叠后的元素值，不再读取数组
+    assert!(ir.contains("ret i32 30"), "{ir}");
+}
+
+#[test]
+fn test_streq() {
+    let code = r#"
+    fn is_a(s: *const u8) -> bool { return streq(s, "a"); }
+    fn main() -> i32 {
+        if (streq("x", "x")) { return 1; }
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        assert!(p.module.verify().is_ok());
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    // 只有运行期比较调用 strcmp
+    assert!(ir.contains("declare i32 @strcmp(ptr, ptr)"), "{ir}");
+    assert_eq!(ir.matches("call i32 @strcmp").count(), 1, "{ir}");
+}
+
+#[test]
+fn test_zero_initializer() {
+    let code = r#"
+    struct P { x: i32, y: i32 }
+    fn main() -> i32 {
+        let a: [i32; 3] = {};
+        let p: struct P = {};
+        return a[0] + p.x;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    assert!(ir.contains("store [3 x i32] zeroinitializer"), "{ir}");
+    assert!(ir.contains("store %P zeroinitializer"), "{ir}");
+}
+
+#[test]
+fn test_struct_forward_decl() {
+    let code = r#"
+    struct B;
+    struct A { b: *mut struct B, v: i32 }
+    struct B { a: *mut struct A }
+    fn main() -> i32 {
+        let a: struct A = { null, 7 };
+        let b: struct B = { &a };
+        a.b = &b;
+        return b.a->b->a->v;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    assert!(ir.contains("%A = type { ptr, i32 }"), "{ir}");
+    assert!(ir.contains("%B = type { ptr }"), "{ir}");
+}
+
+#[test]
+fn test_constant_false_while() {
+    let code = r#"
+    fn main() -> i32 {
+        let x: i32 = 1;
+        while (0) {
+            x = x + 1;
+        }
+        if (1 > 2) {
+            x = 3;
+        }
+        return x;
+    }
+    "#;
+    // 常量条件会给出提示
+    let ir = compile(code, true).unwrap();
+    assert!(!ir.contains("while.cond"), "{ir}");
+    assert!(!ir.contains("then"), "{ir}");
+}
+
+#[test]
+fn test_emit_asm() {
+    let code = r#"
+    fn answer() -> i32 {
+        return 42;
+    }
+    "#;
+    let path = std::env::temp_dir().join(format!("airyc_emit_asm_{}.s", std::process::id()));
+    with_program(code, false, |p| {
+        p.emit_asm(&path, inkwell::OptimizationLevel::None)
+    })
+    .unwrap()
+    .unwrap();
+    let asm = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(asm.contains("answer:"), "{asm}");
+}
+
+#[test]
+fn test_mutual_recursion() {
+    let code = r#"
+    fn is_even(n: i32) -> bool {
+        if (n == 0) {
+            return true;
+        }
+        return is_odd(n - 1);
+    }
+    fn is_odd(n: i32) -> bool {
+        if (n == 0) {
+            return false;
+        }
+        return is_even(n - 1);
+    }
+    fn main() -> i32 {
+        if (is_even(10)) {
+            return 1;
+        }
+        return 0;
+    }
+    "#;
+    let ir = with_program(code, false, |p| {
+        p.module.verify().unwrap();
+        p.module.print_to_string().to_string()
+    })
+    .unwrap();
+    for callee in ["@is_odd(", "@is_even("] {
+        assert!(
+            ir.lines()
+                .any(|l| l.contains(" call ") && l.contains(callee)),
+            "{ir}"
+        );
+    }
+    // 每个函数只声明一次
+    assert!(!ir.contains("is_odd.1"), "{ir}");
+}
+
+#[test]
+fn test_repeat_array_init() {
+    let code = r#"
+    let A: [i32; 3] = { 7; 3 };
+    fn main() -> i32 {
+        let b: [i32; 4] = { 5; 2 };
+        return A[0] + b[1];
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    assert!(ir.contains("[i32 7, i32 7, i32 7]"), "{ir}");
+}
+
+#[test]
+fn test_pointer_backward_iteration() {
+    let code = r#"
+    fn sum_back(arr: *mut i32, n: i32, step: u8) -> i32 {
+        let p: *mut i32 = arr + n - 1;
+        let sum: i32 = 0;
+        while (p >= arr) {
+            sum = sum + *p;
+            p = p - step;
+        }
+        return sum;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    // 指针比较用无符号谓词，u8 偏移量先 zext 到 i64 再取负
+    assert!(ir.contains("icmp uge i64"), "{ir}");
+    assert!(ir.contains("zext i8"), "{ir}");
+    assert!(ir.contains("sub i64 0"), "{ir}");
+    assert!(ir.contains("getelementptr i32, ptr"), "{ir}");
+}
+
+#[test]
+fn test_global_address_init() {
+    let code = r#"
+    let g: i32 = 3;
+    let p: *mut i32 = &g;
+    let q: *const i32 = &g;
+    fn main() -> i32 {
+        *p = 4;
+        return *q;
+    }
+    "#;
+    let ir = compile(code, false).unwrap();
+    assert!(ir.contains("@p = global ptr @g"), "{ir}");
+    assert!(ir.contains("@q = constant ptr @g"), "{ir}");
+}
+
+#[test]
+fn test_empty_source() {
+    for code in ["", "  \n", "// only a comment\n"] {
+        let ir = compile(code, false).unwrap();
+        assert!(!ir.contains("define"), "{ir}");
+    }
+}
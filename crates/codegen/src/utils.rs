@@ -5,7 +5,7 @@ use analyzer::r#type::Ty;
 use analyzer::value::Value;
 use inkwell::basic_block::BasicBlock;
 use inkwell::types::{BasicType, BasicTypeEnum};
-use inkwell::values::{BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue};
 use inkwell::{AddressSpace, IntPredicate};
 use syntax::ast::AstNode;
 use tools::TextRange;
@@ -70,13 +70,87 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         } else {
             builder.position_at_end(entry);
         }
-        builder
+        let alloca = builder
             .build_alloca(ty, name)
-            .map_err(|_| CodegenError::LlvmBuild("alloca failed"))
+            .map_err(|_| CodegenError::LlvmBuild("alloca failed"))?;
+        if let Some(instr) = alloca.as_instruction() {
+            instr
+                .set_alignment(Self::natural_alignment(ty))
+                .map_err(|_| CodegenError::LlvmBuild("set alignment"))?;
+        }
+        Ok(alloca)
+    }
+
+    /// 类型的自然对齐（字节）：标量按自身大小，数组按元素，struct 按字段的最大对齐
+    ///
+    /// 生成代码时模块还没有设置目标的 data layout，LLVM 默认布局下 i64 只按 4 字节对齐，
+    /// 所以变量和 load/store 都显式设置对齐
+    pub(crate) fn natural_alignment(ty: BasicTypeEnum<'ctx>) -> u32 {
+        match ty {
+            BasicTypeEnum::IntType(t) => t.get_bit_width().div_ceil(8).next_power_of_two(),
+            // 目标平台为 64 位
+            BasicTypeEnum::PointerType(_) => 8,
+            BasicTypeEnum::ArrayType(t) => Self::natural_alignment(t.get_element_type()),
+            BasicTypeEnum::StructType(t) if t.is_packed() => 1,
+            BasicTypeEnum::StructType(t) => t
+                .get_field_types_iter()
+                .map(Self::natural_alignment)
+                .max()
+                .unwrap_or(1),
+            // 语言中没有浮点和向量类型
+            _ => 1,
+        }
+    }
+
+    /// 按 `ty` 的自然对齐 load
+    pub(crate) fn build_aligned_load(
+        &self,
+        ty: BasicTypeEnum<'ctx>,
+        ptr: PointerValue<'ctx>,
+        name: &str,
+        err: &'static str,
+    ) -> Result<BasicValueEnum<'ctx>> {
+        let value = self
+            .builder
+            .build_load(ty, ptr, name)
+            .map_err(|_| CodegenError::LlvmBuild(err))?;
+        if let Some(instr) = value.as_instruction_value() {
+            instr
+                .set_alignment(Self::natural_alignment(ty))
+                .map_err(|_| CodegenError::LlvmBuild("set alignment"))?;
+        }
+        Ok(value)
+    }
+
+    /// 按值类型的自然对齐 store
+    pub(crate) fn build_aligned_store(
+        &self,
+        ptr: PointerValue<'ctx>,
+        value: impl BasicValue<'ctx>,
+        err: &'static str,
+    ) -> Result<()> {
+        let ty = value.as_basic_value_enum().get_type();
+        self.builder
+            .build_store(ptr, value)
+            .map_err(|_| CodegenError::LlvmBuild(err))?
+            .set_alignment(Self::natural_alignment(ty))
+            .map_err(|_| CodegenError::LlvmBuild("set alignment"))
     }
 
     /// Convert `NType` to `BasicTypeEnum`
     pub(crate) fn convert_ntype_to_type(&self, ntype: &Ty) -> Result<BasicTypeEnum<'ctx>> {
+        if let Some(ty) = self.symbols.type_cache.borrow().get(ntype) {
+            return Ok(*ty);
+        }
+        let ty = self.convert_ntype_to_type_uncached(ntype)?;
+        self.symbols
+            .type_cache
+            .borrow_mut()
+            .insert(ntype.clone(), ty);
+        Ok(ty)
+    }
+
+    fn convert_ntype_to_type_uncached(&self, ntype: &Ty) -> Result<BasicTypeEnum<'ctx>> {
         match ntype {
             Ty::I32 => Ok(self.context.i32_type().into()),
             Ty::I8 => Ok(self.context.i8_type().into()),
@@ -185,6 +259,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     _ => {
                         return Err(CodegenError::Unsupported(
                             "unsupported array element type".into(),
+                            None,
                         ));
                     }
                 })
@@ -196,7 +271,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 analyzer::array::ArrayTreeValue::Struct {
                     init_list: list_range,
                     ..
-                } => self.get_const_var_value_by_range(*list_range, None),
+                } => self.get_const_var_value_by_range(*list_range, Some(ty)),
                 analyzer::array::ArrayTreeValue::Empty => Ok(ty.const_zero()),
             },
         }
@@ -207,7 +282,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         mut cur_ntype: Ty,
         mut cur_llvm_type: BasicTypeEnum<'ctx>,
         mut ptr: PointerValue<'ctx>,
-        indices: Vec<IntValue<'ctx>>,
+        indices: Vec<(IntValue<'ctx>, Ty)>,
     ) -> Result<(BasicTypeEnum<'ctx>, PointerValue<'ctx>)> {
         let mut idx_iter = indices.into_iter().peekable();
         let ptr_ty = self.context.ptr_type(AddressSpace::default());
@@ -221,8 +296,14 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     let mut depth = 0;
                     let mut inner = &cur_ntype;
 
-                    while let Ty::Array(next_inner, _) = inner {
-                        if let Some(idx) = idx_iter.next() {
+                    while let Ty::Array(next_inner, len) = inner {
+                        if let Some((idx, idx_ty)) = idx_iter.next() {
+                            if self.bounds_check
+                                && !idx.is_const()
+                                && let Some(len) = len
+                            {
+                                self.build_bounds_check(idx, &idx_ty, *len)?;
+                            }
                             indices.push(idx);
                             depth += 1;
                             inner = next_inner;
@@ -249,12 +330,10 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                     // 指针：load 后 GEP 一个索引
                     let pointee_ty = self.convert_ntype_to_type(pointee)?;
                     let loaded_ptr = self
-                        .builder
-                        .build_load(ptr_ty, ptr, "ptr.load")
-                        .map_err(|_| CodegenError::LlvmBuild("load ptr"))?
+                        .build_aligned_load(ptr_ty.into(), ptr, "ptr.load", "load ptr")?
                         .into_pointer_value();
 
-                    let idx = idx_iter.next().unwrap();
+                    let (idx, _) = idx_iter.next().unwrap();
                     ptr = unsafe {
                         self.builder
                             .build_gep(pointee_ty, loaded_ptr, &[idx], "ptr.gep")
@@ -270,6 +349,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
                 _ => {
                     return Err(CodegenError::TypeMismatch(
                         "cannot index non-array/pointer".into(),
+                        None,
                     ));
                 }
             }
@@ -308,7 +388,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let value = self
             .analyzer
             .get_value_by_range(ast_node.text_range())
-            .ok_or(CodegenError::Missing("constant value"))?;
+            .ok_or(CodegenError::Missing("constant value", None))?;
         self.convert_value(value, ty)
     }
 
@@ -322,7 +402,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         let value = self
             .analyzer
             .get_value_by_range(range)
-            .ok_or(CodegenError::Missing("constant value"))?;
+            .ok_or(CodegenError::Missing("constant value", None))?;
         self.convert_value(value, ty)
     }
 
@@ -360,6 +440,7 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             }
             _ => Err(CodegenError::Unsupported(
                 "unsupported type for bool conversion".into(),
+                None,
             )),
         }
     }
@@ -458,13 +539,6 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         }
     }
 
-    /// Zero-extend i1 to i32
-    pub(crate) fn bool_to_i32(&self, val: IntValue<'ctx>) -> Result<IntValue<'ctx>> {
-        self.builder
-            .build_int_z_extend(val, self.context.i32_type(), "bool_ext")
-            .map_err(|_| CodegenError::LlvmBuild("bool extend failed"))
-    }
-
     /// Build int compare and convert result to i32
     pub(crate) fn build_int_cmp(
         &self,
@@ -520,14 +594,19 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
             _ => return Ok(val),
         };
 
-        // 获取源和目标的位宽
-        let from_bits = match from {
-            Ty::Bool => 1,
-            Ty::I8 | Ty::U8 => 8,
-            Ty::I32 | Ty::U32 => 32,
-            Ty::I64 | Ty::U64 => 64,
-            _ => return Ok(val),
-        };
+        // 获取源和目标的位宽，源位宽以实际的 LLVM 值为准，避免 i1 以错误的位宽参与运算
+        if !matches!(
+            from,
+            Ty::Bool | Ty::I8 | Ty::U8 | Ty::I32 | Ty::U32 | Ty::I64 | Ty::U64
+        ) {
+            return Ok(val);
+        }
+        let from_bits = val.get_type().get_bit_width();
+
+        // 转换为 bool 需要判断非零，不能直接截断
+        if matches!(to, Ty::Bool) {
+            return self.as_bool(val.into());
+        }
 
         let to_bits = match to {
             Ty::Bool => 1,
@@ -561,8 +640,49 @@ impl<'a, 'ctx> Program<'a, 'ctx> {
         }
     }
 
+    /// 下标不小于数组长度时跳转到 trap
+    ///
+    /// 下标先按自身的符号扩展到 i64，负数按无符号比较同样视为越界
+    fn build_bounds_check(&self, idx: IntValue<'ctx>, idx_ty: &Ty, len: i32) -> Result<()> {
+        let func = self
+            .symbols
+            .current_function
+            .ok_or(CodegenError::Missing("current function", None))?;
+
+        let idx = self.cast_int_to_type(idx, idx_ty, &Ty::I64)?;
+        let out_of_range = self
+            .builder
+            .build_int_compare(
+                IntPredicate::UGE,
+                idx,
+                self.context.i64_type().const_int(len as u64, false),
+                "bounds.oob",
+            )
+            .map_err(|_| CodegenError::LlvmBuild("int compare failed"))?;
+        let trap_bb = self.context.append_basic_block(func, "bounds.trap");
+        let ok_bb = self.context.append_basic_block(func, "bounds.ok");
+        self.builder
+            .build_conditional_branch(out_of_range, trap_bb, ok_bb)
+            .map_err(|_| CodegenError::LlvmBuild("conditional branch failed"))?;
+
+        self.builder.position_at_end(trap_bb);
+        let trap = self.module.get_function("llvm.trap").unwrap_or_else(|| {
+            let fn_ty = self.context.void_type().fn_type(&[], false);
+            self.module.add_function("llvm.trap", fn_ty, None)
+        });
+        self.builder
+            .build_call(trap, &[], "")
+            .map_err(|_| CodegenError::LlvmBuild("call llvm.trap failed"))?;
+        self.builder
+            .build_unreachable()
+            .map_err(|_| CodegenError::LlvmBuild("unreachable failed"))?;
+
+        self.builder.position_at_end(ok_bb);
+        Ok(())
+    }
+
     /// 通用类型转换
-    /// 整数向上转换
+    /// 整数向上转换，比较结果等 i1 值赋给整数时零扩展到目标位宽
     pub(crate) fn cast_value(
         &self,
         val: BasicValueEnum<'ctx>,
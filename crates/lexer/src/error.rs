@@ -8,6 +8,7 @@ use tools::TextRange;
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum LexerErrorKind {
     InvalidInteger,
+    TrailingBackslash,
     #[default]
     Unknown,
 }
@@ -23,6 +24,13 @@ pub enum LexerError {
         range: TextRange,
     },
 
+    #[error("backslash at end of file inside string literal")]
+    #[diagnostic(code(lexer::trailing_backslash))]
+    TrailingBackslash {
+        #[label("here")]
+        range: TextRange,
+    },
+
     #[error("unknown lexer error")]
     #[diagnostic(code(lexer::unknown))]
     Unknown {
@@ -34,7 +42,9 @@ pub enum LexerError {
 impl LexerError {
     pub fn range(&self) -> &TextRange {
         match self {
-            LexerError::InvalidInteger { range, .. } | LexerError::Unknown { range } => range,
+            LexerError::InvalidInteger { range, .. }
+            | LexerError::TrailingBackslash { range }
+            | LexerError::Unknown { range } => range,
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use logos::Logos;
 use syntax::SyntaxKind;
 use tools::TextRange;
@@ -47,22 +49,40 @@ pub enum Token {
     ELSE_KW,
     #[token("while")]
     WHILE_KW,
+    #[token("for")]
+    FOR_KW,
     #[token("break")]
     BREAK_KW,
     #[token("continue")]
     CONTINUE_KW,
     #[token("return")]
     RETURN_KW,
+    #[token("switch")]
+    SWITCH_KW,
+    #[token("case")]
+    CASE_KW,
+    #[token("default")]
+    DEFAULT_KW,
     #[token("struct")]
     STRUCT_KW,
+    #[token("enum")]
+    ENUM_KW,
+    #[token("type")]
+    TYPE_KW,
     #[token("let")]
     LET_KW,
     #[token("fn")]
     FN_KW,
     #[token("mut")]
     MUT_KW,
+    #[token("restrict")]
+    RESTRICT_KW,
+    #[token("goto")]
+    GOTO_KW,
     #[token("attach")]
     ATTACH_KW,
+    #[token("extern")]
+    EXTERN_KW,
     #[token("null")]
     NULL_KW,
     #[token("true")]
@@ -126,6 +146,12 @@ pub enum Token {
     #[token(">=")]
     GTEQ,
 
+    // 移位运算符
+    #[token("<<")]
+    LTLT,
+    #[token(">>")]
+    GTGT,
+
     // 逻辑运算符
     #[token("&&")]
     AMPAMP,
@@ -138,11 +164,21 @@ pub enum Token {
     #[token("&")]
     AMP,
 
+    /// 属性前缀
+    #[token("@")]
+    AT,
+
     // 字面量
+    /// 标识符，`r#` 前缀的原始标识符允许使用关键字作为名称
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*")]
+    #[regex(r"r#[a-zA-Z_][a-zA-Z0-9_]*")]
     IDENT,
-    #[regex(r#""([^"\\]|\\.)*""#)]
+    /// `\` 紧跟换行是续行符，拼接前后两行
+    #[regex(r#""([^"\\]|\\(.|\r?\n))*""#)]
     STRING_LITERAL,
+    /// 文件在字符串内的 `\` 处结束
+    #[regex(r#""([^"\\]|\\(.|\r?\n))*\\"#, trailing_backslash)]
+    UNTERMINATED_STRING,
     #[regex(r"'([^'\\]|\\.)'")]
     CHAR_LITERAL,
     #[regex(r"0[xX][0-9a-fA-F]+(i8|i32|u8|u32|i64|u64)?", priority = 3)]
@@ -172,11 +208,20 @@ impl From<Token> for SyntaxKind {
             Token::IF_KW => SyntaxKind::IF_KW,
             Token::ELSE_KW => SyntaxKind::ELSE_KW,
             Token::WHILE_KW => SyntaxKind::WHILE_KW,
+            Token::FOR_KW => SyntaxKind::FOR_KW,
             Token::BREAK_KW => SyntaxKind::BREAK_KW,
             Token::CONTINUE_KW => SyntaxKind::CONTINUE_KW,
             Token::RETURN_KW => SyntaxKind::RETURN_KW,
+            Token::SWITCH_KW => SyntaxKind::SWITCH_KW,
+            Token::CASE_KW => SyntaxKind::CASE_KW,
+            Token::DEFAULT_KW => SyntaxKind::DEFAULT_KW,
             Token::STRUCT_KW => SyntaxKind::STRUCT_KW,
+            Token::ENUM_KW => SyntaxKind::ENUM_KW,
+            Token::TYPE_KW => SyntaxKind::TYPE_KW,
+            Token::RESTRICT_KW => SyntaxKind::RESTRICT_KW,
+            Token::GOTO_KW => SyntaxKind::GOTO_KW,
             Token::ATTACH_KW => SyntaxKind::ATTACH_KW,
+            Token::EXTERN_KW => SyntaxKind::EXTERN_KW,
             Token::NULL_KW => SyntaxKind::NULL_KW,
             Token::TRUE_KW => SyntaxKind::TRUE_KW,
             Token::FALSE_KW => SyntaxKind::FALSE_KW,
@@ -198,6 +243,7 @@ impl From<Token> for SyntaxKind {
             Token::DOTDOTDOT => SyntaxKind::DOTDOTDOT,
             Token::DOT => SyntaxKind::DOT,
             Token::ARROW => SyntaxKind::ARROW,
+            Token::AT => SyntaxKind::AT,
             Token::PLUS => SyntaxKind::PLUS,
             Token::MINUS => SyntaxKind::MINUS,
             Token::SLASH => SyntaxKind::SLASH,
@@ -208,6 +254,8 @@ impl From<Token> for SyntaxKind {
             Token::GT => SyntaxKind::GT,
             Token::LTEQ => SyntaxKind::LTEQ,
             Token::GTEQ => SyntaxKind::GTEQ,
+            Token::LTLT => SyntaxKind::LTLT,
+            Token::GTGT => SyntaxKind::GTGT,
             Token::AMPAMP => SyntaxKind::AMPAMP,
             Token::PIPEPIPE => SyntaxKind::PIPEPIPE,
             Token::BANG => SyntaxKind::BANG,
@@ -216,8 +264,54 @@ impl From<Token> for SyntaxKind {
             Token::STRING_LITERAL => SyntaxKind::STRING_LITERAL,
             Token::CHAR_LITERAL => SyntaxKind::CHAR_LITERAL,
             Token::INT_LITERAL => SyntaxKind::INT_LITERAL,
+            // 回调总是返回错误，不会产生这个 token
+            Token::UNTERMINATED_STRING => SyntaxKind::ERROR,
+        }
+    }
+}
+
+/// 字符串停在 `\` 上：位于文件末尾时是悬空的续行符，否则只是未闭合的字符串
+fn trailing_backslash(lex: &mut logos::Lexer<Token>) -> Result<(), LexerErrorKind> {
+    if lex.remainder().is_empty() {
+        Err(LexerErrorKind::TrailingBackslash)
+    } else {
+        Err(LexerErrorKind::Unknown)
+    }
+}
+
+/// 去掉字符串字面量中的续行符（`\` 紧跟换行），其余转义原样保留
+pub fn splice_line_continuations(text: &str) -> Cow<'_, str> {
+    if !text.contains("\\\n") && !text.contains("\\\r\n") {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('\n') => {
+                chars.next();
+            }
+            Some('\r') => {
+                chars.next();
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                } else {
+                    result.push_str("\\\r");
+                }
+            }
+            Some(&next) => {
+                chars.next();
+                result.push(c);
+                result.push(next);
+            }
+            None => result.push(c),
         }
     }
+    Cow::Owned(result)
 }
 
 /// 词法分析器
@@ -246,6 +340,9 @@ impl<'a> Lexer<'a> {
                         LexerErrorKind::Unknown => LexerError::Unknown {
                             range: span.clone().into(),
                         },
+                        LexerErrorKind::TrailingBackslash => LexerError::TrailingBackslash {
+                            range: span.clone().into(),
+                        },
                     };
                     lexer_errors.push(err);
                     SyntaxKind::ERROR
@@ -322,6 +419,16 @@ impl<'a> Lexer<'a> {
             .unwrap_or(SyntaxKind::EOF)
     }
 
+    /// 返回当前非空白 token 之后第 n 个非空白 token 类型，n 为 0 时等同于 `current_without_trivia`
+    pub fn nth_without_trivia(&self, n: usize) -> SyntaxKind {
+        self.tokens[self.pos_skip_trivia..]
+            .iter()
+            .filter(|t| !t.0.is_trivia())
+            .nth(n)
+            .map(|t| t.0)
+            .unwrap_or(SyntaxKind::EOF)
+    }
+
     /// 移动到下一个 token
     pub fn bump(&mut self) {
         if self.pos < self.tokens.len() {
@@ -392,6 +499,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_raw_identifier() {
+        check(
+            "let r#if: i32 = r#while;",
+            &[
+                (LET_KW, "let"),
+                (WHITESPACE, " "),
+                (IDENT, "r#if"),
+                (COLON, ":"),
+                (WHITESPACE, " "),
+                (I32_KW, "i32"),
+                (WHITESPACE, " "),
+                (EQ, "="),
+                (WHITESPACE, " "),
+                (IDENT, "r#while"),
+                (SEMI, ";"),
+            ],
+        );
+    }
+
     #[test]
     fn test_comments_and_whitespace() {
         check(
@@ -464,4 +591,53 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_string_line_continuation() {
+        let source = "let s = \"ab\\\n   cd\\n\";";
+        check(
+            source,
+            &[
+                (LET_KW, "let"),
+                (WHITESPACE, " "),
+                (IDENT, "s"),
+                (WHITESPACE, " "),
+                (EQ, "="),
+                (WHITESPACE, " "),
+                (STRING_LITERAL, "\"ab\\\n   cd\\n\""),
+                (SEMI, ";"),
+            ],
+        );
+        // 续行符和换行一起去掉，普通转义保留给后续处理
+        assert_eq!(
+            splice_line_continuations("\"ab\\\n   cd\\n\""),
+            "\"ab   cd\\n\""
+        );
+        assert_eq!(splice_line_continuations("\"a\\\r\nb\""), "\"ab\"");
+        assert_eq!(splice_line_continuations(r#""a\\b""#), r#""a\\b""#);
+    }
+
+    #[test]
+    fn test_string_trailing_backslash() {
+        let lexer = Lexer::new("let s = \"abc\\");
+        assert_eq!(
+            lexer.lexer_errors,
+            vec![LexerError::TrailingBackslash {
+                range: TextRange::new(8, 13)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nth_without_trivia() {
+        let mut lexer = Lexer::new("  alloc < /* c */ i32 >");
+        assert_eq!(lexer.nth_without_trivia(0), IDENT);
+        assert_eq!(lexer.nth_without_trivia(1), LT);
+        assert_eq!(lexer.nth_without_trivia(2), I32_KW);
+        assert_eq!(lexer.nth_without_trivia(4), EOF);
+        lexer.bump();
+        lexer.bump();
+        assert_eq!(lexer.nth_without_trivia(0), LT);
+        assert_eq!(lexer.nth_without_trivia(1), I32_KW);
+    }
 }
@@ -4,7 +4,7 @@ use lexer::LexerError;
 use miette::Diagnostic;
 use syntax::SyntaxKind;
 use thiserror::Error;
-use tools::TextRange;
+use tools::{LineIndex, TextRange};
 
 /// 格式化 SyntaxKind 列表为字符串
 fn format_kinds(kinds: &[SyntaxKind]) -> String {
@@ -38,4 +38,10 @@ impl ParserError {
             Self::LexerError(e) => e.range(),
         }
     }
+
+    /// 错误起始位置的行号和列号，均从 1 开始
+    pub fn location(&self, line_index: &LineIndex) -> (usize, usize) {
+        let (row, col) = line_index.get_row_column(self.range().start().into());
+        (row as usize + 1, col as usize + 1)
+    }
 }
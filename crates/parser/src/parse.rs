@@ -1,16 +1,19 @@
 mod block;
 mod common;
+mod r#enum;
 mod expression;
 mod function;
 mod header;
 mod recovery;
 mod statement;
 mod r#struct;
+mod type_alias;
 mod variable;
 
 use lexer::Lexer;
 use rowan::{Checkpoint, GreenNode, GreenNodeBuilder};
-use syntax::SyntaxKind;
+use syntax::ast::{AstNode, Expr};
+use syntax::{SyntaxKind, SyntaxNode};
 use tools::TextRange;
 
 pub use crate::error::ParserError;
@@ -41,6 +44,37 @@ impl<'a> Parser<'a> {
         (self.builder.finish(), all_errors)
     }
 
+    /// 解析单独的表达式片段（REPL、求值选区等场景）
+    ///
+    /// 表达式之后多余的 token 会放进 ERROR 节点并报告错误；
+    /// 表达式不完整时仍返回已解析出的部分语法树
+    pub fn parse_expr(source: &str) -> (Option<Expr>, Vec<ParserError>) {
+        let mut parser = Parser::new(source);
+        // 用 EXPR_STMT 作为根节点承载表达式和多余的 token
+        parser.start_node(SyntaxKind::EXPR_STMT);
+        let success = parser.parse_exp();
+        if !parser.at(SyntaxKind::EOF) {
+            if success {
+                parser.skip_until(&[SyntaxKind::EOF]);
+            } else {
+                // 表达式本身已经报告过错误，剩余 token 只保留在树中
+                parser.start_node(SyntaxKind::ERROR);
+                while !parser.at(SyntaxKind::EOF) {
+                    parser.bump();
+                }
+                parser.finish_node();
+            }
+        }
+        parser.bump_trivia();
+        parser.finish_node();
+
+        let mut all_errors = parser.parse_errors;
+        all_errors.extend(parser.lexer.lexer_errors.into_iter().map(ParserError::from));
+
+        let root = SyntaxNode::new_root(parser.builder.finish());
+        (root.children().find_map(Expr::cast), all_errors)
+    }
+
     pub(crate) fn checkpoint(&self) -> Checkpoint {
         self.builder.checkpoint()
     }
@@ -96,6 +130,11 @@ impl<'a> Parser<'a> {
         self.lexer.current_without_trivia()
     }
 
+    /// 获取当前 token 之后第 n 个 token 类型（跳过空白）
+    pub(crate) fn nth(&self, n: usize) -> SyntaxKind {
+        self.lexer.nth_without_trivia(n)
+    }
+
     pub fn parse_root(&mut self) {
         self.start_node(SyntaxKind::COMP_UNIT);
 
@@ -108,12 +147,21 @@ impl<'a> Parser<'a> {
                 SyntaxKind::LET_KW => {
                     self.parse_var_def();
                 }
-                SyntaxKind::FN_KW => {
+                SyntaxKind::FN_KW
+                | SyntaxKind::AT
+                | SyntaxKind::CONST_KW
+                | SyntaxKind::EXTERN_KW => {
                     self.parse_func_def();
                 }
                 SyntaxKind::STRUCT_KW => {
                     self.parse_struct_def();
                 }
+                SyntaxKind::ENUM_KW => {
+                    self.parse_enum_def();
+                }
+                SyntaxKind::TYPE_KW => {
+                    self.parse_type_alias();
+                }
                 SyntaxKind::ATTACH_KW => {
                     self.parse_func_attach();
                 }
@@ -123,8 +171,12 @@ impl<'a> Parser<'a> {
                         SyntaxKind::IMPORT_KW,
                         SyntaxKind::LET_KW,
                         SyntaxKind::FN_KW,
+                        SyntaxKind::EXTERN_KW,
                         SyntaxKind::STRUCT_KW,
+                        SyntaxKind::ENUM_KW,
+                        SyntaxKind::TYPE_KW,
                         SyntaxKind::ATTACH_KW,
+                        SyntaxKind::AT,
                         SyntaxKind::EOF,
                     ]);
                 }
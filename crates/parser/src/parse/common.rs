@@ -51,7 +51,7 @@ impl Parser<'_> {
         success
     }
 
-    /// 解析原始类型: 'void' | 'i32' | 'f32' | 'struct' Name
+    /// 解析原始类型: 'void' | 整数类型 | 'bool' | 'struct' Name | 类型别名 Name
     pub(super) fn parse_primitive_type(&mut self) -> bool {
         self.bump_trivia();
         self.start_node(SyntaxKind::PRIMIT_TYPE);
@@ -73,6 +73,8 @@ impl Parser<'_> {
         } else if current_token == SyntaxKind::STRUCT_KW {
             self.bump();
             self.parse_name() // 传播返回值
+        } else if current_token == SyntaxKind::IDENT {
+            self.parse_name()
         } else {
             let range = self.current_range();
             self.bump(); // eat it
@@ -87,10 +89,13 @@ impl Parser<'_> {
         success
     }
 
-    /// 解析指针: '*' ('mut' | 'const')
+    /// 解析指针: '*' 'restrict'? ('mut' | 'const')
     pub(super) fn parse_pointer(&mut self) -> bool {
         self.start_node(SyntaxKind::POINTER);
         self.bump(); // consume '*'
+        if self.at(SyntaxKind::RESTRICT_KW) {
+            self.bump();
+        }
 
         if self.at(SyntaxKind::MUT_KW) || self.at(SyntaxKind::CONST_KW) {
             self.bump();
@@ -0,0 +1,69 @@
+use crate::parse::Parser;
+use syntax::syntax_kind::SyntaxKind;
+
+impl Parser<'_> {
+    /// 解析枚举定义：`enum Name (: Type)? { Variant (= Exp)?, ... }`
+    pub(super) fn parse_enum_def(&mut self) -> bool {
+        self.start_node(SyntaxKind::ENUM_DEF);
+
+        if !self.expect(SyntaxKind::ENUM_KW) {
+            self.finish_node();
+            return false;
+        }
+        if !self.parse_name() {
+            self.finish_node();
+            return false;
+        }
+        // 可选的底层整数类型
+        if self.at(SyntaxKind::COLON) {
+            self.bump();
+            if !self.parse_type() {
+                self.finish_node();
+                return false;
+            }
+        }
+        if !self.expect(SyntaxKind::L_BRACE) {
+            self.finish_node();
+            return false;
+        }
+
+        // 解析第一个成员
+        if !self.at(SyntaxKind::R_BRACE) && !self.parse_enum_variant() {
+            self.finish_node();
+            return false;
+        }
+
+        while self.at(SyntaxKind::COMMA) {
+            self.bump();
+            if self.at(SyntaxKind::R_BRACE) {
+                break;
+            }
+            if !self.parse_enum_variant() {
+                self.finish_node();
+                return false;
+            }
+        }
+
+        let success = self.expect(SyntaxKind::R_BRACE);
+        self.finish_node();
+        success
+    }
+
+    /// 解析枚举成员
+    fn parse_enum_variant(&mut self) -> bool {
+        self.start_node(SyntaxKind::ENUM_VARIANT);
+        if !self.parse_name() {
+            self.finish_node();
+            return false;
+        }
+        if self.at(SyntaxKind::EQ) {
+            self.bump();
+            if !self.parse_exp() {
+                self.finish_node();
+                return false;
+            }
+        }
+        self.finish_node();
+        true
+    }
+}
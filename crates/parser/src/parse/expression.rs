@@ -60,13 +60,30 @@ impl Parser<'_> {
 
     fn parse_rel_exp(&mut self) -> bool {
         let cp = self.checkpoint();
-        if !self.parse_add_exp() {
+        if !self.parse_shift_exp() {
             return false;
         }
         while matches!(
             self.peek(),
             SyntaxKind::LT | SyntaxKind::GT | SyntaxKind::LTEQ | SyntaxKind::GTEQ
         ) {
+            self.start_node_at(cp, SyntaxKind::BINARY_EXPR);
+            self.parse_binary_op();
+            if !self.parse_shift_exp() {
+                self.finish_node();
+                return false;
+            }
+            self.finish_node();
+        }
+        true
+    }
+
+    fn parse_shift_exp(&mut self) -> bool {
+        let cp = self.checkpoint();
+        if !self.parse_add_exp() {
+            return false;
+        }
+        while matches!(self.peek(), SyntaxKind::LTLT | SyntaxKind::GTGT) {
             self.start_node_at(cp, SyntaxKind::BINARY_EXPR);
             self.parse_binary_op();
             if !self.parse_add_exp() {
@@ -188,16 +205,7 @@ impl Parser<'_> {
             let success = self.expect(SyntaxKind::R_PAREN);
             self.finish_node();
             success
-        } else if self.peek().is_number()
-            || matches!(
-                self.peek(),
-                SyntaxKind::NULL_KW
-                    | SyntaxKind::TRUE_KW
-                    | SyntaxKind::FALSE_KW
-                    | SyntaxKind::STRING_LITERAL
-                    | SyntaxKind::CHAR_LITERAL
-            )
-        {
+        } else if self.peek().is_literal() {
             self.start_node(SyntaxKind::LITERAL);
             self.bump();
             self.finish_node();
@@ -207,19 +215,38 @@ impl Parser<'_> {
         }
     }
 
-    /// 解析左值或函数调用表达式
+    /// 解析左值、函数调用或枚举成员表达式
     pub(super) fn parse_lval_or_call_expr(&mut self) -> bool {
         let cp = self.checkpoint();
         if !self.parse_name() {
             return false;
         }
-        if self.at(SyntaxKind::L_PAREN) {
+        if self.at(SyntaxKind::COLONCOLON) {
+            self.start_node_at(cp, SyntaxKind::ENUM_VARIANT_EXPR);
+            self.bump(); // `::`
+            let success = self.parse_name();
+            self.finish_node();
+            success
+        } else if self.at(SyntaxKind::L_PAREN) || self.at_type_arg() {
             self.start_node_at(cp, SyntaxKind::CALL_EXPR);
+            if self.at(SyntaxKind::LT) {
+                self.bump(); // `<`
+                if !self.parse_type() || !self.expect(SyntaxKind::GT) {
+                    self.finish_node();
+                    return false;
+                }
+            }
             if !self.expect(SyntaxKind::L_PAREN) {
                 self.finish_node();
                 return false;
             }
-            if !self.at(SyntaxKind::R_PAREN) && !self.parse_func_r_params() {
+            if self.type_starts_at(0) {
+                // 括号中只能是类型时（如 `sizeof(i64)`）同样作为类型参数
+                if !self.parse_type() {
+                    self.finish_node();
+                    return false;
+                }
+            } else if !self.at(SyntaxKind::R_PAREN) && !self.parse_func_r_params() {
                 self.finish_node();
                 return false;
             }
@@ -244,6 +271,35 @@ impl Parser<'_> {
         }
     }
 
+    /// 名字后的 `<` 紧跟只能开始类型的 token 时是类型参数（如 `alloc<i32>(n)`），否则是小于号
+    fn at_type_arg(&self) -> bool {
+        self.at(SyntaxKind::LT) && self.type_starts_at(1)
+    }
+
+    /// 向后第 n 个 token 只能开始类型，不能开始表达式
+    fn type_starts_at(&self, n: usize) -> bool {
+        match self.nth(n) {
+            SyntaxKind::STAR => matches!(
+                self.nth(n + 1),
+                SyntaxKind::MUT_KW | SyntaxKind::CONST_KW | SyntaxKind::RESTRICT_KW
+            ),
+            kind => matches!(
+                kind,
+                SyntaxKind::I32_KW
+                    | SyntaxKind::I8_KW
+                    | SyntaxKind::U8_KW
+                    | SyntaxKind::U32_KW
+                    | SyntaxKind::I64_KW
+                    | SyntaxKind::U64_KW
+                    | SyntaxKind::BOOL_KW
+                    | SyntaxKind::VOID_KW
+                    | SyntaxKind::STRUCT_KW
+                    | SyntaxKind::CONST_KW
+                    | SyntaxKind::L_BRACK
+            ),
+        }
+    }
+
     /// 仅在确认是二元运算符后调用
     fn parse_binary_op(&mut self) {
         self.bump_trivia();
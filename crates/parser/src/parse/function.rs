@@ -6,6 +6,25 @@ impl Parser<'_> {
     pub(super) fn parse_func_def(&mut self) -> bool {
         self.start_node(SyntaxKind::FUNC_DEF);
 
+        while self.at(SyntaxKind::AT) {
+            self.parse_attribute();
+        }
+        // `extern` 后可以跟 ABI 字符串：`extern "C" fn`
+        if self.at(SyntaxKind::EXTERN_KW) {
+            self.bump();
+            if self.at(SyntaxKind::STRING_LITERAL) {
+                self.bump();
+            }
+        }
+        if self.at(SyntaxKind::CONST_KW) {
+            self.bump();
+        }
+        if !self.at(SyntaxKind::FN_KW) {
+            let success = self.expect(SyntaxKind::FN_KW);
+            self.finish_node();
+            return success;
+        }
+
         self.parse_func_sign();
 
         let success = if self.at(SyntaxKind::SEMI) {
@@ -18,6 +37,16 @@ impl Parser<'_> {
         success
     }
 
+    /// 解析属性：'@' Name
+    fn parse_attribute(&mut self) -> bool {
+        self.bump_trivia();
+        self.start_node(SyntaxKind::ATTRIBUTE);
+        self.bump(); // AT
+        let success = self.parse_name();
+        self.finish_node();
+        success
+    }
+
     fn parse_func_sign(&mut self) -> bool {
         self.start_node(SyntaxKind::FUNC_SIGN);
         self.bump(); // FN_KW
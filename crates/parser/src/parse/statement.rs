@@ -7,38 +7,42 @@ impl Parser<'_> {
         match self.peek() {
             SyntaxKind::IF_KW => self.parse_if_statement(),
             SyntaxKind::WHILE_KW => self.parse_while_statement(),
+            SyntaxKind::FOR_KW => self.parse_for_statement(),
             SyntaxKind::BREAK_KW => self.parse_break_statement(),
             SyntaxKind::CONTINUE_KW => self.parse_continue_statement(),
+            SyntaxKind::GOTO_KW => self.parse_goto_statement(),
+            SyntaxKind::IDENT if self.nth(1) == SyntaxKind::COLON => self.parse_label_statement(),
             SyntaxKind::RETURN_KW => self.parse_return_statement(),
+            SyntaxKind::SWITCH_KW => self.parse_switch_statement(),
             SyntaxKind::L_BRACE => self.parse_block(),
             SyntaxKind::SEMI => {
                 self.bump(); // consume ';'
                 true
             }
-            _ => {
-                // 让语义分析检查赋值语句左值是否为 Lval
-                let cp = self.checkpoint();
-                if !self.parse_exp() {
-                    return false;
-                }
-                if self.at(SyntaxKind::EQ) {
-                    self.start_node_at(cp, SyntaxKind::ASSIGN_STMT);
-                    self.bump(); // =
-                    if !self.parse_exp() {
-                        self.finish_node();
-                        return false;
-                    }
-                    let success = self.expect(SyntaxKind::SEMI);
-                    self.finish_node();
-                    success
-                } else {
-                    self.start_node_at(cp, SyntaxKind::EXPR_STMT);
-                    let success = self.expect(SyntaxKind::SEMI);
-                    self.finish_node();
-                    success
-                }
+            _ => self.parse_simple_statement(true),
+        }
+    }
+
+    /// 解析赋值语句或表达式语句，for 的步进部分没有结尾的 `;`
+    fn parse_simple_statement(&mut self, expect_semi: bool) -> bool {
+        // 让语义分析检查赋值语句左值是否为 Lval
+        let cp = self.checkpoint();
+        if !self.parse_exp() {
+            return false;
+        }
+        if self.peek().is_assignment_op() {
+            self.start_node_at(cp, SyntaxKind::ASSIGN_STMT);
+            self.bump(); // =
+            if !self.parse_exp() {
+                self.finish_node();
+                return false;
             }
+        } else {
+            self.start_node_at(cp, SyntaxKind::EXPR_STMT);
         }
+        let success = !expect_semi || self.expect(SyntaxKind::SEMI);
+        self.finish_node();
+        success
     }
 
     fn parse_if_statement(&mut self) -> bool {
@@ -99,6 +103,63 @@ impl Parser<'_> {
         success
     }
 
+    /// 解析 `for (init; cond; step) body`，三个部分都可以省略
+    fn parse_for_statement(&mut self) -> bool {
+        self.start_node(SyntaxKind::FOR_STMT);
+
+        if !self.expect(SyntaxKind::FOR_KW) {
+            self.finish_node();
+            return false;
+        }
+        if !self.expect(SyntaxKind::L_PAREN) {
+            self.finish_node();
+            return false;
+        }
+
+        if self.at(SyntaxKind::SEMI) {
+            self.bump();
+        } else {
+            self.start_node(SyntaxKind::FOR_INIT);
+            let success = if self.at(SyntaxKind::LET_KW) {
+                self.parse_var_def()
+            } else {
+                self.parse_simple_statement(true)
+            };
+            self.finish_node();
+            if !success {
+                self.finish_node();
+                return false;
+            }
+        }
+
+        if !self.at(SyntaxKind::SEMI) && !self.parse_exp() {
+            self.finish_node();
+            return false;
+        }
+        if !self.expect(SyntaxKind::SEMI) {
+            self.finish_node();
+            return false;
+        }
+
+        if !self.at(SyntaxKind::R_PAREN) {
+            self.start_node(SyntaxKind::FOR_STEP);
+            let success = self.parse_simple_statement(false);
+            self.finish_node();
+            if !success {
+                self.finish_node();
+                return false;
+            }
+        }
+        if !self.expect(SyntaxKind::R_PAREN) {
+            self.finish_node();
+            return false;
+        }
+
+        let success = self.parse_statement();
+        self.finish_node();
+        success
+    }
+
     fn parse_break_statement(&mut self) -> bool {
         self.start_node(SyntaxKind::BREAK_STMT);
 
@@ -123,6 +184,23 @@ impl Parser<'_> {
         success
     }
 
+    /// `goto label;`：语言不支持，完整解析后由分析阶段报错，避免级联的语法错误
+    fn parse_goto_statement(&mut self) -> bool {
+        self.start_node(SyntaxKind::GOTO_STMT);
+        self.bump(); // consume 'goto'
+        let success = self.parse_name() && self.expect(SyntaxKind::SEMI);
+        self.finish_node();
+        success
+    }
+
+    /// `label:`：同 goto，只做解析
+    fn parse_label_statement(&mut self) -> bool {
+        self.start_node(SyntaxKind::LABEL_STMT);
+        let success = self.parse_name() && self.expect(SyntaxKind::COLON);
+        self.finish_node();
+        success
+    }
+
     fn parse_return_statement(&mut self) -> bool {
         self.start_node(SyntaxKind::RETURN_STMT);
 
@@ -138,4 +216,68 @@ impl Parser<'_> {
         self.finish_node();
         success
     }
+
+    fn parse_switch_statement(&mut self) -> bool {
+        self.start_node(SyntaxKind::SWITCH_STMT);
+
+        if !self.expect(SyntaxKind::SWITCH_KW) {
+            self.finish_node();
+            return false;
+        }
+        if !self.expect(SyntaxKind::L_PAREN) {
+            self.finish_node();
+            return false;
+        }
+        if !self.parse_exp() {
+            self.finish_node();
+            return false;
+        }
+        if !self.expect(SyntaxKind::R_PAREN) {
+            self.finish_node();
+            return false;
+        }
+        if !self.expect(SyntaxKind::L_BRACE) {
+            self.finish_node();
+            return false;
+        }
+        while matches!(self.peek(), SyntaxKind::CASE_KW | SyntaxKind::DEFAULT_KW) {
+            if !self.parse_switch_case() {
+                self.finish_node();
+                return false;
+            }
+        }
+        let success = self.expect(SyntaxKind::R_BRACE);
+        self.finish_node();
+        success
+    }
+
+    /// 解析 `case Expr:` 或 `default:` 及其后的语句，直到下一个分支或 `}`
+    fn parse_switch_case(&mut self) -> bool {
+        self.start_node(SyntaxKind::SWITCH_CASE);
+
+        if self.at(SyntaxKind::DEFAULT_KW) {
+            self.bump();
+        } else {
+            self.bump(); // case
+            if !self.parse_exp() {
+                self.finish_node();
+                return false;
+            }
+        }
+        if !self.expect(SyntaxKind::COLON) {
+            self.finish_node();
+            return false;
+        }
+        while !matches!(
+            self.peek(),
+            SyntaxKind::CASE_KW | SyntaxKind::DEFAULT_KW | SyntaxKind::R_BRACE | SyntaxKind::EOF
+        ) {
+            if !self.parse_statement() {
+                self.finish_node();
+                return false;
+            }
+        }
+        self.finish_node();
+        true
+    }
 }
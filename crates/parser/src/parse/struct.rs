@@ -2,7 +2,7 @@ use crate::parse::Parser;
 use syntax::syntax_kind::SyntaxKind;
 
 impl Parser<'_> {
-    /// 解析结构体定义
+    /// 解析结构体定义或前向声明
     pub(super) fn parse_struct_def(&mut self) -> bool {
         self.start_node(SyntaxKind::STRUCT_DEF);
 
@@ -14,6 +14,12 @@ impl Parser<'_> {
             self.finish_node();
             return false;
         }
+        // 前向声明：`struct A;`
+        if self.at(SyntaxKind::SEMI) {
+            self.bump();
+            self.finish_node();
+            return true;
+        }
         if !self.expect(SyntaxKind::L_BRACE) {
             self.finish_node();
             return false;
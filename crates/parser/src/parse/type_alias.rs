@@ -0,0 +1,18 @@
+use crate::parse::Parser;
+use syntax::syntax_kind::SyntaxKind;
+
+impl Parser<'_> {
+    /// 解析类型别名：`type Name = Type;`
+    pub(super) fn parse_type_alias(&mut self) -> bool {
+        self.start_node(SyntaxKind::TYPE_ALIAS);
+
+        let success = self.expect(SyntaxKind::TYPE_KW)
+            && self.parse_name()
+            && self.expect(SyntaxKind::EQ)
+            && self.parse_type()
+            && self.expect(SyntaxKind::SEMI);
+
+        self.finish_node();
+        success
+    }
+}
@@ -3,38 +3,87 @@ use syntax::SyntaxKind;
 
 impl Parser<'_> {
     /// 解析变量定义
+    ///
+    /// `let a: i32 = 1, b: i32 = 2;` 中每个变量各自生成一个 VAR_DEF 节点，
+    /// 逗号留在前一个节点中，`let` 在第一个节点中，`;` 在最后一个节点中
     pub(super) fn parse_var_def(&mut self) -> bool {
         self.start_node(SyntaxKind::VAR_DEF);
         self.bump(); // LET_KW
 
-        if !self.parse_name() {
+        loop {
+            if !self.parse_var_def_item() {
+                self.finish_node();
+                return false;
+            }
+            if !self.at(SyntaxKind::COMMA) {
+                break;
+            }
+            self.bump(); // COMMA
             self.finish_node();
+            self.start_node(SyntaxKind::VAR_DEF);
+        }
+        let success = self.expect(SyntaxKind::SEMI);
+        self.finish_node();
+        success
+    }
+
+    /// 解析单个变量：`Name ':' Type ['=' InitVal]`
+    fn parse_var_def_item(&mut self) -> bool {
+        if !self.parse_name() {
             return false;
         }
         if !self.expect(SyntaxKind::COLON) {
-            self.finish_node();
             return false;
         }
         if !self.parse_type() {
-            self.finish_node();
             return false;
         }
         if self.at(SyntaxKind::EQ) {
             self.bump();
             if !self.parse_init_val() {
-                self.finish_node();
                 return false;
             }
         }
-        let success = self.expect(SyntaxKind::SEMI);
-        self.finish_node();
-        success
+        true
     }
 
     /// 解析初始化值
     pub(super) fn parse_init_val(&mut self) -> bool {
         self.start_node(SyntaxKind::INIT_VAL);
+        let success = self.parse_init_val_body();
+        self.finish_node();
+        success
+    }
+
+    /// 解析初始化列表中的元素：`['[' Expr ']' '=']` InitVal
+    fn parse_init_list_item(&mut self) -> bool {
+        self.start_node(SyntaxKind::INIT_VAL);
+        let success = (!self.at(SyntaxKind::L_BRACK) || self.parse_designator())
+            && self.parse_init_val_body();
+        self.finish_node();
+        success
+    }
+
+    /// 解析下标指示符 `[Expr] =`，`=` 留在元素节点中
+    fn parse_designator(&mut self) -> bool {
+        self.start_node(SyntaxKind::DESIGNATOR);
+        self.bump(); // [
+        let success = self.parse_exp() && self.expect(SyntaxKind::R_BRACK);
+        self.finish_node();
+        success && self.expect(SyntaxKind::EQ)
+    }
+
+    /// 解析重复次数 `; Expr`
+    fn parse_repeat_count(&mut self) -> bool {
+        self.start_node(SyntaxKind::REPEAT_COUNT);
+        self.bump(); // ;
+        let success = self.parse_exp();
+        self.finish_node();
+        success
+    }
 
+    /// 初始化值本身，由调用方包上 INIT_VAL 节点
+    fn parse_init_val_body(&mut self) -> bool {
         if self.at(SyntaxKind::L_BRACE) {
             self.bump(); // {
 
@@ -50,20 +99,22 @@ impl Parser<'_> {
                     continue;
                 }
 
-                if !self.parse_init_val() {
-                    self.finish_node();
+                if !self.parse_init_list_item() {
                     return false;
                 }
+                // 重复初始化 `{ value; count }` 只有一个元素
+                if is_first && self.at(SyntaxKind::SEMI) {
+                    if !self.parse_repeat_count() {
+                        return false;
+                    }
+                    break;
+                }
                 is_first = false;
             }
-            let success = self.expect(SyntaxKind::R_BRACE);
-            self.finish_node();
-            success
+            self.expect(SyntaxKind::R_BRACE)
         } else {
             self.bump_trivia();
-            let success = self.parse_exp();
-            self.finish_node();
-            success
+            self.parse_exp()
         }
     }
 }
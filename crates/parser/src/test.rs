@@ -1,7 +1,8 @@
 use rowan::SyntaxNode;
 
-use crate::parse::Parser;
+use crate::parse::{Parser, ParserError};
 use syntax::AirycLanguage;
+use syntax::AstNode;
 
 fn try_it(source: &str) -> SyntaxNode<AirycLanguage> {
     let parser = Parser::new(source);
@@ -199,6 +200,29 @@ fn test_postfix_expressions() {
     insta::assert_debug_snapshot!(try_it(source));
 }
 
+#[test]
+fn test_type_arg_call() {
+    let source = r#"
+    let p: *mut i32 = alloc<i32>(n);
+    let q: *mut *mut struct P = alloc<*mut struct P>(2);
+    let lt: bool = a < b;
+    let deref: bool = a < *p;
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_paren_type_arg_call() {
+    // 括号中只能是类型时作为类型参数，否则仍是普通参数
+    let source = r#"
+    let a: u64 = sizeof(i64);
+    let b: u64 = sizeof([i32; 3]);
+    let c: u64 = sizeof(*mut struct P);
+    let d: i32 = f(*p);
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
 #[test]
 fn test_struct_ast_nodes() {
     use syntax::ast::*;
@@ -311,3 +335,231 @@ import "../utils"::add
         panic!("Failed to parse CompUnit");
     }
 }
+
+#[test]
+fn test_attributes() {
+    let source = r#"
+    @inline fn add(a: i32, b: i32) -> i32 {}
+    @noinline @inline
+    fn main() {}
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_enum_def() {
+    let source = r#"
+    enum Color { Red, Green = 5, Blue, }
+    enum Small: u8 { A = 1 }
+    fn main() -> i32 { return Color::Blue + Small::A; }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_switch_statement() {
+    let source = r#"
+    fn main() -> i32 {
+        switch (x) {
+            case 1:
+                y = 1;
+            case 2:
+                y = 2;
+                break;
+            default:
+                return 0;
+        }
+    }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_parse_expr_fragment() {
+    let (expr, errors) = Parser::parse_expr("1 + 2 * 3");
+    assert!(errors.is_empty(), "{errors:?}");
+    let expr = expr.unwrap();
+    assert!(matches!(expr, syntax::ast::Expr::BinaryExpr(_)));
+    insta::assert_debug_snapshot!(expr.syntax());
+
+    let (expr, errors) = Parser::parse_expr("1 +");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    let Some(syntax::ast::Expr::BinaryExpr(bin)) = expr else {
+        panic!("expected partial BinaryExpr, got {expr:?}");
+    };
+    assert!(bin.lhs().is_some());
+    assert!(bin.rhs().is_none());
+
+    let (expr, errors) = Parser::parse_expr("1 2");
+    assert!(matches!(expr, Some(syntax::ast::Expr::Literal(_))));
+    assert!(matches!(
+        errors.as_slice(),
+        [ParserError::Expected { expected, .. }] if expected == &[syntax::SyntaxKind::EOF]
+    ));
+}
+
+#[test]
+fn test_ast_hash_ignores_trivia() {
+    let a = try_it("fn main() -> i32 {\n    let x: i32 = 1;\n    return x;\n}\n");
+    let b = try_it("fn main()->i32{\n\t// 注释\n  let x:i32=1; return x; }");
+    let c = try_it("fn main() -> i32 {\n    let x: i32 = 2;\n    return x;\n}\n");
+    assert_eq!(syntax::ast_hash(&a), syntax::ast_hash(&b));
+    assert_ne!(syntax::ast_hash(&a), syntax::ast_hash(&c));
+    // 哈希不依赖运行时随机种子，固定输入得到固定结果
+    assert_eq!(
+        syntax::ast_hash(&try_it("let x: i32 = 1;")),
+        7173556865710470247
+    );
+}
+
+#[test]
+fn test_for_statement() {
+    let source = r#"
+    fn main() -> i32 {
+        for (let i: i32 = 3; i > 0; i = i - 1) {
+            x = x + i;
+        }
+        for (;;) {
+            break;
+        }
+    }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_const_fn() {
+    let source = r#"
+    @inline
+    const fn square(x: i32) -> i32 {
+        return x * x;
+    }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_multi_var_def() {
+    let source = r#"
+    let a: i32 = 1, b: *mut i32;
+    fn main() {
+        let x: i32 = 1, y: [i32; 2] = {2, 3};
+        for (let i: i32 = 0, j: i32 = 9; i < j; i = i + 1) {}
+    }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_extern_fn() {
+    let source = r#"
+    extern "C" fn printf(format: *const u8, ...) -> i32;
+    extern fn puts(s: *const u8) -> i32;
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_parser_error_location() {
+    let source = "fn main() {\n    let x: i32 = 1;\n    let y: i32 = ;\n}\n";
+    let (_, errors) = Parser::new(source).parse();
+    let line_index = tools::LineIndex::from_text(source);
+    let locations: Vec<_> = errors.iter().map(|e| e.location(&line_index)).collect();
+    assert_eq!(locations.first(), Some(&(3, 18)), "{errors:?}");
+}
+
+#[test]
+fn test_dangling_else() {
+    use syntax::ast::*;
+
+    // else 与最近的 if 结合
+    let source = "fn f() { if (a) if (b) x = 1; else x = 2; }";
+    let syntax = try_it(source);
+    let outer = syntax
+        .descendants()
+        .find_map(IfStmt::cast)
+        .expect("应该找到外层 if");
+    assert!(outer.else_branch().is_none());
+
+    let Some(Stmt::IfStmt(inner)) = outer.then_branch() else {
+        panic!("外层 if 的分支应该是内层 if");
+    };
+    assert!(matches!(inner.then_branch(), Some(Stmt::AssignStmt(_))));
+    assert!(matches!(inner.else_branch(), Some(Stmt::AssignStmt(_))));
+}
+
+#[test]
+fn test_designated_init() {
+    let source = r#"
+    let a: [i32; 6] = { [0] = 1, [N - 1] = 9, 2 };
+    let b: [[i32; 2]; 2] = { [1] = {3, 4} };
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_type_alias() {
+    let source = r#"
+    type Int = i32;
+    type P = *mut struct S;
+    type Arr = [Int; 4];
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_repeat_init() {
+    let source = r#"
+    let a: [[i32; 2]; 3] = { { 0; 2 }; N };
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_struct_forward_decl() {
+    let source = r#"
+    struct B;
+    struct A { b: *mut struct B }
+    "#;
+    insta::assert_debug_snapshot!(try_it(source));
+}
+
+#[test]
+fn test_shift_precedence() {
+    use syntax::ast::*;
+
+    // 移位低于加减、高于比较：`a < b << 1 + c` 即 `a < (b << (1 + c))`
+    let source = "let x: bool = a < b << 1 + c;";
+    let syntax = try_it(source);
+    let ops: Vec<_> = syntax
+        .descendants()
+        .filter_map(BinaryExpr::cast)
+        .filter_map(|e| e.op())
+        .map(|op| op.op().kind())
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            syntax::SyntaxKind::LT,
+            syntax::SyntaxKind::LTLT,
+            syntax::SyntaxKind::PLUS
+        ]
+    );
+}
+
+#[test]
+fn test_empty_source() {
+    for source in [
+        "",
+        " \t\r\n\n",
+        "// only a comment",
+        "/* block */\n// line\n",
+    ] {
+        let root = try_it(source);
+        let comp_unit = syntax::ast::CompUnit::cast(root.clone()).unwrap();
+        assert_eq!(comp_unit.global_decls().count(), 0, "{source:?}");
+        assert_eq!(root.children().count(), 0, "{source:?}");
+        // 空白和注释原样保留在树中
+        assert_eq!(root.text().to_string(), source);
+    }
+}
@@ -126,6 +126,8 @@ ast_enum!(GlobalDecl {
     VarDef,
     FuncDef,
     StructDef,
+    EnumDef,
+    TypeAlias,
     FuncAttach
 });
 
@@ -141,8 +143,10 @@ ast_node!(
 
 ast_node!(
     InitVal ~ INIT_VAL {
+        designator: node(Designator),
         expr: node(Expr),
         inits: nodes(InitVal),
+        repeat: node(RepeatCount),
     }
 );
 impl InitVal {
@@ -151,14 +155,45 @@ impl InitVal {
     }
 }
 
-// Struct 定义
+// 数组初始化列表中的下标指示符：`{ [0] = 1, [5] = 9 }` 中的 `[0]`
+ast_node!(
+    Designator ~ DESIGNATOR {
+        index: node(Expr),
+    }
+);
+
+// 数组重复初始化 `{ 0; 8 }` 中的 `; 8`
+ast_node!(
+    RepeatCount ~ REPEAT_COUNT {
+        count: node(Expr),
+    }
+);
+
+// 类型别名：`type Int32 = i32;`
+ast_node!(
+    TypeAlias ~ TYPE_ALIAS {
+        name: node(Name),
+        ty: node(Type),
+    }
+);
+
+// Struct 定义，或者前向声明 `struct A;`
 ast_node!(
     StructDef ~ STRUCT_DEF {
         name: node(Name),
+        l_brace_token: token(L_BRACE),
         fields: nodes(StructField),
+        semi_token: token(SEMI),
     }
 );
 
+impl StructDef {
+    /// 检查是否为前向声明 `struct A;`
+    pub fn is_forward_decl(&self) -> bool {
+        self.l_brace_token().is_none() && self.semi_token().is_some()
+    }
+}
+
 ast_node!(
     StructField ~ STRUCT_FIELD {
         name: node(Name),
@@ -166,14 +201,53 @@ ast_node!(
     }
 );
 
+// Enum 定义：`enum Color: i32 { Red, Green = 5, Blue }`
+ast_node!(
+    EnumDef ~ ENUM_DEF {
+        name: node(Name),
+        ty: node(Type),
+        variants: nodes(EnumVariant),
+    }
+);
+
+ast_node!(
+    EnumVariant ~ ENUM_VARIANT {
+        name: node(Name),
+        value: node(Expr),
+    }
+);
+
 // 函数
 ast_node!(
     FuncDef ~ FUNC_DEF {
+        attributes: nodes(Attribute),
+        extern_token: token(EXTERN_KW),
+        abi: token(STRING_LITERAL),
+        const_token: token(CONST_KW),
         sign: node(FuncSign),
         block: node(Block),
     }
 );
 
+impl FuncDef {
+    /// 检查是否为 `const fn`
+    pub fn is_const(&self) -> bool {
+        self.const_token().is_some()
+    }
+
+    /// 检查是否为 `extern fn`
+    pub fn is_extern(&self) -> bool {
+        self.extern_token().is_some()
+    }
+}
+
+// 函数属性：`@inline`
+ast_node!(
+    Attribute ~ ATTRIBUTE {
+        name: node(Name),
+    }
+);
+
 ast_node!(
     FuncSign ~ FUNC_SIGN {
         name: node(Name),
@@ -225,9 +299,13 @@ ast_enum!(Stmt {
     Block,
     IfStmt,
     WhileStmt,
+    ForStmt,
     BreakStmt,
     ContinueStmt,
+    GotoStmt,
+    LabelStmt,
     ReturnStmt,
+    SwitchStmt,
 });
 
 ast_node!(
@@ -258,15 +336,63 @@ ast_node!(
     }
 );
 
+ast_node!(
+    ForStmt ~ FOR_STMT {
+        init: node(ForInit),
+        condition: node(Expr),
+        step: node(ForStep),
+        body: node(Stmt),
+    }
+);
+
+ast_node!(
+    ForInit ~ FOR_INIT {
+        var_defs: nodes(VarDef),
+        stmt: node(Stmt),
+    }
+);
+
+ast_node!(
+    ForStep ~ FOR_STEP {
+        stmt: node(Stmt),
+    }
+);
+
 ast_node!(BreakStmt ~ BREAK_STMT {});
 ast_node!(ContinueStmt ~ CONTINUE_STMT {});
 
+// `goto label;` 和 `label:` 只做解析，分析阶段报告不支持
+ast_node!(GotoStmt ~ GOTO_STMT { label: node(Name) });
+ast_node!(LabelStmt ~ LABEL_STMT { name: node(Name) });
+
 ast_node!(
     ReturnStmt ~ RETURN_STMT {
         expr: node(Expr),
     }
 );
 
+ast_node!(
+    SwitchStmt ~ SWITCH_STMT {
+        condition: node(Expr),
+        cases: nodes(SwitchCase),
+    }
+);
+
+ast_node!(
+    SwitchCase ~ SWITCH_CASE {
+        value: node(Expr),
+        body: nodes(Stmt),
+        default_token: token(DEFAULT_KW),
+    }
+);
+
+impl SwitchCase {
+    /// 检查是否为 default 分支
+    pub fn is_default(&self) -> bool {
+        self.default_token().is_some()
+    }
+}
+
 // 表达式
 ast_enum!(Expr {
     BinaryExpr,
@@ -275,6 +401,7 @@ ast_enum!(Expr {
     ParenExpr,
     PostfixExpr,
     IndexVal,
+    EnumVariantExpr,
     Literal,
 });
 
@@ -324,7 +451,10 @@ impl OpNode for PostfixOp {}
 ast_node!(
     CallExpr ~ CALL_EXPR {
         name: node(Name),
+        l_paren_token: token(L_PAREN),
+        type_arg: node(Type),
         args: node(FuncRParams),
+        r_paren_token: token(R_PAREN),
     }
 );
 
@@ -348,6 +478,14 @@ ast_node!(
     }
 );
 
+// Enum 成员访问：`Color::Red`
+ast_node!(
+    EnumVariantExpr ~ ENUM_VARIANT_EXPR {
+        enum_name: nth(Name, 0),
+        variant: nth(Name, 1),
+    }
+);
+
 // PostfixExpr 中的字段访问
 ast_node!(
     FieldAccess ~ FIELD_ACCESS {
@@ -401,17 +539,24 @@ ast_node!(
 );
 
 impl Name {
+    /// 语义上的名称，原始标识符会去掉 `r#` 前缀
     pub fn var_name(&self) -> Option<String> {
-        self.ident().map(|i| i.text().to_string())
+        self.ident().map(|i| strip_raw_prefix(i.text()).to_string())
     }
     pub fn var_range(&self) -> Option<TextRange> {
         self.ident().map(|i| TextRange(i.text_range()))
     }
 }
 
+/// 去掉原始标识符的 `r#` 前缀
+pub fn strip_raw_prefix(ident: &str) -> &str {
+    ident.strip_prefix("r#").unwrap_or(ident)
+}
+
 ast_node!(Pointer ~ POINTER {
     mut_token: token(MUT_KW),
     const_token: token(CONST_KW),
+    restrict_token: token(RESTRICT_KW),
 });
 
 impl Pointer {
@@ -422,4 +567,8 @@ impl Pointer {
     pub fn is_mut(&self) -> bool {
         self.mut_token().is_some()
     }
+
+    pub fn is_restrict(&self) -> bool {
+        self.restrict_token().is_some()
+    }
 }
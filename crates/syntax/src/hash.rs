@@ -0,0 +1,49 @@
+use rowan::{NodeOrToken, WalkEvent};
+
+use crate::ast::SyntaxNode;
+use crate::syntax_kind::SyntaxKind;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a，结果不依赖随机种子，跨进程、跨版本稳定
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// 按名字而不是枚举序号写入，新增 SyntaxKind 不会改变已有树的哈希
+    fn write_kind(&mut self, kind: SyntaxKind) {
+        self.write(format!("{kind:?}").as_bytes());
+        self.write(&[0]);
+    }
+}
+
+/// 计算语法树的稳定哈希，忽略空白和注释
+///
+/// 只有空白或注释不同的两棵树哈希相同，可用于构建缓存判断文件是否需要重新编译
+pub fn ast_hash(node: &SyntaxNode) -> u64 {
+    let mut hasher = StableHasher(FNV_OFFSET_BASIS);
+    for event in node.preorder_with_tokens() {
+        match event {
+            WalkEvent::Enter(NodeOrToken::Node(n)) => {
+                hasher.write(b"(");
+                hasher.write_kind(n.kind());
+            }
+            WalkEvent::Enter(NodeOrToken::Token(t)) if !t.kind().is_trivia() => {
+                hasher.write_kind(t.kind());
+                // 写入长度避免相邻 token 拼接后产生歧义
+                hasher.write(&(t.text().len() as u32).to_le_bytes());
+                hasher.write(t.text().as_bytes());
+            }
+            WalkEvent::Leave(NodeOrToken::Node(_)) => hasher.write(b")"),
+            _ => {}
+        }
+    }
+    hasher.0
+}
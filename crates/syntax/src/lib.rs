@@ -1,10 +1,15 @@
 pub mod ast;
+mod hash;
 pub mod syntax_kind;
 pub mod visitor;
 
 pub use ast::AstNode;
 pub use ast::SyntaxNode;
 pub use ast::SyntaxToken;
+pub use hash::ast_hash;
 pub use syntax_kind::AirycLanguage;
 pub use syntax_kind::SyntaxKind;
 pub use visitor::Visitor;
+
+#[cfg(test)]
+mod test;
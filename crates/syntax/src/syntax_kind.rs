@@ -23,14 +23,23 @@ pub enum SyntaxKind {
     IF_KW,       // "if"
     ELSE_KW,     // "else"
     WHILE_KW,    // "while"
+    FOR_KW,      // "for"
     BREAK_KW,    // "break"
     CONTINUE_KW, // "continue"
     RETURN_KW,   // "return"
+    SWITCH_KW,   // "switch"
+    CASE_KW,     // "case"
+    DEFAULT_KW,  // "default"
     STRUCT_KW,   // "struct"
+    ENUM_KW,     // "enum"
+    TYPE_KW,     // "type"
     LET_KW,      // "let"
     FN_KW,       // "fn"
     MUT_KW,      // "mut"
+    RESTRICT_KW, // "restrict"
+    GOTO_KW,     // "goto"，保留字，不支持
     ATTACH_KW,   // "attach"
+    EXTERN_KW,   // "extern"
     NULL_KW,     // "null"
     TRUE_KW,     // "true"
     FALSE_KW,    // "false"
@@ -51,6 +60,8 @@ pub enum SyntaxKind {
     GT,             // >
     LTEQ,           // <=
     GTEQ,           // >=
+    LTLT,           // <<
+    GTGT,           // >>
     AMP,            // &
     AMPAMP,         // &&
     PIPEPIPE,       // ||
@@ -68,6 +79,7 @@ pub enum SyntaxKind {
     R_BRACE,        // }
     L_BRACK,        // [
     R_BRACK,        // ]
+    AT,             // @
 
     // 语法树节点
     COMP_UNIT,
@@ -78,11 +90,17 @@ pub enum SyntaxKind {
     FUNC_DEF,
     FUNC_SIGN,
     FUNC_ATTACH,
+    ATTRIBUTE,
 
     VAR_DEF,
     INIT_VAL,
+    DESIGNATOR,
+    REPEAT_COUNT,
     STRUCT_DEF,
     STRUCT_FIELD,
+    ENUM_DEF,
+    ENUM_VARIANT,
+    TYPE_ALIAS,
 
     PRIMIT_TYPE,
     TYPE,
@@ -106,15 +124,23 @@ pub enum SyntaxKind {
 
     INDEX_VAL,
     FIELD_ACCESS,
+    ENUM_VARIANT_EXPR,
 
     BLOCK,
     IF_STMT,
     WHILE_STMT,
+    FOR_STMT,
+    FOR_INIT,
+    FOR_STEP,
     ASSIGN_STMT,
     EXPR_STMT,
     BREAK_STMT,
     CONTINUE_STMT,
+    GOTO_STMT,
+    LABEL_STMT,
     RETURN_STMT,
+    SWITCH_STMT,
+    SWITCH_CASE,
 
     __LAST,
 }
@@ -135,7 +161,8 @@ impl SyntaxKind {
     pub fn is_keyword(self) -> bool {
         matches!(
             self,
-            SyntaxKind::CONST_KW
+            SyntaxKind::IMPORT_KW
+                | SyntaxKind::CONST_KW
                 | SyntaxKind::I32_KW
                 | SyntaxKind::I8_KW
                 | SyntaxKind::U8_KW
@@ -147,19 +174,43 @@ impl SyntaxKind {
                 | SyntaxKind::IF_KW
                 | SyntaxKind::ELSE_KW
                 | SyntaxKind::WHILE_KW
+                | SyntaxKind::FOR_KW
                 | SyntaxKind::BREAK_KW
                 | SyntaxKind::CONTINUE_KW
                 | SyntaxKind::RETURN_KW
+                | SyntaxKind::SWITCH_KW
+                | SyntaxKind::CASE_KW
+                | SyntaxKind::DEFAULT_KW
                 | SyntaxKind::STRUCT_KW
+                | SyntaxKind::ENUM_KW
+                | SyntaxKind::TYPE_KW
                 | SyntaxKind::LET_KW
                 | SyntaxKind::FN_KW
                 | SyntaxKind::MUT_KW
+                | SyntaxKind::RESTRICT_KW
+                | SyntaxKind::GOTO_KW
+                | SyntaxKind::ATTACH_KW
+                | SyntaxKind::EXTERN_KW
+                | SyntaxKind::NULL_KW
                 | SyntaxKind::TRUE_KW
                 | SyntaxKind::FALSE_KW
         )
     }
 
-    /// 检查是否为一元运算符：`+`, `-`, `!`, `&`
+    /// 检查是否为字面量：整数、字符、字符串、`true`/`false` 和 `null`
+    pub fn is_literal(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::INT_LITERAL
+                | SyntaxKind::CHAR_LITERAL
+                | SyntaxKind::STRING_LITERAL
+                | SyntaxKind::TRUE_KW
+                | SyntaxKind::FALSE_KW
+                | SyntaxKind::NULL_KW
+        )
+    }
+
+    /// 检查是否为一元运算符：`+`, `-`, `!`, `&`, `*`
     pub fn is_unary_op(self) -> bool {
         matches!(
             self,
@@ -186,11 +237,41 @@ impl SyntaxKind {
                 | SyntaxKind::GT
                 | SyntaxKind::LTEQ
                 | SyntaxKind::GTEQ
+                | SyntaxKind::LTLT
+                | SyntaxKind::GTGT
                 | SyntaxKind::AMPAMP
                 | SyntaxKind::PIPEPIPE
         )
     }
 
+    /// 检查是否为比较运算符：`==`, `!=`, `<`, `>`, `<=`, `>=`
+    pub fn is_comparison_op(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::EQEQ
+                | SyntaxKind::NEQ
+                | SyntaxKind::LT
+                | SyntaxKind::GT
+                | SyntaxKind::LTEQ
+                | SyntaxKind::GTEQ
+        )
+    }
+
+    /// 检查是否为逻辑运算符：`&&`, `||`
+    pub fn is_logical_op(self) -> bool {
+        matches!(self, SyntaxKind::AMPAMP | SyntaxKind::PIPEPIPE)
+    }
+
+    /// 检查是否为移位运算符：`<<`, `>>`
+    pub fn is_shift_op(self) -> bool {
+        matches!(self, SyntaxKind::LTLT | SyntaxKind::GTGT)
+    }
+
+    /// 检查是否为赋值运算符，目前只有 `=`
+    pub fn is_assignment_op(self) -> bool {
+        matches!(self, SyntaxKind::EQ)
+    }
+
     /// 检查是否是后缀运算符
     pub fn is_postfix_op(self) -> bool {
         matches!(self, SyntaxKind::ARROW | SyntaxKind::DOT)
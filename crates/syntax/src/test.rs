@@ -0,0 +1,64 @@
+use crate::SyntaxKind::{self, *};
+
+#[test]
+fn test_operator_classification() {
+    for kind in [
+        PLUS, MINUS, STAR, SLASH, PERCENT, LTLT, GTGT, AMPAMP, PIPEPIPE,
+    ] {
+        assert!(kind.is_binary_op(), "{kind:?}");
+    }
+    for kind in [EQEQ, NEQ, LT, GT, LTEQ, GTEQ] {
+        assert!(kind.is_binary_op() && kind.is_comparison_op(), "{kind:?}");
+    }
+    for kind in [PLUS, MINUS, BANG, AMP, STAR] {
+        assert!(kind.is_unary_op(), "{kind:?}");
+    }
+    assert!(AMPAMP.is_logical_op() && PIPEPIPE.is_logical_op());
+    assert!(LTLT.is_shift_op() && GTGT.is_shift_op());
+    assert!(EQ.is_assignment_op());
+
+    // `=` 不是比较运算，`!` 和 `&` 不是二元运算
+    assert!(!EQ.is_comparison_op() && !EQ.is_binary_op());
+    assert!(!BANG.is_binary_op() && !AMP.is_binary_op());
+    assert!(!EQEQ.is_assignment_op() && !EQEQ.is_unary_op());
+    assert!(!LT.is_shift_op() && !AMP.is_logical_op());
+    assert!(!DOT.is_binary_op() && DOT.is_postfix_op());
+}
+
+#[test]
+fn test_token_classification() {
+    for kind in [
+        INT_LITERAL,
+        CHAR_LITERAL,
+        STRING_LITERAL,
+        TRUE_KW,
+        FALSE_KW,
+        NULL_KW,
+    ] {
+        assert!(kind.is_literal(), "{kind:?}");
+    }
+    assert!(!IDENT.is_literal() && !LITERAL.is_literal());
+
+    for kind in [
+        LET_KW, FN_KW, IMPORT_KW, ATTACH_KW, EXTERN_KW, NULL_KW, TYPE_KW,
+    ] {
+        assert!(kind.is_keyword(), "{kind:?}");
+    }
+    assert!(!IDENT.is_keyword() && !PLUS.is_keyword());
+
+    for kind in [WHITESPACE, NEWLINE, COMMENT_LINE, COMMENT_BLOCK] {
+        assert!(kind.is_trivia(), "{kind:?}");
+    }
+    assert!(!SEMI.is_trivia() && !ERROR.is_trivia());
+}
+
+#[test]
+fn test_every_keyword_is_classified() {
+    // 所有 `*_KW` 都应当被 is_keyword 识别
+    for raw in 0..SyntaxKind::__LAST as u16 {
+        let kind = SyntaxKind::from(raw);
+        if format!("{kind:?}").ends_with("_KW") {
+            assert!(kind.is_keyword(), "{kind:?}");
+        }
+    }
+}
@@ -24,7 +24,9 @@ pub trait Visitor: DeclVisitor + FuncVisitor + StmtVisitor + ExprVisitor + Sized
     /// 遍历语法树
     fn walk(&mut self, root: &SyntaxNode) {
         let mut error_depth = 0usize;
-        for event in root.preorder() {
+        let mut skipped = None;
+        let mut preorder = root.preorder();
+        while let Some(event) = preorder.next() {
             match event {
                 WalkEvent::Enter(node) => {
                     let kind = node.kind();
@@ -34,9 +36,22 @@ pub trait Visitor: DeclVisitor + FuncVisitor + StmtVisitor + ExprVisitor + Sized
                     if error_depth > 0 {
                         continue;
                     }
-                    self.dispatch_enter(node);
+                    // 根节点总是遍历
+                    if node != *root && self.skip_subtree(&node) {
+                        // rowan 跳过子树后仍会产生该节点的 Leave 事件
+                        preorder.skip_subtree();
+                        skipped = Some(node);
+                        continue;
+                    }
+                    self.before_dispatch(&node);
+                    self.dispatch_enter(node.clone());
+                    self.after_dispatch(&node);
                 }
                 WalkEvent::Leave(node) => {
+                    if skipped.as_ref() == Some(&node) {
+                        skipped = None;
+                        continue;
+                    }
                     let kind = node.kind();
                     if kind == SyntaxKind::ERROR {
                         error_depth -= 1;
@@ -44,12 +59,25 @@ pub trait Visitor: DeclVisitor + FuncVisitor + StmtVisitor + ExprVisitor + Sized
                     if error_depth > 0 {
                         continue;
                     }
-                    self.dispatch_leave(node);
+                    self.before_dispatch(&node);
+                    self.dispatch_leave(node.clone());
+                    self.after_dispatch(&node);
                 }
             }
         }
     }
 
+    /// 进入节点前调用，返回 true 时跳过该节点及其子树（不触发 enter/leave）
+    fn skip_subtree(&mut self, _node: &SyntaxNode) -> bool {
+        false
+    }
+
+    /// 每次分发 enter/leave 事件之前调用，用于计时等统计
+    fn before_dispatch(&mut self, _node: &SyntaxNode) {}
+
+    /// 每次分发 enter/leave 事件之后调用
+    fn after_dispatch(&mut self, _node: &SyntaxNode) {}
+
     /// 分发 enter 事件到对应的子 trait 方法
     fn dispatch_enter(&mut self, node: SyntaxNode) {
         dispatch_node!(self, node, enter);
@@ -73,6 +101,11 @@ macro_rules! dispatch_node {
             SyntaxKind::STRUCT_FIELD => {
                 StructField::cast($node).map(|n| $self.enter_struct_field(n))
             }
+            SyntaxKind::ENUM_DEF => EnumDef::cast($node).map(|n| $self.enter_enum_def(n)),
+            SyntaxKind::TYPE_ALIAS => TypeAlias::cast($node).map(|n| $self.enter_type_alias(n)),
+            SyntaxKind::ENUM_VARIANT => {
+                EnumVariant::cast($node).map(|n| $self.enter_enum_variant(n))
+            }
             SyntaxKind::FUNC_DEF => FuncDef::cast($node).map(|n| $self.enter_func_def(n)),
             SyntaxKind::FUNC_SIGN => FuncSign::cast($node).map(|n| $self.enter_func_sign(n)),
             SyntaxKind::FUNC_ATTACH => FuncAttach::cast($node).map(|n| $self.enter_func_attach(n)),
@@ -87,11 +120,18 @@ macro_rules! dispatch_node {
             SyntaxKind::EXPR_STMT => ExprStmt::cast($node).map(|n| $self.enter_expr_stmt(n)),
             SyntaxKind::IF_STMT => IfStmt::cast($node).map(|n| $self.enter_if_stmt(n)),
             SyntaxKind::WHILE_STMT => WhileStmt::cast($node).map(|n| $self.enter_while_stmt(n)),
+            SyntaxKind::FOR_STMT => ForStmt::cast($node).map(|n| $self.enter_for_stmt(n)),
+            SyntaxKind::FOR_INIT => ForInit::cast($node).map(|n| $self.enter_for_init(n)),
+            SyntaxKind::FOR_STEP => ForStep::cast($node).map(|n| $self.enter_for_step(n)),
             SyntaxKind::BREAK_STMT => BreakStmt::cast($node).map(|n| $self.enter_break_stmt(n)),
             SyntaxKind::CONTINUE_STMT => {
                 ContinueStmt::cast($node).map(|n| $self.enter_continue_stmt(n))
             }
+            SyntaxKind::GOTO_STMT => GotoStmt::cast($node).map(|n| $self.enter_goto_stmt(n)),
+            SyntaxKind::LABEL_STMT => LabelStmt::cast($node).map(|n| $self.enter_label_stmt(n)),
             SyntaxKind::RETURN_STMT => ReturnStmt::cast($node).map(|n| $self.enter_return_stmt(n)),
+            SyntaxKind::SWITCH_STMT => SwitchStmt::cast($node).map(|n| $self.enter_switch_stmt(n)),
+            SyntaxKind::SWITCH_CASE => SwitchCase::cast($node).map(|n| $self.enter_switch_case(n)),
             SyntaxKind::BINARY_EXPR => BinaryExpr::cast($node).map(|n| $self.enter_binary_expr(n)),
             SyntaxKind::UNARY_EXPR => UnaryExpr::cast($node).map(|n| $self.enter_unary_expr(n)),
             SyntaxKind::POSTFIX_EXPR => {
@@ -106,6 +146,9 @@ macro_rules! dispatch_node {
             SyntaxKind::FIELD_ACCESS => {
                 FieldAccess::cast($node).map(|n| $self.enter_field_access(n))
             }
+            SyntaxKind::ENUM_VARIANT_EXPR => {
+                EnumVariantExpr::cast($node).map(|n| $self.enter_enum_variant_expr(n))
+            }
             SyntaxKind::LITERAL => Literal::cast($node).map(|n| $self.enter_literal(n)),
             _ => None,
         };
@@ -120,6 +163,11 @@ macro_rules! dispatch_node {
             SyntaxKind::STRUCT_FIELD => {
                 StructField::cast($node).map(|n| $self.leave_struct_field(n))
             }
+            SyntaxKind::ENUM_DEF => EnumDef::cast($node).map(|n| $self.leave_enum_def(n)),
+            SyntaxKind::TYPE_ALIAS => TypeAlias::cast($node).map(|n| $self.leave_type_alias(n)),
+            SyntaxKind::ENUM_VARIANT => {
+                EnumVariant::cast($node).map(|n| $self.leave_enum_variant(n))
+            }
             SyntaxKind::FUNC_DEF => FuncDef::cast($node).map(|n| $self.leave_func_def(n)),
             SyntaxKind::FUNC_SIGN => FuncSign::cast($node).map(|n| $self.leave_func_sign(n)),
             SyntaxKind::FUNC_ATTACH => FuncAttach::cast($node).map(|n| $self.leave_func_attach(n)),
@@ -134,11 +182,18 @@ macro_rules! dispatch_node {
             SyntaxKind::EXPR_STMT => ExprStmt::cast($node).map(|n| $self.leave_expr_stmt(n)),
             SyntaxKind::IF_STMT => IfStmt::cast($node).map(|n| $self.leave_if_stmt(n)),
             SyntaxKind::WHILE_STMT => WhileStmt::cast($node).map(|n| $self.leave_while_stmt(n)),
+            SyntaxKind::FOR_STMT => ForStmt::cast($node).map(|n| $self.leave_for_stmt(n)),
+            SyntaxKind::FOR_INIT => ForInit::cast($node).map(|n| $self.leave_for_init(n)),
+            SyntaxKind::FOR_STEP => ForStep::cast($node).map(|n| $self.leave_for_step(n)),
             SyntaxKind::BREAK_STMT => BreakStmt::cast($node).map(|n| $self.leave_break_stmt(n)),
             SyntaxKind::CONTINUE_STMT => {
                 ContinueStmt::cast($node).map(|n| $self.leave_continue_stmt(n))
             }
+            SyntaxKind::GOTO_STMT => GotoStmt::cast($node).map(|n| $self.leave_goto_stmt(n)),
+            SyntaxKind::LABEL_STMT => LabelStmt::cast($node).map(|n| $self.leave_label_stmt(n)),
             SyntaxKind::RETURN_STMT => ReturnStmt::cast($node).map(|n| $self.leave_return_stmt(n)),
+            SyntaxKind::SWITCH_STMT => SwitchStmt::cast($node).map(|n| $self.leave_switch_stmt(n)),
+            SyntaxKind::SWITCH_CASE => SwitchCase::cast($node).map(|n| $self.leave_switch_case(n)),
             SyntaxKind::BINARY_EXPR => BinaryExpr::cast($node).map(|n| $self.leave_binary_expr(n)),
             SyntaxKind::UNARY_EXPR => UnaryExpr::cast($node).map(|n| $self.leave_unary_expr(n)),
             SyntaxKind::POSTFIX_EXPR => {
@@ -153,6 +208,9 @@ macro_rules! dispatch_node {
             SyntaxKind::FIELD_ACCESS => {
                 FieldAccess::cast($node).map(|n| $self.leave_field_access(n))
             }
+            SyntaxKind::ENUM_VARIANT_EXPR => {
+                EnumVariantExpr::cast($node).map(|n| $self.leave_enum_variant_expr(n))
+            }
             SyntaxKind::LITERAL => Literal::cast($node).map(|n| $self.leave_literal(n)),
             _ => None,
         };
@@ -16,4 +16,13 @@ pub trait DeclVisitor {
 
     fn enter_struct_field(&mut self, _node: StructField) {}
     fn leave_struct_field(&mut self, _node: StructField) {}
+
+    fn enter_enum_def(&mut self, _node: EnumDef) {}
+    fn leave_enum_def(&mut self, _node: EnumDef) {}
+
+    fn enter_enum_variant(&mut self, _node: EnumVariant) {}
+    fn leave_enum_variant(&mut self, _node: EnumVariant) {}
+
+    fn enter_type_alias(&mut self, _node: TypeAlias) {}
+    fn leave_type_alias(&mut self, _node: TypeAlias) {}
 }
@@ -26,6 +26,9 @@ pub trait ExprVisitor {
     fn enter_field_access(&mut self, _node: FieldAccess) {}
     fn leave_field_access(&mut self, _node: FieldAccess) {}
 
+    fn enter_enum_variant_expr(&mut self, _node: EnumVariantExpr) {}
+    fn leave_enum_variant_expr(&mut self, _node: EnumVariantExpr) {}
+
     fn enter_literal(&mut self, _node: Literal) {}
     fn leave_literal(&mut self, _node: Literal) {}
 }
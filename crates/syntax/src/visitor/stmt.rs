@@ -17,12 +17,33 @@ pub trait StmtVisitor {
     fn enter_while_stmt(&mut self, _node: WhileStmt) {}
     fn leave_while_stmt(&mut self, _node: WhileStmt) {}
 
+    fn enter_for_stmt(&mut self, _node: ForStmt) {}
+    fn leave_for_stmt(&mut self, _node: ForStmt) {}
+
+    fn enter_for_init(&mut self, _node: ForInit) {}
+    fn leave_for_init(&mut self, _node: ForInit) {}
+
+    fn enter_for_step(&mut self, _node: ForStep) {}
+    fn leave_for_step(&mut self, _node: ForStep) {}
+
     fn enter_break_stmt(&mut self, _node: BreakStmt) {}
     fn leave_break_stmt(&mut self, _node: BreakStmt) {}
 
     fn enter_continue_stmt(&mut self, _node: ContinueStmt) {}
     fn leave_continue_stmt(&mut self, _node: ContinueStmt) {}
 
+    fn enter_goto_stmt(&mut self, _node: GotoStmt) {}
+    fn leave_goto_stmt(&mut self, _node: GotoStmt) {}
+
+    fn enter_label_stmt(&mut self, _node: LabelStmt) {}
+    fn leave_label_stmt(&mut self, _node: LabelStmt) {}
+
     fn enter_return_stmt(&mut self, _node: ReturnStmt) {}
     fn leave_return_stmt(&mut self, _node: ReturnStmt) {}
+
+    fn enter_switch_stmt(&mut self, _node: SwitchStmt) {}
+    fn leave_switch_stmt(&mut self, _node: SwitchStmt) {}
+
+    fn enter_switch_case(&mut self, _node: SwitchCase) {}
+    fn leave_switch_case(&mut self, _node: SwitchCase) {}
 }
@@ -5,3 +5,9 @@ pub use text_range::TextRange;
 
 mod line_index;
 pub use line_index::LineIndex;
+
+mod render;
+pub use render::render_diagnostic;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,38 @@
+use crate::{LineIndex, TextRange};
+
+/// 仿照 rustc 渲染一条诊断：源码行、范围下方的 `^` 以及消息
+///
+/// ```text
+/// 2 |     let x: i32 = y;
+///   |                  ^ message
+/// ```
+///
+/// 跨行的范围只显示第一行，`^` 延伸到行尾并以 `...` 结尾
+pub fn render_diagnostic(source: &str, range: TextRange, message: &str) -> String {
+    let line_index = LineIndex::from_text(source);
+    let start = u32::from(range.start()).min(source.len() as u32);
+    let end = u32::from(range.end()).clamp(start, source.len() as u32);
+
+    let (row, col) = line_index.get_row_column(start);
+    let line_start = line_index.get_offset(row, 0) as usize;
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |i| line_start + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    let prefix = &line[..col as usize];
+    let multiline = end as usize > line_start + line.len();
+    let span = &line[prefix.len()..(end as usize - line_start).min(line.len())];
+
+    // 制表符原样保留，保证 `^` 与源码对齐
+    let padding: String = prefix
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let carets = "^".repeat(span.chars().count().max(1));
+    let more = if multiline { "..." } else { "" };
+
+    let line_no = (row + 1).to_string();
+    let gutter = " ".repeat(line_no.len());
+    format!("{line_no} | {line}\n{gutter} | {padding}{carets}{more} {message}")
+}
@@ -0,0 +1,28 @@
+use crate::{TextRange, render_diagnostic};
+
+#[test]
+fn test_render_diagnostic() {
+    let source = "fn main() -> i32 {\n    let x: i32 = y;\n    return x;\n}\n";
+    let start = source.find("y;").unwrap() as u32;
+    let rendered = render_diagnostic(source, TextRange::new(start, start + 1), "undefined");
+    assert_eq!(
+        rendered,
+        "2 |     let x: i32 = y;\n  |                  ^ undefined"
+    );
+    // `^` 与出错的字符在同一列
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[0].find('y'), lines[1].find('^'));
+
+    // 跨行范围只显示第一行
+    let start = source.find("let").unwrap() as u32;
+    let end = source.find("x;").unwrap() as u32;
+    let rendered = render_diagnostic(source, TextRange::new(start, end), "here");
+    assert_eq!(
+        rendered,
+        "2 |     let x: i32 = y;\n  |     ^^^^^^^^^^^^^^^... here"
+    );
+
+    // 空范围仍然画一个 `^`
+    let rendered = render_diagnostic("ab", TextRange::new(2, 2), "eof");
+    assert_eq!(rendered, "1 | ab\n  |   ^ eof");
+}
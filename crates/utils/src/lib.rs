@@ -1,6 +1,6 @@
 //! 辅助函数
 
-use syntax::{AirycLanguage, AstNode, SyntaxNode};
+use syntax::{AirycLanguage, AstNode, SyntaxKind, SyntaxNode};
 
 use tools::TextRange;
 
@@ -43,6 +43,74 @@ pub fn extract_name_and_range(name_node: &syntax::ast::Name) -> Option<(String,
     Some((name, range))
 }
 
+/// 收集紧贴在节点前面的注释作为文档，去掉注释标记和公共缩进
+/// 注释与节点之间隔着空行时不算文档
+pub fn leading_comments(node: &SyntaxNode) -> Option<String> {
+    let mut comments = Vec::new();
+    let mut newlines = 0;
+    let mut prev = node.prev_sibling_or_token();
+    while let Some(rowan::NodeOrToken::Token(token)) = prev {
+        match token.kind() {
+            SyntaxKind::COMMENT_LINE | SyntaxKind::COMMENT_BLOCK => {
+                comments.push(token.text().to_string());
+                newlines = 0;
+            }
+            SyntaxKind::NEWLINE => {
+                newlines += 1;
+                if newlines > 1 {
+                    break;
+                }
+            }
+            SyntaxKind::WHITESPACE => {}
+            _ => break,
+        }
+        prev = token.prev_sibling_or_token();
+    }
+    if comments.is_empty() {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    for comment in comments.iter().rev() {
+        if let Some(text) = comment.strip_prefix("//") {
+            lines.push(text.trim_start_matches('/').trim_end().to_string());
+        } else {
+            let text = comment
+                .trim_start_matches("/*")
+                .trim_start_matches('*')
+                .trim_end_matches("*/");
+            for line in text.lines() {
+                let line = line.trim_end();
+                // 块注释中间行常见的 ` * ` 前缀
+                let line = match line.trim_start().strip_prefix('*') {
+                    Some(rest) => rest,
+                    None => line,
+                };
+                lines.push(line.to_string());
+            }
+        }
+    }
+
+    // 去掉首尾空行和公共缩进
+    while lines.first().is_some_and(|l| l.trim().is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()?;
+    let doc = lines
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or("").to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(doc)
+}
+
 /// 定义 ID 包装类型的宏，用于 arena 索引
 #[macro_export]
 macro_rules! define_id_type {
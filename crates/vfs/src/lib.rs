@@ -1,10 +1,20 @@
 use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::path::PathBuf;
 
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use thiserror::Error;
 use thunderdome::{Arena, Index};
-use tools::LineIndex;
+use tools::{LineIndex, TextRange};
+
+/// 源文件不是合法的 UTF-8
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{}: invalid UTF-8 at byte offset {offset}", path.display())]
+pub struct DecodeError {
+    pub path: PathBuf,
+    /// 第一个非法字节序列的偏移
+    pub offset: usize,
+}
 
 /// 虚拟文件系统，支持并发访问
 #[derive(Debug)]
@@ -134,6 +144,32 @@ impl Vfs {
         VfsFileMut::new(guard, **id)
     }
 
+    /// 在读锁内访问文件，锁只在闭包执行期间持有
+    ///
+    /// 短小的读取操作优先使用这个方法，避免守卫的生命周期扩散到调用方
+    pub fn with_file<R>(&self, id: &FileID, f: impl FnOnce(&VirtulFile) -> R) -> Option<R> {
+        let inner = self.inner.read();
+        inner.files.get(**id).map(f)
+    }
+
+    /// 在写锁内修改文件，锁只在闭包执行期间持有
+    ///
+    /// 修改 `text` 后需要自行更新 `line_index`，整体替换内容请用 `update_file`
+    pub fn with_file_mut<R>(&self, id: &FileID, f: impl FnOnce(&mut VirtulFile) -> R) -> Option<R> {
+        let mut inner = self.inner.write();
+        inner.files.get_mut(**id).map(f)
+    }
+
+    /// 获取文件中 `range` 范围的文本，范围越界或不在字符边界上时返回 None
+    pub fn text_slice(&self, id: &FileID, range: TextRange) -> Option<String> {
+        self.with_file(id, |file| {
+            file.text
+                .get(Range::<usize>::from(range))
+                .map(str::to_string)
+        })
+        .flatten()
+    }
+
     /// 原子添加文件到 VFS（使用绝对路径）
     pub fn new_file(&self, path: PathBuf, text: String) -> FileID {
         let mut inner = self.inner.write();
@@ -143,6 +179,21 @@ impl Vfs {
         id
     }
 
+    /// 按 UTF-8 解码文件内容后添加到 VFS，解码失败时报告第一个非法字节的偏移
+    pub fn new_file_from_bytes(
+        &self,
+        path: PathBuf,
+        bytes: Vec<u8>,
+    ) -> Result<FileID, DecodeError> {
+        match String::from_utf8(bytes) {
+            Ok(text) => Ok(self.new_file(path, text)),
+            Err(e) => Err(DecodeError {
+                path,
+                offset: e.utf8_error().valid_up_to(),
+            }),
+        }
+    }
+
     /// 原子从 VFS 中删除文件
     pub fn remove_file(&self, file_id: &FileID) -> bool {
         let mut inner = self.inner.write();
@@ -340,4 +391,80 @@ mod tests {
         assert_eq!(file1.text, "content1");
         assert_eq!(file2.text, "content2");
     }
+
+    #[test]
+    fn test_with_file() {
+        let vfs = Vfs::default();
+        let file_id = vfs.new_file(PathBuf::from("/test.airy"), "content".to_string());
+
+        let len = vfs.with_file(&file_id, |file| file.text.len());
+        assert_eq!(len, Some(7));
+
+        // 闭包返回后锁已释放，同一线程内连续加写锁不会死锁
+        vfs.with_file_mut(&file_id, |file| file.text.push_str(" 1"));
+        vfs.with_file_mut(&file_id, |file| file.text.push_str(" 2"));
+        assert!(vfs.update_file(&file_id, "new".to_string()));
+        assert_eq!(
+            vfs.with_file(&file_id, |file| file.text.clone()),
+            Some("new".to_string())
+        );
+
+        assert!(vfs.remove_file(&file_id));
+        assert_eq!(vfs.with_file(&file_id, |_| ()), None);
+        assert_eq!(vfs.with_file_mut(&file_id, |_| ()), None);
+    }
+
+    #[test]
+    fn test_text_slice() {
+        let vfs = Vfs::default();
+        let file_id = vfs.new_file(
+            PathBuf::from("/test.airy"),
+            "let 变量: i32 = 1;".to_string(),
+        );
+
+        assert_eq!(
+            vfs.text_slice(&file_id, TextRange::new(4, 10)),
+            Some("变量".to_string())
+        );
+        assert_eq!(
+            vfs.text_slice(&file_id, TextRange::new(12, 15)),
+            Some("i32".to_string())
+        );
+        // 越界和切在多字节字符中间
+        assert_eq!(vfs.text_slice(&file_id, TextRange::new(15, 100)), None);
+        assert_eq!(vfs.text_slice(&file_id, TextRange::new(5, 10)), None);
+
+        assert!(vfs.remove_file(&file_id));
+        assert_eq!(vfs.text_slice(&file_id, TextRange::new(0, 3)), None);
+    }
+
+    #[test]
+    fn test_new_file_from_bytes() {
+        let vfs = Vfs::default();
+        let path = PathBuf::from("/ok.airy");
+        let file_id = vfs
+            .new_file_from_bytes(path.clone(), "let 变量: i32;".as_bytes().to_vec())
+            .unwrap();
+        assert_eq!(vfs.get_file_id_by_path(&path), Some(file_id));
+
+        // 0xff 不可能出现在 UTF-8 中，截断的多字节字符同样非法
+        let path = PathBuf::from("/bad.airy");
+        let mut bytes = b"let x".to_vec();
+        bytes.push(0xff);
+        bytes.extend_from_slice(b": i32;");
+        assert_eq!(
+            vfs.new_file_from_bytes(path.clone(), bytes),
+            Err(DecodeError {
+                path: path.clone(),
+                offset: 5
+            })
+        );
+        assert_eq!(vfs.get_file_id_by_path(&path), None);
+
+        let mut bytes = "变量".as_bytes().to_vec();
+        bytes.pop();
+        let err = vfs.new_file_from_bytes(path, bytes).unwrap_err();
+        assert_eq!(err.offset, 3);
+        assert_eq!(err.to_string(), "/bad.airy: invalid UTF-8 at byte offset 3");
+    }
 }